@@ -0,0 +1,68 @@
+//! Measures how many codes per second are built and rendered to SVG, one by one with
+//! `Options::build` and with the batch API on one thread and on every core.
+//!
+//!     cargo bench --features rayon --bench batch
+
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rayon::ThreadPoolBuilder;
+use rounded_qr::batch::Batch;
+use rounded_qr::options::Options;
+use rounded_qr::rendering::style::Style;
+use rounded_qr::rendering::svg;
+use rounded_qr::types::Size;
+
+const CODES: usize = 2_000;
+
+fn options() -> Options {
+    Options {
+        size: Size::new(256.0, 256.0),
+        quiet_zone: 4,
+        ..Options::default()
+    }
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{name:<24} {:>8.0} codes/s ({:.2?})",
+        CODES as f64 / elapsed.as_secs_f64(),
+        elapsed
+    );
+}
+
+fn main() {
+    let payloads = (0..CODES)
+        .map(|i| format!("https://example.com/products/{i:08}"))
+        .collect::<Vec<_>>();
+    let options = options();
+    let style = Style::default();
+
+    let start = Instant::now();
+    for payload in &payloads {
+        let display_list = options.build(payload.as_str()).unwrap();
+        black_box(svg::render(&display_list, options.size, &style));
+    }
+    report("sequential", start.elapsed());
+
+    let batch = Batch::new(options, style);
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut thread_counts = vec![1];
+    if cores > 1 {
+        thread_counts.push(cores);
+    }
+    for threads in thread_counts {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        let results = pool.install(|| batch.render(&payloads, svg::render));
+        let elapsed = start.elapsed();
+        assert!(results.iter().all(Result::is_ok));
+        black_box(results);
+        let plural = if threads == 1 { "" } else { "s" };
+        report(&format!("batch, {threads} thread{plural}"), elapsed);
+    }
+}
@@ -0,0 +1,13 @@
+#!/bin/sh
+
+# Builds the JNI libraries for the Kotlin bindings in kotlin/ with cargo-ndk,
+# laid out as the jniLibs directory of an Android module.
+
+rm -rf ./jniLibs
+
+cargo ndk \
+    -t arm64-v8a \
+    -t armeabi-v7a \
+    -t x86_64 \
+    -o ./jniLibs \
+    build --release --features jni
@@ -3,22 +3,276 @@
 //  Copyright (c) 2024 ktiays. All rights reserved.
 //
 
-#include <stdlib.h>
+#ifndef ROUNDED_QR_H
+#define ROUNDED_QR_H
+
+// This file is generated by cbindgen from src/ffi.rs. Do not edit it manually.
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// The result of a fallible function.
+typedef enum {
+  RQR_STATUS_OK = 0,
+  // A required pointer argument was null.
+  RQR_STATUS_NULL_POINTER = 1,
+  // An argument was out of range.
+  RQR_STATUS_INVALID_ARGUMENT = 2,
+  // The text was not valid UTF-8.
+  RQR_STATUS_INVALID_UTF8 = 3,
+  // The data does not fit in a QR Code at the requested error correction level.
+  RQR_STATUS_DATA_TOO_LONG = 4,
+  // The modules are smaller than the minimum module size at the resolution of the image.
+  RQR_STATUS_MODULE_TOO_SMALL = 5,
+} RqrStatus;
+
+// The kind of an [`RqrOp`].
+typedef enum {
+  // Starts a new subpath at `(x, y)`.
+  RQR_OP_KIND_MOVE_TO = 0,
+  // Adds a line to `(x, y)`.
+  RQR_OP_KIND_LINE_TO = 1,
+  // Adds an arc of the circle centered at `(x, y)` with `radius`, from `start_angle`
+  // to `end_angle` in radians, in the direction given by `clockwise`. The arc starts
+  // at the current point.
+  RQR_OP_KIND_ARC_TO = 2,
+  // Adds a cubic Bézier curve to `(x, y)` with the control points `(x1, y1)` and `(x2, y2)`.
+  RQR_OP_KIND_CUBIC_TO = 3,
+  // Closes the current subpath.
+  RQR_OP_KIND_CLOSE_PATH = 4,
+} RqrOpKind;
+
+// How arcs are represented in an [`RqrOpBuffer`].
+typedef enum {
+  // Arcs are kept as `RQR_OP_KIND_ARC_TO` operations.
+  RQR_ARC_MODE_ARCS = 0,
+  // Arcs are approximated with `RQR_OP_KIND_CUBIC_TO` operations,
+  // for graphics APIs that cannot draw arcs from their center.
+  RQR_ARC_MODE_CUBICS = 1,
+} RqrArcMode;
+
+// The error correction level of a QR Code.
+typedef enum {
+  RQR_ERROR_CORRECTION_LEVEL_LOW = 0,
+  RQR_ERROR_CORRECTION_LEVEL_MEDIUM = 1,
+  RQR_ERROR_CORRECTION_LEVEL_QUARTILE = 2,
+  RQR_ERROR_CORRECTION_LEVEL_HIGH = 3,
+} RqrErrorCorrectionLevel;
+
+// The unit of a physical length.
+typedef enum {
+  RQR_LENGTH_UNIT_MILLIMETERS = 0,
+  RQR_LENGTH_UNIT_INCHES = 1,
+  // Typographic points, 72 to the inch.
+  RQR_LENGTH_UNIT_POINTS = 2,
+} RqrLengthUnit;
+
+// How the dark modules of a QR Code are drawn.
+typedef enum {
+  // Neighboring modules are merged into shapes with rounded corners.
+  RQR_MODULE_SHAPE_CONNECTED = 0,
+  // Every data module is drawn on its own, as a dot.
+  RQR_MODULE_SHAPE_DOTS = 1,
+} RqrModuleShape;
+
+// An opaque handle to a QR Code builder.
+typedef struct RqrBuilder RqrBuilder;
+
+// The radii of the rounded corners, in modules.
+typedef struct {
+  float module;
+  float concave;
+  float finder_outer;
+  float finder_inner;
+  float finder_center;
+} RqrCornerRadii;
+
+// A path operation. The fields that are not used by its kind are zero.
+typedef struct {
+  RqrOpKind kind;
+  float x;
+  float y;
+  float x1;
+  float y1;
+  float x2;
+  float y2;
+  float radius;
+  float start_angle;
+  float end_angle;
+  int clockwise;
+} RqrOp;
+
+// An array of path operations allocated by [`rqr_builder_build_ops`].
+typedef struct {
+  RqrOp *ops;
+  size_t count;
+} RqrOpBuffer;
 
 #ifdef __cplusplus
 extern "C" {
-#endif
+#endif // __cplusplus
+
+// Returns a description of the last error that occurred on the calling thread,
+// or null if no error occurred yet.
+//
+// The string is valid until the next failing call on the same thread.
+const char *rqr_last_error_message(void);
+
+// Creates a builder that encodes a copy of the given bytes.
+//
+// # Safety
+//
+// `data` must point to `length` readable bytes, or may be null if `length` is 0.
+// `out_builder` must point to writable memory for a pointer.
+RqrStatus rqr_builder_create_with_data(const uint8_t *data,
+                                       size_t length,
+                                       RqrBuilder **out_builder);
+
+// Creates a builder that encodes a copy of the given NUL-terminated UTF-8 text.
+//
+// # Safety
+//
+// `text` must point to a NUL-terminated string.
+// `out_builder` must point to writable memory for a pointer.
+RqrStatus rqr_builder_create_with_text(const char *text, RqrBuilder **out_builder);
+
+// Releases a builder. Passing null does nothing.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+// and must not be used afterwards.
+void rqr_builder_free(RqrBuilder *builder);
+
+// Sets the error correction level, one of the `RqrErrorCorrectionLevel` values.
+// Defaults to medium.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_error_correction_level(RqrBuilder *builder, int ecl);
+
+// Sets the size of the output image. Both dimensions must be finite and positive.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_size(RqrBuilder *builder, float width, float height);
+
+// Sets the width of the empty border around the QR Code, in modules, which must be
+// at most 100. Defaults to 0.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_quiet_zone(RqrBuilder *builder, uint32_t modules);
+
+// Sets the radii of the rounded corners, in modules. Every radius must be finite and
+// non-negative, and the module and concave radii must not exceed 0.5.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+// and `radii` must be null or point to a readable `RqrCornerRadii`.
+RqrStatus rqr_builder_set_corner_radii(RqrBuilder *builder, const RqrCornerRadii *radii);
 
-void *rqr_builder_create_with_data(const char *data, size_t length, int ecl,
-                                   float width, float height);
+// Sets the size of the region in the middle of the QR Code that is left empty
+// for a logo, in modules. A zero width or height disables the region, which is the default.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_logo_region(RqrBuilder *builder, uint32_t width, uint32_t height);
 
-void rqr_builder_build_path(void *builder, void *context,
-                            void (*move_to_point_fn)(void *, float, float),
-                            void (*line_to_point_fn)(void *, float, float),
-                            void (*arc_to_fn)(void *, float, float, float,
-                                              float, float, int),
-                            void (*close_path_fn)(void *));
+// Sets whether the light modules and the quiet zone are drawn instead of the dark modules,
+// for codes drawn in a light color on a dark background. Disabled by default.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_invert(RqrBuilder *builder, bool invert);
+
+// Sets how the dark modules are drawn, one of the `RqrModuleShape` values.
+// Defaults to connected.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_module_shape(RqrBuilder *builder, int shape);
+
+// Sets the resolution of the image, in units per inch, which relates its units to
+// physical lengths. The resolution must be finite and positive. Defaults to 72.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_dpi(RqrBuilder *builder, float dpi);
+
+// Sets the physical side of every module in the unit, one of the `RqrLengthUnit` values,
+// which makes the image as large as the code and its quiet zone instead of its size.
+// A length of zero removes the module size, which is the default.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_module_size(RqrBuilder *builder, float value, int unit);
+
+// Sets the smallest physical side of the modules in the unit, one of the `RqrLengthUnit`
+// values, below which building fails with `RQR_STATUS_MODULE_TOO_SMALL`.
+// A length of zero removes the minimum, which is the default.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_min_module_size(RqrBuilder *builder, float value, int unit);
+
+// Sets whether modules are a whole number of units wide, for raster images whose
+// units are pixels. Disabled by default.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+RqrStatus rqr_builder_set_snap_to_pixels(RqrBuilder *builder, bool snap_to_pixels);
+
+// Builds the QR Code and calls the functions with the path operations that outline it.
+//
+// The builder is left untouched and can be built again or reconfigured.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+// The functions are called with `context` as their first argument.
+RqrStatus rqr_builder_build_path(const RqrBuilder *builder,
+                                 void *context,
+                                 void (*move_to_point_fn)(void*, float, float),
+                                 void (*line_to_point_fn)(void*, float, float),
+                                 void (*arc_to_fn)(void*, float, float, float, float, float, int),
+                                 void (*close_path_fn)(void*));
+
+// Builds the QR Code and stores the path operations that outline it in `out_buffer`,
+// which must be released with [`rqr_op_buffer_free`]. `arc_mode` is one of the
+// `RqrArcMode` values.
+//
+// The builder is left untouched and can be built again or reconfigured.
+//
+// # Safety
+//
+// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+// and `out_buffer` must point to a writable `RqrOpBuffer`.
+RqrStatus rqr_builder_build_ops(const RqrBuilder *builder, int arc_mode, RqrOpBuffer *out_buffer);
+
+// Releases the operations of a buffer filled by [`rqr_builder_build_ops`] and resets it.
+// Passing null or an empty buffer does nothing.
+//
+// # Safety
+//
+// `buffer` must be null or point to a buffer filled by `rqr_builder_build_ops`
+// that has not been released yet.
+void rqr_op_buffer_free(RqrOpBuffer *buffer);
 
 #ifdef __cplusplus
-}
-#endif
\ No newline at end of file
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ROUNDED_QR_H */
@@ -0,0 +1,32 @@
+//
+//  Android integration of the rounded-qr Kotlin bindings.
+//
+
+package com.ktiays.roundedqr
+
+import android.graphics.Path
+import android.graphics.RectF
+
+/** Returns the outline of the code as an `android.graphics.Path`. */
+fun RoundedQR.toPath(): Path {
+    val path = Path()
+    val oval = RectF()
+    fillPath(object : PathSink {
+        override fun moveTo(x: Float, y: Float) = path.moveTo(x, y)
+
+        override fun lineTo(x: Float, y: Float) = path.lineTo(x, y)
+
+        override fun arcTo(left: Float, top: Float, right: Float, bottom: Float, startAngle: Float, sweepAngle: Float) {
+            oval.set(left, top, right, bottom)
+            path.arcTo(oval, startAngle, sweepAngle, false)
+        }
+
+        override fun cubicTo(x1: Float, y1: Float, x2: Float, y2: Float, x: Float, y: Float) =
+            path.cubicTo(x1, y1, x2, y2, x, y)
+
+        override fun quadTo(x1: Float, y1: Float, x: Float, y: Float) = path.quadTo(x1, y1, x, y)
+
+        override fun close() = path.close()
+    })
+    return path
+}
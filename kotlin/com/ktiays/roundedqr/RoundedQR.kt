@@ -0,0 +1,140 @@
+//
+//  Kotlin bindings for the `jni` feature of rounded-qr.
+//
+//  This file only depends on the Kotlin standard library, so it can be used
+//  on a desktop JVM as well as on Android. See `AndroidPath.kt` for the
+//  `android.graphics.Path` integration.
+//
+
+package com.ktiays.roundedqr
+
+/** Receives the path operations that outline a QR Code. */
+interface PathSink {
+    fun moveTo(x: Float, y: Float)
+
+    fun lineTo(x: Float, y: Float)
+
+    /**
+     * Adds an arc of the oval bounded by the rectangle, starting at [startAngle] degrees
+     * and sweeping [sweepAngle] degrees, clockwise when positive.
+     * It has the same semantics as `android.graphics.Path.arcTo(RectF, Float, Float, false)`.
+     */
+    fun arcTo(left: Float, top: Float, right: Float, bottom: Float, startAngle: Float, sweepAngle: Float)
+
+    /** Adds a cubic Bézier curve to ([x], [y]) with the control points ([x1], [y1]) and ([x2], [y2]). */
+    fun cubicTo(x1: Float, y1: Float, x2: Float, y2: Float, x: Float, y: Float)
+
+    /** Adds a quadratic Bézier curve to ([x], [y]) with the control point ([x1], [y1]). */
+    fun quadTo(x1: Float, y1: Float, x: Float, y: Float)
+
+    fun close()
+}
+
+/** Builds a QR Code with rounded modules. Options can be changed between builds. */
+class RoundedQR private constructor(private val data: ByteArray, private val isText: Boolean) {
+    enum class ErrorCorrectionLevel { LOW, MEDIUM, QUARTILE, HIGH }
+
+    /**
+     * The radii of the rounded corners, in modules. Every radius must be finite and
+     * non-negative, and [module] and [concave] must not exceed 0.5, or building throws
+     * an `IllegalArgumentException`.
+     */
+    data class CornerRadii(
+        val module: Float = 0.5f,
+        val concave: Float = 0.25f,
+        val finderOuter: Float = 2.1f,
+        val finderInner: Float = 1.3f,
+        val finderCenter: Float = 0.6f,
+    )
+
+    var errorCorrectionLevel = ErrorCorrectionLevel.MEDIUM
+
+    /** The width and height of the output. */
+    var size = 256f
+
+    /**
+     * The width of the empty border around the code, in modules. It must be between
+     * 0 and 100, or building throws an `IllegalArgumentException`.
+     */
+    var quietZone = 0
+
+    var cornerRadii = CornerRadii()
+
+    /** The size of the region in the middle of the code left empty for a logo, in modules. */
+    var logoWidth = 0
+    var logoHeight = 0
+
+    /** Returns the path operations as tagged values, see [fillPath] for the layout. */
+    fun pathOps(): FloatArray =
+        nativePathOps(
+            data, isText, errorCorrectionLevel.ordinal, size, quietZone,
+            radiiArray(), logoWidth, logoHeight,
+        )
+
+    /** Sends the outline of the code to [sink]. */
+    fun fillPath(sink: PathSink) {
+        val ops = pathOps()
+        var i = 0
+        while (i < ops.size) {
+            when (ops[i].toInt()) {
+                OP_MOVE_TO -> sink.moveTo(ops[i + 1], ops[i + 2]).also { i += 3 }
+                OP_LINE_TO -> sink.lineTo(ops[i + 1], ops[i + 2]).also { i += 3 }
+                OP_ARC_TO -> sink.arcTo(ops[i + 1], ops[i + 2], ops[i + 3], ops[i + 4], ops[i + 5], ops[i + 6])
+                    .also { i += 7 }
+                OP_CLOSE -> sink.close().also { i += 1 }
+                OP_CUBIC_TO -> sink.cubicTo(ops[i + 1], ops[i + 2], ops[i + 3], ops[i + 4], ops[i + 5], ops[i + 6])
+                    .also { i += 7 }
+                OP_QUAD_TO -> sink.quadTo(ops[i + 1], ops[i + 2], ops[i + 3], ops[i + 4]).also { i += 5 }
+                else -> error("unknown path operation ${ops[i]}")
+            }
+        }
+    }
+
+    /**
+     * Returns the code as an Android VectorDrawable resource.
+     *
+     * Colors are Android color integers (`0xAARRGGBB`); a null [background] leaves it transparent.
+     */
+    fun toVectorDrawableXml(foreground: Int = 0xFF000000.toInt(), background: Int? = 0xFFFFFFFF.toInt()): String =
+        nativeVectorDrawable(
+            data, isText, errorCorrectionLevel.ordinal, size, quietZone,
+            radiiArray(), logoWidth, logoHeight,
+            foreground, background ?: 0, background != null,
+        )
+
+    private fun radiiArray() = cornerRadii.run {
+        floatArrayOf(module, concave, finderOuter, finderInner, finderCenter)
+    }
+
+    companion object {
+        private const val OP_MOVE_TO = 0
+        private const val OP_LINE_TO = 1
+        private const val OP_ARC_TO = 2
+        private const val OP_CLOSE = 3
+        private const val OP_CUBIC_TO = 4
+        private const val OP_QUAD_TO = 5
+
+        init {
+            System.loadLibrary("rounded_qr")
+        }
+
+        /** Creates a builder that encodes the text. */
+        fun text(text: String) = RoundedQR(text.toByteArray(Charsets.UTF_8), true)
+
+        /** Creates a builder that encodes a copy of the bytes. */
+        fun binary(bytes: ByteArray) = RoundedQR(bytes.copyOf(), false)
+
+        @JvmStatic
+        private external fun nativePathOps(
+            data: ByteArray, isText: Boolean, ecl: Int, size: Float, quietZone: Int,
+            radii: FloatArray?, logoWidth: Int, logoHeight: Int,
+        ): FloatArray
+
+        @JvmStatic
+        private external fun nativeVectorDrawable(
+            data: ByteArray, isText: Boolean, ecl: Int, size: Float, quietZone: Int,
+            radii: FloatArray?, logoWidth: Int, logoHeight: Int,
+            foreground: Int, background: Int, hasBackground: Boolean,
+        ): String
+    }
+}
@@ -0,0 +1,73 @@
+import math
+
+import pytest
+
+from rounded_qr import Builder
+
+
+def test_svg_is_a_document():
+    svg = Builder("rounded-qr").svg()
+    assert svg.startswith("<svg")
+    assert 'width="256"' in svg
+
+
+def test_png_has_the_requested_size():
+    png = Builder("rounded-qr", size=64).png()
+    assert png.startswith(b"\x89PNG\r\n\x1a\n")
+    # The IHDR chunk follows the signature and starts with the width and height.
+    assert int.from_bytes(png[16:20], "big") == 64
+    assert int.from_bytes(png[20:24], "big") == 64
+
+
+def test_path_ops_trace_closed_subpaths():
+    ops = Builder("rounded-qr").path_ops()
+    assert ops[0][0] == "move_to"
+    assert ops[-1] == ("close_path",)
+    assert {op[0] for op in ops} <= {"move_to", "line_to", "arc_to", "cubic_to", "quad_to", "close_path"}
+
+
+def test_bytes_are_encoded_like_text():
+    assert Builder(b"rounded-qr").path_ops() == Builder("rounded-qr").path_ops()
+
+
+def test_square_radii_draw_no_arcs():
+    radii = dict(
+        module_radius=0,
+        concave_radius=0,
+        finder_outer_radius=0,
+        finder_inner_radius=0,
+        finder_center_radius=0,
+    )
+    ops = Builder("rounded-qr", **radii).path_ops()
+    assert all(op[0] != "arc_to" for op in ops)
+
+
+def test_background_can_be_omitted():
+    svg = Builder("rounded-qr", foreground="#ff0000", background=None).svg()
+    assert "#ff0000" in svg
+    assert "#ffffff" not in svg
+
+
+@pytest.mark.parametrize(
+    "options",
+    [
+        {"ecl": "maximum"},
+        {"size": 0},
+        {"size": math.inf},
+        {"foreground": "red"},
+        {"module_radius": math.nan},
+        {"module_radius": 0.6},
+        {"concave_radius": 0.6},
+        {"finder_outer_radius": -1},
+        {"finder_center_radius": math.inf},
+    ],
+)
+def test_invalid_options_raise_value_error(options):
+    with pytest.raises(ValueError):
+        Builder("rounded-qr", **options)
+
+
+def test_data_too_long_raises_value_error():
+    builder = Builder("a" * 8000)
+    with pytest.raises(ValueError, match="too long"):
+        builder.svg()
@@ -0,0 +1,35 @@
+from typing import List, Literal, Optional, Tuple, Union
+
+PathOp = Union[
+    Tuple[Literal["move_to"], float, float],
+    Tuple[Literal["line_to"], float, float],
+    Tuple[Literal["arc_to"], float, float, float, float, float, bool],
+    Tuple[Literal["cubic_to"], float, float, float, float, float, float],
+    Tuple[Literal["quad_to"], float, float, float, float],
+    Tuple[Literal["close_path"]],
+]
+
+class Builder:
+    """Builds rounded QR Codes."""
+
+    def __init__(
+        self,
+        data: Union[str, bytes],
+        *,
+        ecl: Literal["low", "medium", "quartile", "high", "L", "M", "Q", "H"] = "medium",
+        size: float = 256.0,
+        quiet_zone: int = 0,
+        module_radius: Optional[float] = None,
+        concave_radius: Optional[float] = None,
+        finder_outer_radius: Optional[float] = None,
+        finder_inner_radius: Optional[float] = None,
+        finder_center_radius: Optional[float] = None,
+        foreground: str = "#000000",
+        background: Optional[str] = "#ffffff",
+    ) -> None: ...
+    def svg(self) -> str:
+        """Returns a standalone SVG document of the code."""
+    def png(self) -> bytes:
+        """Returns a PNG image of the code, `size` pixels wide and high."""
+    def path_ops(self) -> List[PathOp]:
+        """Returns the outline of the code as a list of tuples."""
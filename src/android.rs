@@ -0,0 +1,436 @@
+//! JNI bindings for the `com.ktiays.roundedqr.RoundedQR` Kotlin class in `kotlin/`.
+//!
+//! The functions are plain JNI exports, so the same library can be loaded by an Android
+//! app or by a desktop JVM.
+
+use jni::objects::{JByteArray, JClass, JFloatArray};
+use jni::sys::{jboolean, jfloat, jfloatArray, jint, jstring, JNI_TRUE};
+use jni::JNIEnv;
+
+use crate::builder::Builder;
+use crate::options::Options;
+use crate::rendering::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use crate::rendering::style::{Color, Style};
+use crate::rendering::vector_drawable;
+use crate::types::{CornerRadii, ErrorCorrectionLevel, LogoRegion, Size};
+
+/// The options shared by every native method, as passed by `RoundedQR.kt`.
+struct Request {
+    data: Vec<u8>,
+    is_text: bool,
+    ecl: ErrorCorrectionLevel,
+    size: f32,
+    quiet_zone: u32,
+    radii: CornerRadii,
+    logo: LogoRegion,
+}
+
+impl Request {
+    #[allow(clippy::too_many_arguments)]
+    fn read(
+        env: &mut JNIEnv,
+        data: &JByteArray,
+        is_text: jboolean,
+        ecl: jint,
+        size: jfloat,
+        quiet_zone: jint,
+        radii: &JFloatArray,
+        logo_width: jint,
+        logo_height: jint,
+    ) -> Result<Self, String> {
+        let data = env.convert_byte_array(data).map_err(|e| e.to_string())?;
+        let ecl = match ecl {
+            0 => ErrorCorrectionLevel::Low,
+            1 => ErrorCorrectionLevel::Medium,
+            2 => ErrorCorrectionLevel::Quartile,
+            3 => ErrorCorrectionLevel::High,
+            _ => return Err(format!("invalid error correction level {ecl}")),
+        };
+        if !(size.is_finite() && size > 0.0) {
+            return Err("size must be finite and positive".to_owned());
+        }
+        let non_negative = |value: jint, name: &str| {
+            u32::try_from(value).map_err(|_| format!("{name} must not be negative"))
+        };
+        let radii = if radii.is_null() {
+            CornerRadii::default()
+        } else {
+            // Reading past the end would leave an exception pending, which must not
+            // be followed by another one.
+            let mut values = [0.0; 5];
+            if env.get_array_length(radii).map_err(|e| e.to_string())? != values.len() as jint {
+                return Err("radii must hold 5 values".to_owned());
+            }
+            env.get_float_array_region(radii, 0, &mut values)
+                .map_err(|e| e.to_string())?;
+            let [module, concave, finder_outer, finder_inner, finder_center] =
+                values.map(|value| value as f64);
+            let radii = CornerRadii {
+                module,
+                concave,
+                finder_outer,
+                finder_inner,
+                finder_center,
+            };
+            radii.validate().map_err(|e| e.to_string())?;
+            radii
+        };
+        let quiet_zone = non_negative(quiet_zone, "quietZone")?;
+        if quiet_zone > Options::MAX_QUIET_ZONE {
+            return Err(format!(
+                "quietZone must be at most {}",
+                Options::MAX_QUIET_ZONE
+            ));
+        }
+        Ok(Self {
+            data,
+            is_text: is_text == JNI_TRUE,
+            ecl,
+            size,
+            quiet_zone,
+            radii,
+            logo: LogoRegion::new(
+                non_negative(logo_width, "logoWidth")?,
+                non_negative(logo_height, "logoHeight")?,
+            ),
+        })
+    }
+
+    fn image_size(&self) -> Size {
+        Size::new(self.size, self.size)
+    }
+
+    fn build(&self) -> Result<DisplayList, String> {
+        let builder = if self.is_text {
+            let text = std::str::from_utf8(&self.data).map_err(|e| e.to_string())?;
+            Builder::text(text)
+        } else {
+            Builder::binary(&self.data)
+        };
+        builder
+            .error_correction_level(self.ecl)
+            .size(self.image_size())
+            .quiet_zone(self.quiet_zone)
+            .corner_radii(self.radii)
+            .logo_region(self.logo)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// A receiver that flattens drawing operations to the tagged values expected by
+/// `RoundedQR.fillPath`, with arcs in the shape of `android.graphics.Path.arcTo`.
+#[derive(Default)]
+struct AndroidPathOps(Vec<f32>);
+
+impl DisplayListOpReceiver for AndroidPathOps {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.0.extend([0.0, op.point.x as f32, op.point.y as f32]);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.0.extend([1.0, op.point.x as f32, op.point.y as f32]);
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        // The oval bounds, followed by the start and sweep angles in degrees.
+        self.0.extend([
+            2.0,
+            (op.center.x - op.radius) as f32,
+            (op.center.y - op.radius) as f32,
+            (op.center.x + op.radius) as f32,
+            (op.center.y + op.radius) as f32,
+            op.start_angle.to_degrees(),
+            op.sweep_angle().to_degrees() as f32,
+        ]);
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.0.extend([
+            4.0,
+            op.control1.x as f32,
+            op.control1.y as f32,
+            op.control2.x as f32,
+            op.control2.y as f32,
+            op.point.x as f32,
+            op.point.y as f32,
+        ]);
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.0.extend([
+            5.0,
+            op.control.x as f32,
+            op.control.y as f32,
+            op.point.x as f32,
+            op.point.y as f32,
+        ]);
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.0.push(3.0);
+    }
+}
+
+/// Throws an `IllegalArgumentException` with the message.
+fn throw(env: &mut JNIEnv, message: &str) {
+    // Nothing else can be reported if throwing fails, which only happens
+    // when another exception is already pending.
+    _ = env.throw_new("java/lang/IllegalArgumentException", message);
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_com_ktiays_roundedqr_RoundedQR_nativePathOps<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    data: JByteArray<'local>,
+    is_text: jboolean,
+    ecl: jint,
+    size: jfloat,
+    quiet_zone: jint,
+    radii: JFloatArray<'local>,
+    logo_width: jint,
+    logo_height: jint,
+) -> jfloatArray {
+    let result = Request::read(
+        &mut env,
+        &data,
+        is_text,
+        ecl,
+        size,
+        quiet_zone,
+        &radii,
+        logo_width,
+        logo_height,
+    )
+    .and_then(|request| request.build())
+    .and_then(|display_list| {
+        let mut ops = AndroidPathOps::default();
+        display_list.present(&mut ops);
+        let array = env
+            .new_float_array(ops.0.len() as jint)
+            .map_err(|e| e.to_string())?;
+        env.set_float_array_region(&array, 0, &ops.0)
+            .map_err(|e| e.to_string())?;
+        Ok(array)
+    });
+    match result {
+        Ok(array) => array.into_raw(),
+        Err(message) => {
+            throw(&mut env, &message);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_com_ktiays_roundedqr_RoundedQR_nativeVectorDrawable<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    data: JByteArray<'local>,
+    is_text: jboolean,
+    ecl: jint,
+    size: jfloat,
+    quiet_zone: jint,
+    radii: JFloatArray<'local>,
+    logo_width: jint,
+    logo_height: jint,
+    foreground: jint,
+    background: jint,
+    has_background: jboolean,
+) -> jstring {
+    // Colors are passed as Android color integers, in the 0xAARRGGBB layout.
+    let color = |argb: jint| {
+        let [a, r, g, b] = (argb as u32).to_be_bytes();
+        Color::rgba(r, g, b, a)
+    };
+    let style = Style {
+        foreground: color(foreground).into(),
+        finder: None,
+        background: (has_background == JNI_TRUE).then(|| color(background).into()),
+    };
+    let result = Request::read(
+        &mut env,
+        &data,
+        is_text,
+        ecl,
+        size,
+        quiet_zone,
+        &radii,
+        logo_width,
+        logo_height,
+    )
+    .and_then(|request| {
+        let display_list = request.build()?;
+        let xml = vector_drawable::render(&display_list, request.image_size(), &style);
+        env.new_string(xml).map_err(|e| e.to_string())
+    });
+    match result {
+        Ok(string) => string.into_raw(),
+        Err(message) => {
+            throw(&mut env, &message);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use jni::objects::{JObject, JString};
+    use jni::sys::JNI_FALSE;
+    use jni::{InitArgsBuilder, JavaVM};
+
+    use super::*;
+
+    /// Returns a JVM shared by the tests, which check the calls they make to it.
+    fn jvm() -> &'static JavaVM {
+        static JVM: OnceLock<JavaVM> = OnceLock::new();
+        JVM.get_or_init(|| {
+            let args = InitArgsBuilder::new()
+                .option("-Xcheck:jni")
+                .build()
+                .expect("the JVM options should be valid");
+            JavaVM::new(args).expect("a JVM should be installed")
+        })
+    }
+
+    /// Returns the message of the pending exception after checking that it is an
+    /// `IllegalArgumentException`, or `None` if there is none.
+    fn take_exception(env: &mut JNIEnv) -> Option<String> {
+        if !env.exception_check().unwrap() {
+            return None;
+        }
+        let throwable = env.exception_occurred().unwrap();
+        env.exception_clear().unwrap();
+        let class = "java/lang/IllegalArgumentException";
+        assert!(env.is_instance_of(&throwable, class).unwrap());
+        let message = env
+            .call_method(&throwable, "getMessage", "()Ljava/lang/String;", &[])
+            .and_then(|value| value.l())
+            .unwrap();
+        Some(env.get_string(&JString::from(message)).unwrap().into())
+    }
+
+    /// Calls `nativePathOps` like `RoundedQR.pathOps` does, and returns the operations
+    /// or the message of the exception it throws.
+    fn path_ops(text: &str, quiet_zone: jint, radii: Option<&[f32]>) -> Result<Vec<f32>, String> {
+        let mut env = jvm().attach_current_thread().unwrap();
+        let class = env.find_class("java/lang/Object").unwrap();
+        let data = env.byte_array_from_slice(text.as_bytes()).unwrap();
+        let radii = match radii {
+            Some(values) => {
+                let array = env.new_float_array(values.len() as jint).unwrap();
+                env.set_float_array_region(&array, 0, values).unwrap();
+                array
+            }
+            None => JFloatArray::from(JObject::null()),
+        };
+        let ops = Java_com_ktiays_roundedqr_RoundedQR_nativePathOps(
+            unsafe { env.unsafe_clone() },
+            class,
+            data,
+            JNI_TRUE,
+            1,
+            256.0,
+            quiet_zone,
+            radii,
+            0,
+            0,
+        );
+        if let Some(message) = take_exception(&mut env) {
+            return Err(message);
+        }
+        let ops = unsafe { JFloatArray::from_raw(ops) };
+        let mut values = vec![0.0; env.get_array_length(&ops).unwrap() as usize];
+        env.get_float_array_region(&ops, 0, &mut values).unwrap();
+        Ok(values)
+    }
+
+    #[test]
+    fn path_ops_match_the_builder() {
+        let ops = path_ops("rounded-qr", 0, None).unwrap();
+        let mut expected = AndroidPathOps::default();
+        Builder::text("rounded-qr")
+            .build()
+            .unwrap()
+            .present(&mut expected);
+        assert_eq!(ops, expected.0);
+    }
+
+    #[test]
+    fn radii_are_passed_in_order() {
+        let radii = [0.0, 0.0, 0.0, 0.0, 0.0];
+        let ops = path_ops("rounded-qr", 0, Some(&radii)).unwrap();
+        let mut expected = AndroidPathOps::default();
+        let display_list = Builder::text("rounded-qr")
+            .corner_radii(CornerRadii::SQUARE)
+            .build()
+            .unwrap();
+        display_list.present(&mut expected);
+        assert_eq!(ops, expected.0);
+    }
+
+    #[test]
+    fn short_radii_throw_a_single_exception() {
+        let message = path_ops("rounded-qr", 0, Some(&[0.5, 0.25])).unwrap_err();
+        assert_eq!(message, "radii must hold 5 values");
+    }
+
+    #[test]
+    fn out_of_range_radii_throw() {
+        for radii in [
+            [0.6, 0.25, 2.1, 1.3, 0.6],
+            [0.5, f32::NAN, 2.1, 1.3, 0.6],
+            [0.5, 0.25, -1.0, 1.3, 0.6],
+        ] {
+            let message = path_ops("rounded-qr", 0, Some(&radii)).unwrap_err();
+            assert!(message.contains("corner radii"), "{message}");
+        }
+    }
+
+    #[test]
+    fn out_of_range_quiet_zones_throw() {
+        let message = path_ops("rounded-qr", -1, None).unwrap_err();
+        assert_eq!(message, "quietZone must not be negative");
+        for quiet_zone in [Options::MAX_QUIET_ZONE as jint + 1, jint::MAX] {
+            let message = path_ops("rounded-qr", quiet_zone, None).unwrap_err();
+            assert_eq!(message, "quietZone must be at most 100");
+        }
+        let quiet_zone = Options::MAX_QUIET_ZONE as jint;
+        assert!(path_ops("rounded-qr", quiet_zone, None).is_ok());
+    }
+
+    #[test]
+    fn vector_drawable_is_returned_as_a_string() {
+        let mut env = jvm().attach_current_thread().unwrap();
+        let class = env.find_class("java/lang/Object").unwrap();
+        let data = env.byte_array_from_slice(b"rounded-qr").unwrap();
+        let xml = Java_com_ktiays_roundedqr_RoundedQR_nativeVectorDrawable(
+            unsafe { env.unsafe_clone() },
+            class,
+            data,
+            JNI_FALSE,
+            3,
+            128.0,
+            4,
+            JFloatArray::from(JObject::null()),
+            0,
+            0,
+            0xFF000000_u32 as jint,
+            0,
+            JNI_FALSE,
+        );
+        assert_eq!(take_exception(&mut env), None);
+        let xml: String = env
+            .get_string(&unsafe { JString::from_raw(xml) })
+            .unwrap()
+            .into();
+        assert!(xml.starts_with("<vector"), "{xml}");
+    }
+}
@@ -0,0 +1,271 @@
+use crate::builder::{Builder, Placement, Result};
+use crate::draw::{draw, is_in_finder};
+use crate::grid::ModuleGrid;
+use crate::rendering::display_list::{BeginLayer, DisplayList, DisplayListOp, Layer};
+use crate::rendering::geometry::{Point, Rect, Size};
+use crate::types::{CornerRadii, ModuleShape};
+
+/// Animates QR Codes module by module, either growing a code in from an empty image
+/// or morphing one code into another.
+///
+/// Modules enter by growing from their centers and leave by shrinking into them, starting
+/// at the center of the code and spreading outwards. Modules that are at rest are traced
+/// together like in a still image, so their rounded corners are recomputed on every frame.
+///
+/// Both codes are drawn with the size, quiet zone, corner radii and shape of the end code,
+/// so that the modules they share stay in place. Inverted tracing is not animated.
+#[derive(Debug, Clone)]
+pub struct Animation<'a> {
+    from: Option<Builder<'a>>,
+    to: Builder<'a>,
+    stagger: f64,
+}
+
+/// The modules of the codes of an animation, encoded once for all of its frames.
+struct Keyframes {
+    from: Option<(ModuleGrid, Placement)>,
+    to: ModuleGrid,
+    placement: Placement,
+    quiet_zone: i32,
+    radii: CornerRadii,
+    shape: ModuleShape,
+    stagger: f64,
+}
+
+impl<'a> Animation<'a> {
+    /// Creates an animation in which the modules of the code grow in on an empty image.
+    pub fn reveal(to: Builder<'a>) -> Self {
+        Self {
+            from: None,
+            to,
+            stagger: 0.5,
+        }
+    }
+
+    /// Creates an animation from the code of `from` to the code of `to`, in which the modules
+    /// that only belong to the first code shrink away while the ones that only belong to the
+    /// second code grow in.
+    pub fn morph(from: Builder<'a>, to: Builder<'a>) -> Self {
+        Self {
+            from: Some(from),
+            ..Self::reveal(to)
+        }
+    }
+
+    /// Sets the fraction of the animation by which the start of the outermost modules is
+    /// delayed relative to the center ones, between 0 for modules that all move together
+    /// and 1. Defaults to 0.5.
+    pub fn stagger(self, stagger: f64) -> Self {
+        Self { stagger, ..self }
+    }
+
+    pub fn get_stagger(&self) -> f64 {
+        self.stagger
+    }
+
+    /// Returns the size of the frames, which is the size of the image of the end code.
+    pub fn get_size(&self) -> Result<Size> {
+        Ok(self.to.placement()?.size)
+    }
+
+    /// Returns the display list of the frame at the time `t`, between 0 for the start
+    /// of the animation and 1 for its end.
+    pub fn frame(&self, t: f64) -> Result<DisplayList> {
+        Ok(self.keyframes()?.frame(t))
+    }
+
+    /// Returns the display lists of `count` frames evenly spaced over the animation,
+    /// including its start and its end.
+    pub fn frames(&self, count: usize) -> Result<Vec<DisplayList>> {
+        let keyframes = self.keyframes()?;
+        let last = count.saturating_sub(1).max(1) as f64;
+        Ok((0..count)
+            .map(|i| keyframes.frame(i as f64 / last))
+            .collect())
+    }
+
+    fn keyframes(&self) -> Result<Keyframes> {
+        let from = match &self.from {
+            Some(from) => {
+                let grid = from.build_modules()?;
+                let placement = self.to.placement_for(grid.size())?;
+                Some((grid, placement))
+            }
+            None => None,
+        };
+        let to = self.to.build_modules()?;
+        Ok(Keyframes {
+            from,
+            placement: self.to.placement_for(to.size())?,
+            to,
+            quiet_zone: self.to.get_quiet_zone() as i32,
+            radii: self.to.get_corner_radii(),
+            shape: self.to.get_module_shape(),
+            stagger: self.stagger.clamp(0.0, 1.0),
+        })
+    }
+}
+
+impl Keyframes {
+    /// Returns how far the module at the given position has moved at the time `t`,
+    /// from 0 before it starts to 1 once it is done, eased in and out.
+    fn progress(&self, grid: &ModuleGrid, x: i32, y: i32, t: f64) -> f64 {
+        let center = (grid.size() - 1) as f64 / 2.0;
+        let distance = (x as f64 - center).hypot(y as f64 - center);
+        let delay = self.stagger * distance / (center * std::f64::consts::SQRT_2).max(1.0);
+        let duration = (1.0 - self.stagger).max(1e-6);
+        let p = ((t - delay) / duration).clamp(0.0, 1.0);
+        p * p * (3.0 - 2.0 * p)
+    }
+
+    fn frame(&self, t: f64) -> DisplayList {
+        // Modules of the end code that are still dark in the start code do not move.
+        let shared = |x: i32, y: i32| {
+            self.from
+                .as_ref()
+                .is_some_and(|(from, _)| from.size() == self.to.size() && from.get(x, y))
+        };
+        // The modules that grow or shrink, with the placement and the size of their grid
+        // and their scale, and the grids of the modules at rest, which are traced together.
+        let mut moving = vec![];
+        let mut at_rest = vec![];
+
+        let mut to_rest = ModuleGrid::new(self.to.size());
+        for (x, y) in dark_modules(&self.to) {
+            let scale = if shared(x, y) {
+                1.0
+            } else {
+                self.progress(&self.to, x, y, t)
+            };
+            if scale >= 1.0 {
+                to_rest.set(x, y, true);
+            } else if scale > 0.0 {
+                moving.push((&self.placement, self.to.size(), x, y, scale));
+            }
+        }
+        if let Some((from, placement)) = &self.from {
+            let same_size = from.size() == self.to.size();
+            let mut from_rest = ModuleGrid::new(from.size());
+            for (x, y) in dark_modules(from) {
+                if same_size && self.to.get(x, y) {
+                    continue;
+                }
+                let scale = 1.0 - self.progress(from, x, y, t);
+                if scale >= 1.0 {
+                    // Modules of codes of the same size are traced together with the end code.
+                    if same_size {
+                        to_rest.set(x, y, true);
+                    } else {
+                        from_rest.set(x, y, true);
+                    }
+                } else if scale > 0.0 {
+                    moving.push((placement, from.size(), x, y, scale));
+                }
+            }
+            if !same_size {
+                at_rest.push((from_rest, placement));
+            }
+        }
+        at_rest.push((to_rest, &self.placement));
+
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        for (grid, placement) in &at_rest {
+            draw(
+                grid,
+                **placement,
+                self.quiet_zone,
+                self.radii,
+                self.shape,
+                false,
+                &mut recorder,
+            );
+        }
+        for layer in [Layer::Data, Layer::Finder] {
+            let mut modules = moving
+                .iter()
+                .filter(|(_, size, x, y, _)| {
+                    (layer == Layer::Finder) == is_in_finder(*x, *y, *size)
+                })
+                .peekable();
+            if modules.peek().is_some() {
+                display_list.add_op(DisplayListOp::BeginLayer(BeginLayer { layer }));
+            }
+            for &(placement, _, x, y, scale) in modules {
+                let side = placement.module_size * scale;
+                let center = placement.module_origin(
+                    (x + self.quiet_zone) as f64 + 0.5,
+                    (y + self.quiet_zone) as f64 + 0.5,
+                );
+                let rect = Rect {
+                    origin: Point::new(center.x - side / 2.0, center.y - side / 2.0),
+                    size: Size::new(side, side),
+                };
+                display_list.extend(DisplayList::rounded_rect(rect, self.radii.module * side));
+            }
+        }
+        display_list
+    }
+}
+
+/// Returns the positions of the dark modules of the grid.
+fn dark_modules(grid: &ModuleGrid) -> impl Iterator<Item = (i32, i32)> + '_ {
+    let size = grid.size();
+    (0..size)
+        .flat_map(move |y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| grid.get(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::raster::render_mask;
+
+    fn builder(text: &str) -> Builder<'_> {
+        Builder::text(text).size(Size::new(200.0, 200.0))
+    }
+
+    /// Returns whether the display list covers no pixel of a 200 by 200 image.
+    fn is_blank(display_list: &DisplayList) -> bool {
+        render_mask(display_list, 200, 200)
+            .data
+            .iter()
+            .all(|&coverage| coverage == 0)
+    }
+
+    #[test]
+    fn reveals_the_code_from_an_empty_image() {
+        let to = builder("hello");
+        let animation = Animation::reveal(to.clone());
+        assert!(is_blank(&animation.frame(0.0).unwrap()));
+        assert!(!is_blank(&animation.frame(0.5).unwrap()));
+        assert_eq!(animation.frame(1.0).unwrap(), to.build().unwrap());
+    }
+
+    #[test]
+    fn morphs_between_codes_of_the_same_size() {
+        let (from, to) = (builder("hello"), builder("world"));
+        let animation = Animation::morph(from.clone(), to.clone());
+        assert_eq!(animation.frame(0.0).unwrap(), from.build().unwrap());
+        assert_eq!(animation.frame(1.0).unwrap(), to.build().unwrap());
+    }
+
+    #[test]
+    fn morphs_between_codes_of_different_sizes() {
+        let from = builder("a code with more data than the other one");
+        let to = builder("hello");
+        let animation = Animation::morph(from.clone(), to.clone());
+        let start = render_mask(&animation.frame(0.0).unwrap(), 200, 200);
+        assert_eq!(start, render_mask(&from.build().unwrap(), 200, 200));
+        assert_eq!(animation.frame(1.0).unwrap(), to.build().unwrap());
+    }
+
+    #[test]
+    fn frames_include_the_start_and_the_end() {
+        let to = builder("hello");
+        let frames = Animation::reveal(to.clone()).frames(5).unwrap();
+        assert_eq!(frames.len(), 5);
+        assert!(is_blank(&frames[0]));
+        assert_eq!(frames[4], to.build().unwrap());
+    }
+}
@@ -0,0 +1,136 @@
+use rayon::prelude::*;
+
+use crate::builder::Result;
+use crate::draw::DrawScratch;
+use crate::options::{Options, Payload};
+use crate::rendering::display_list::DisplayList;
+use crate::rendering::style::Style;
+use crate::types::Size;
+
+/// Builds many QR Codes in parallel, with the same options and style.
+///
+/// The codes are built on the threads of the current rayon thread pool, which reuse
+/// their tracing buffers from one code to the next, and the results are returned
+/// in the order of the payloads.
+#[derive(Debug, Clone)]
+pub struct Batch {
+    options: Options,
+    style: Style,
+}
+
+impl Batch {
+    /// Creates a batch that builds codes with the options and renders them with the style.
+    pub fn new(options: Options, style: Style) -> Self {
+        Self { options, style }
+    }
+
+    pub fn get_options(&self) -> &Options {
+        &self.options
+    }
+
+    pub fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Builds a code for every payload and returns the display lists of their drawing
+    /// operations with the sizes of their images.
+    pub fn build<I>(&self, payloads: I) -> Vec<Result<(DisplayList, Size)>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Send,
+    {
+        self.map(payloads, |display_list, size| (display_list, size))
+    }
+
+    /// Builds a code for every payload and renders it with the style of the batch,
+    /// such as with [`svg::render`](crate::rendering::svg::render).
+    pub fn render<I, T, F>(&self, payloads: I, render: F) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Send,
+        T: Send,
+        F: Fn(&DisplayList, Size, &Style) -> T + Sync,
+    {
+        self.map(payloads, |display_list, size| {
+            render(&display_list, size, &self.style)
+        })
+    }
+
+    fn map<I, T, F>(&self, payloads: I, f: F) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Send,
+        T: Send,
+        F: Fn(DisplayList, Size) -> T + Sync,
+    {
+        let payloads = payloads.into_iter().collect::<Vec<_>>();
+        payloads
+            .into_par_iter()
+            .map_init(DrawScratch::default, |scratch, payload| {
+                let payload = Payload::Text(payload.as_ref());
+                let (display_list, size) = self.options.build_with(payload, scratch)?;
+                Ok(f(display_list, size))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::FailedToGenerate;
+
+    fn payloads() -> Vec<String> {
+        let mut payloads = (0..20)
+            .map(|i| format!("https://example.com/{i}"))
+            .collect::<Vec<_>>();
+        // Too long for a QR Code at any error correction level.
+        payloads.insert(7, "x".repeat(3000));
+        payloads
+    }
+
+    /// Builds every payload one after the other.
+    fn build_sequentially(
+        options: &Options,
+        payloads: &[String],
+    ) -> Vec<Result<(DisplayList, Size)>> {
+        payloads
+            .iter()
+            .map(|payload| {
+                let display_list = options.build(payload.as_str())?;
+                Ok((display_list, options.placement(payload.as_str())?.size))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builds_the_same_codes_as_sequential_builds() {
+        let options = Options {
+            quiet_zone: 2,
+            ..Options::default()
+        };
+        let batch = Batch::new(options.clone(), Style::default());
+        let payloads = payloads();
+        let expected = build_sequentially(&options, &payloads);
+        assert_eq!(batch.build(&payloads), expected);
+        // A single thread reuses its buffers for every code.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        assert_eq!(pool.install(|| batch.build(&payloads)), expected);
+    }
+
+    #[test]
+    fn reports_errors_for_each_payload() {
+        let batch = Batch::new(Options::default(), Style::default());
+        let results = batch.render(payloads(), |display_list, _, _| display_list.len());
+        for (i, result) in results.iter().enumerate() {
+            if i == 7 {
+                assert_eq!(*result, Err(FailedToGenerate::DataTooLong));
+            } else {
+                assert!(matches!(result, Ok(len) if *len > 0), "payload {i}");
+            }
+        }
+    }
+}
@@ -0,0 +1,698 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::builder::PossibleValuesParser;
+use clap::{ArgGroup, Parser, ValueEnum};
+use rounded_qr::builder::Builder;
+use rounded_qr::frame::FrameTemplate;
+use rounded_qr::options::Options;
+use rounded_qr::preset::{self, Preset};
+use rounded_qr::rendering::style::{ContrastWarning, Style};
+use rounded_qr::rendering::terminal::{TerminalCharset, TerminalOptions};
+use rounded_qr::rendering::{eps, pdf, raster, svg};
+use rounded_qr::sheet::{self, LabelTemplate, Margins, SheetLayout};
+use rounded_qr::text::Font;
+use rounded_qr::types::{Color, ErrorCorrectionLevel, Length, Size};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Generates QR Codes with rounded modules.
+#[derive(Debug, Parser)]
+#[command(version, group(ArgGroup::new("decoration").args(["frame", "caption"])))]
+struct Args {
+    /// The text to encode. Read from standard input if neither this nor `--input` is given.
+    text: Option<String>,
+
+    /// Reads the data to encode from a file, or from standard input if `-`.
+    #[arg(short, long, value_name = "FILE", conflicts_with = "text")]
+    input: Option<PathBuf>,
+
+    /// Generates one code per line of a CSV file, writing numbered files to the output directory.
+    #[arg(long, value_name = "CSV", conflicts_with_all = ["text", "input"])]
+    batch: Option<PathBuf>,
+
+    /// The 1-based CSV column that holds the data in batch mode.
+    #[arg(long, default_value_t = 1, requires = "batch")]
+    column: usize,
+
+    /// Skips the first line of the CSV file in batch mode.
+    #[arg(long, requires = "batch")]
+    skip_header: bool,
+
+    /// The prefix of the file names written in batch mode.
+    #[arg(long, default_value = "qr-", requires = "batch")]
+    prefix: String,
+
+    /// Lays out the codes of the batch on the pages of a single PDF or SVG file,
+    /// with `--caption` setting the data of every code below it.
+    #[arg(long, requires = "batch")]
+    sheet: bool,
+
+    /// The size of the pages of a sheet.
+    #[arg(long, value_enum, default_value_t = Page::A4, requires = "sheet")]
+    page: Page,
+
+    /// The number of codes on a page of a sheet, as `COLUMNSxROWS`. Defaults to `4x5`.
+    #[arg(long, value_name = "COLUMNSxROWS", value_parser = parse_grid, requires = "sheet")]
+    grid: Option<(u32, u32)>,
+
+    /// Places the codes of a sheet on the labels of a label sheet, such as `L7160` or `5160`,
+    /// which sets the page size and margins.
+    #[arg(long, value_name = "TEMPLATE", requires = "sheet", conflicts_with_all = ["grid", "page", "margin", "gutter"])]
+    labels: Option<LabelTemplate>,
+
+    /// The margins of the pages of a sheet. Defaults to `0.5in`.
+    #[arg(long, value_name = "LENGTH", requires = "sheet")]
+    margin: Option<Length>,
+
+    /// The space between the cells of a sheet. Defaults to `0.25in`.
+    #[arg(long, value_name = "LENGTH", requires = "sheet")]
+    gutter: Option<Length>,
+
+    /// The space between the edges of the cells of a sheet and their codes.
+    #[arg(long, value_name = "LENGTH", requires = "sheet")]
+    padding: Option<Length>,
+
+    /// Draws marks around the corners of the cells of a sheet to guide cutting them apart.
+    #[arg(long, requires = "sheet")]
+    crop_marks: bool,
+
+    /// The error correction level.
+    #[arg(short, long, value_enum, default_value_t = Ecl::Medium)]
+    ecl: Ecl,
+
+    /// The width and height of the image, in pixels or points, up to 10000.
+    #[arg(short, long, default_value_t = 512.0, value_parser = parse_size)]
+    size: f64,
+
+    /// The width of the empty border around the code, in modules. Defaults to 4,
+    /// or to the quiet zone of the style.
+    #[arg(short, long)]
+    quiet_zone: Option<u32>,
+
+    /// The resolution of the image, in pixels or points per inch, which relates them
+    /// to physical lengths, up to 10000.
+    #[arg(long, default_value_t = 72.0, value_parser = parse_dpi)]
+    dpi: f64,
+
+    /// The side of every module, such as `0.33mm`, `0.013in` or `1pt`,
+    /// which sizes the image to fit the code instead of `--size`.
+    #[arg(long, value_name = "LENGTH")]
+    module_size: Option<Length>,
+
+    /// Fails if the modules are smaller than this length at the resolution of the image.
+    #[arg(long, value_name = "LENGTH")]
+    min_module_size: Option<Length>,
+
+    /// Makes modules a whole number of pixels wide, adding the remaining pixels
+    /// to the quiet zone, so that raster images stay sharp.
+    #[arg(long)]
+    snap_to_pixels: bool,
+
+    /// A built-in style, which sets the shape, the colors and the quiet zone of the code.
+    /// The other options override the settings of the style.
+    #[arg(long, value_parser = PossibleValuesParser::new(Preset::NAMES), conflicts_with = "style")]
+    preset: Option<String>,
+
+    /// A style file in TOML, or in JSON if its extension is `.json`, which sets the shape,
+    /// the colors, the quiet zone, the logo region and the frame of the code.
+    /// The other options override the settings of the style.
+    #[arg(long, value_name = "FILE")]
+    style: Option<PathBuf>,
+
+    /// The color of the modules, as `#rrggbb` or `#rrggbbaa`. Defaults to black.
+    #[arg(long)]
+    foreground: Option<Color>,
+
+    /// The color behind the modules, as `#rrggbb` or `#rrggbbaa`, or `none` for a transparent one.
+    /// Defaults to white.
+    #[arg(long, value_parser = parse_background)]
+    background: Option<BackgroundColor>,
+
+    /// The color of the finder patterns in the corners, if it differs from the foreground.
+    #[arg(long)]
+    finder: Option<Color>,
+
+    /// The radius of the corners of the background, in pixels or points. Defaults to 0.
+    #[arg(long)]
+    background_radius: Option<f64>,
+
+    /// Draws the light modules and the quiet zone instead of the dark modules,
+    /// for codes drawn in a light color on a dark background.
+    #[arg(long)]
+    invert: bool,
+
+    /// Surrounds the code with a frame holding a call-to-action label.
+    #[arg(long, value_enum)]
+    frame: Option<Frame>,
+
+    /// The text of the frame label. Defaults to "SCAN ME".
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Sets a line of text below the code, or the encoded text if no value is given.
+    #[arg(long, value_name = "TEXT", num_args = 0..=1, require_equals = true)]
+    caption: Option<Option<String>>,
+
+    /// The TrueType or OpenType font of the frame label or the caption.
+    /// Defaults to the embedded font.
+    #[arg(long, value_name = "FILE")]
+    font: Option<PathBuf>,
+
+    /// The output format. Inferred from the extension of the output file if omitted.
+    #[arg(short, long, value_enum)]
+    format: Option<Format>,
+
+    /// The characters used by the terminal format.
+    #[arg(long, value_enum, default_value_t = Charset::HalfBlocks)]
+    charset: Charset,
+
+    /// Disables ANSI colors in the terminal format, drawing light modules
+    /// with the terminal foreground color for dark terminals.
+    #[arg(long)]
+    no_color: bool,
+
+    /// The output file, or the output directory in batch mode without `--sheet`.
+    /// Defaults to standard output, or to the current directory in batch mode.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Ecl {
+    Low,
+    Medium,
+    Quartile,
+    High,
+}
+
+impl From<Ecl> for ErrorCorrectionLevel {
+    fn from(ecl: Ecl) -> Self {
+        match ecl {
+            Ecl::Low => ErrorCorrectionLevel::Low,
+            Ecl::Medium => ErrorCorrectionLevel::Medium,
+            Ecl::Quartile => ErrorCorrectionLevel::Quartile,
+            Ecl::High => ErrorCorrectionLevel::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Svg,
+    Png,
+    Pdf,
+    Eps,
+    Terminal,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Page {
+    A4,
+    Letter,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Frame {
+    RoundedBorder,
+    SpeechBubble,
+    BottomBanner,
+}
+
+impl From<Frame> for FrameTemplate {
+    fn from(frame: Frame) -> Self {
+        match frame {
+            Frame::RoundedBorder => FrameTemplate::RoundedBorder,
+            Frame::SpeechBubble => FrameTemplate::SpeechBubble,
+            Frame::BottomBanner => FrameTemplate::BottomBanner,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Charset {
+    HalfBlocks,
+    Beveled,
+    Ascii,
+}
+
+impl From<Charset> for TerminalCharset {
+    fn from(charset: Charset) -> Self {
+        match charset {
+            Charset::HalfBlocks => TerminalCharset::HalfBlocks,
+            Charset::Beveled => TerminalCharset::Beveled,
+            Charset::Ascii => TerminalCharset::Ascii,
+        }
+    }
+}
+
+/// The settings shared by every code written by an invocation.
+struct Output {
+    format: Format,
+    options: Options,
+    style: Style,
+    terminal: TerminalOptions,
+    frame: Option<preset::Frame>,
+    /// The caption, which is the encoded text if `None`.
+    caption: Option<Option<String>>,
+    /// The font of the frame label or the caption.
+    font: Font,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Self::from_str(&extension, true).ok()
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+            Format::Pdf => "pdf",
+            Format::Eps => "eps",
+            Format::Terminal => "txt",
+        }
+    }
+}
+
+/// An optional background color, wrapped so that clap does not treat the argument as optional.
+#[derive(Debug, Clone, Copy)]
+struct BackgroundColor(Option<Color>);
+
+fn parse_background(s: &str) -> std::result::Result<BackgroundColor, String> {
+    if s.eq_ignore_ascii_case("none") || s.eq_ignore_ascii_case("transparent") {
+        Ok(BackgroundColor(None))
+    } else {
+        s.parse()
+            .map(|c| BackgroundColor(Some(c)))
+            .map_err(|e| format!("{e}"))
+    }
+}
+
+/// The largest image, in pixels or points, which keeps raster images within memory.
+const MAX_SIZE: f64 = 10_000.0;
+
+/// The highest resolution, in pixels or points per inch.
+const MAX_DPI: f64 = 10_000.0;
+
+fn parse_size(s: &str) -> std::result::Result<f64, String> {
+    parse_positive(s, MAX_SIZE)
+}
+
+fn parse_dpi(s: &str) -> std::result::Result<f64, String> {
+    parse_positive(s, MAX_DPI)
+}
+
+/// Parses a finite number above zero and at most `max`.
+fn parse_positive(s: &str, max: f64) -> std::result::Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 && value <= max => Ok(value),
+        _ => Err(format!("expected a number above 0 and at most {max}")),
+    }
+}
+
+fn parse_grid(s: &str) -> std::result::Result<(u32, u32), String> {
+    let error = || "expected the number of columns and rows, such as `4x5`".to_owned();
+    let (columns, rows) = s.split_once(['x', 'X']).ok_or_else(error)?;
+    match (columns.trim().parse(), rows.trim().parse()) {
+        (Ok(columns), Ok(rows)) if columns > 0 && rows > 0 => Ok((columns, rows)),
+        _ => Err(error()),
+    }
+}
+
+/// Parses the fields of a single CSV line, honoring quoted fields.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Renders the code, with `text` being the encoded data if it is text.
+fn render(builder: Builder, text: Option<&str>, output: &Output) -> Result<Vec<u8>> {
+    if output.format == Format::Terminal {
+        return Ok(builder
+            .to_terminal_string_with(&output.terminal)?
+            .into_bytes());
+    }
+    let style = &output.style;
+    let (display_list, size) = match (&output.frame, &output.caption) {
+        (Some(frame), _) => {
+            let frame = builder
+                .frame(frame.template)
+                .label(&frame.label)
+                .font(&output.font);
+            let size = frame.get_size()?;
+            (frame.build()?, size)
+        }
+        (None, Some(caption)) => {
+            let caption = caption
+                .as_deref()
+                .or(text)
+                .ok_or("the data is not text, so the caption must be given")?;
+            let caption = builder.caption(caption).font(&output.font);
+            let size = caption.get_size()?;
+            (caption.build()?, size)
+        }
+        (None, None) => {
+            let size = builder.placement()?.size;
+            (builder.build()?, size)
+        }
+    };
+    if output.format == Format::Png && size.width.max(size.height) > MAX_SIZE {
+        return Err(format!(
+            "the image would be {} by {} pixels, above the maximum of {MAX_SIZE}",
+            size.width.ceil(),
+            size.height.ceil()
+        )
+        .into());
+    }
+    let bytes = match output.format {
+        Format::Svg => svg::render(&display_list, size, style).into_bytes(),
+        Format::Png => raster::render(&display_list, size, style).encode_png()?,
+        Format::Pdf => pdf::render(&display_list, size, style),
+        Format::Eps => eps::render(&display_list, size, style).into_bytes(),
+        Format::Terminal => unreachable!(),
+    };
+    Ok(bytes)
+}
+
+/// Returns the style given by `--preset` or `--style`, with the settings of the other
+/// options applied over it.
+fn load_preset(args: &Args) -> Result<Preset> {
+    let mut preset = match (&args.preset, &args.style) {
+        (Some(name), _) => Preset::named(name).ok_or("unknown preset")?,
+        (None, Some(path)) => {
+            let contents = fs::read_to_string(path)?;
+            let is_json = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+            let preset = if is_json {
+                Preset::from_json(&contents)
+            } else {
+                Preset::from_toml(&contents)
+            };
+            preset.map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        (None, None) => Preset::default(),
+    };
+
+    let colors = &mut preset.colors;
+    if let Some(foreground) = args.foreground {
+        colors.foreground = foreground;
+    }
+    if let Some(finder) = args.finder {
+        colors.finder = Some(finder);
+    }
+    if let Some(BackgroundColor(background)) = args.background {
+        colors.background = background;
+    }
+    if let Some(radius) = args.background_radius {
+        colors.background_radius = radius;
+    }
+    if let Some(quiet_zone) = args.quiet_zone {
+        preset.quiet_zone = quiet_zone;
+    }
+    // A caption replaces the frame of the style.
+    if args.caption.is_some() {
+        preset.frame = None;
+    }
+    if let Some(frame) = args.frame {
+        preset.frame = Some(preset::Frame {
+            template: frame.into(),
+            ..preset.frame.take().unwrap_or_default()
+        });
+    }
+    if let Some(label) = &args.label {
+        let frame = preset.frame.as_mut().ok_or("`--label` requires a frame")?;
+        frame.label = label.clone();
+    }
+    preset.validate()?;
+    Ok(preset)
+}
+
+fn options(args: &Args, preset: &Preset) -> Options {
+    Options {
+        error_correction_level: args.ecl.into(),
+        size: Size::new(args.size, args.size),
+        invert: args.invert,
+        dpi: args.dpi,
+        module_size: args.module_size,
+        min_module_size: args.min_module_size,
+        snap_to_pixels: args.snap_to_pixels,
+        ..preset.options()
+    }
+}
+
+/// Lays out the codes of the payloads on a sheet.
+fn render_sheet(args: &Args, payloads: &[&str], output: &Output) -> Result<Vec<u8>> {
+    if output.frame.is_some() {
+        return Err("frames cannot be drawn on sheets".into());
+    }
+    if matches!(output.caption, Some(Some(_))) {
+        return Err(
+            "sheets caption every code with its own data, so `--caption` takes no value".into(),
+        );
+    }
+    let mut sheet = Builder::text("")
+        .options(output.options.clone())
+        .sheet()
+        .page_size(match args.page {
+            Page::A4 => sheet::A4,
+            Page::Letter => sheet::LETTER,
+        })
+        .crop_marks(args.crop_marks)
+        .captions(output.caption.is_some())
+        .font(&output.font);
+    if let Some(margin) = args.margin {
+        sheet = sheet.margins(Margins::uniform(margin.to_units(72.0)));
+    }
+    if let Some(padding) = args.padding {
+        sheet = sheet.padding(padding.to_units(72.0));
+    }
+    let layout = match (args.labels, sheet.get_layout()) {
+        (Some(template), _) => SheetLayout::Labels(template),
+        (
+            None,
+            SheetLayout::Grid {
+                columns,
+                rows,
+                gutter,
+            },
+        ) => {
+            let (columns, rows) = args.grid.unwrap_or((columns, rows));
+            let gutter = args.gutter.map_or(gutter, |gutter| gutter.to_units(72.0));
+            SheetLayout::Grid {
+                columns,
+                rows,
+                gutter,
+            }
+        }
+        (None, layout) => layout,
+    };
+    let pages = sheet.layout(layout).pages(payloads)?;
+    match output.format {
+        Format::Pdf => Ok(pdf::render_sheet(&pages, &output.style)),
+        Format::Svg => Ok(svg::render_sheet(&pages, &output.style).into_bytes()),
+        _ => Err("sheets can only be written in the PDF and SVG formats".into()),
+    }
+}
+
+fn run_batch(args: &Args, csv: &Path, output: &Output) -> Result<()> {
+    let column = args
+        .column
+        .checked_sub(1)
+        .ok_or("columns are numbered from 1")?;
+    let contents = fs::read_to_string(csv)?;
+    let payloads = contents
+        .lines()
+        .skip(args.skip_header as usize)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            csv_fields(line)
+                .into_iter()
+                .nth(column)
+                .ok_or_else(|| format!("missing column {} in line `{}`", args.column, line))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    if args.sheet {
+        let payloads = payloads.iter().map(String::as_str).collect::<Vec<_>>();
+        let bytes = render_sheet(args, &payloads, output)?;
+        return match &args.output {
+            Some(path) => Ok(fs::write(path, bytes)?),
+            None => Ok(io::stdout().write_all(&bytes)?),
+        };
+    }
+
+    let directory = args.output.clone().unwrap_or_else(|| PathBuf::from("."));
+    fs::create_dir_all(&directory)?;
+    let digits = payloads.len().to_string().len();
+    for (index, payload) in payloads.iter().enumerate() {
+        let bytes = render(
+            Builder::text(payload).options(output.options.clone()),
+            Some(payload),
+            output,
+        )
+        .map_err(|e| format!("line {}: {}", index + 1, e))?;
+        let name = format!(
+            "{}{:0digits$}.{}",
+            args.prefix,
+            index + 1,
+            output.format.extension()
+        );
+        fs::write(directory.join(name), bytes)?;
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(Format::from_path))
+        .unwrap_or(Format::Svg);
+    if format == Format::Terminal && (args.frame.is_some() || args.caption.is_some()) {
+        return Err("frames and captions cannot be drawn in the terminal format".into());
+    }
+    let preset = load_preset(&args)?;
+    if args.font.is_some() && preset.frame.is_none() && args.caption.is_none() {
+        return Err("`--font` requires a frame or a caption".into());
+    }
+    let colors = preset.colors;
+    let output = Output {
+        format,
+        options: options(&args, &preset),
+        style: preset.style(),
+        terminal: TerminalOptions {
+            charset: args.charset.into(),
+            colors: (!args.no_color).then(|| {
+                let background = colors.background.unwrap_or(Color::WHITE);
+                (colors.foreground, background)
+            }),
+            invert: args.no_color,
+        },
+        // The frame of a style is left out of the terminal format.
+        frame: preset.frame.filter(|_| format != Format::Terminal),
+        caption: args.caption.clone(),
+        font: match &args.font {
+            Some(path) => Font::from_bytes(fs::read(path)?)?,
+            None => Font::embedded().clone(),
+        },
+    };
+
+    if !matches!(output.format, Format::Terminal) {
+        match output.style.check_contrast() {
+            // Inverted codes are meant to be filled with a color lighter than the background.
+            Some(ContrastWarning::Inverted) if args.invert => {}
+            Some(warning) => eprintln!("warning: {warning}"),
+            None => {}
+        }
+    }
+
+    if let Some(csv) = &args.batch {
+        return run_batch(&args, csv, &output);
+    }
+
+    let data = match (&args.text, &args.input) {
+        (Some(text), _) => text.clone().into_bytes(),
+        (None, Some(path)) if path != Path::new("-") => fs::read(path)?,
+        _ => {
+            let mut data = vec![];
+            io::stdin().read_to_end(&mut data)?;
+            // Drop the line break that terminates piped input.
+            if data.ends_with(b"\n") {
+                data.pop();
+                if data.ends_with(b"\r") {
+                    data.pop();
+                }
+            }
+            data
+        }
+    };
+    let text = std::str::from_utf8(&data).ok();
+    let builder = match text {
+        Some(text) => Builder::text(text),
+        None => Builder::binary(&data),
+    };
+    let bytes = render(builder.options(output.options.clone()), text, &output)?;
+
+    match &args.output {
+        Some(path) => fs::write(path, bytes)?,
+        None => io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_and_resolutions() {
+        assert_eq!(parse_size("512"), Ok(512.0));
+        assert_eq!(parse_size(" 0.5 "), Ok(0.5));
+        assert_eq!(parse_size("10000"), Ok(MAX_SIZE));
+        assert_eq!(parse_dpi("300"), Ok(300.0));
+        for s in ["0", "-1", "nan", "inf", "10001", "1e9", "", "big"] {
+            assert!(parse_size(s).is_err(), "{s:?}");
+            assert!(parse_dpi(s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn parses_grids() {
+        assert_eq!(parse_grid("4x5"), Ok((4, 5)));
+        assert_eq!(parse_grid("4X5"), Ok((4, 5)));
+        assert_eq!(parse_grid(" 4 x 5 "), Ok((4, 5)));
+        for s in [
+            "0x5",
+            "4x0",
+            "4",
+            "4x",
+            "x5",
+            "4x5x6",
+            "-1x5",
+            "four by five",
+        ] {
+            assert!(parse_grid(s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn splits_csv_fields() {
+        assert_eq!(csv_fields("a,b,c"), ["a", "b", "c"]);
+        assert_eq!(csv_fields("a,,c,"), ["a", "", "c", ""]);
+        assert_eq!(csv_fields(""), [""]);
+        assert_eq!(csv_fields(r#""a,b",c"#), ["a,b", "c"]);
+        assert_eq!(csv_fields(r#""say ""hi""",x"#), [r#"say "hi""#, "x"]);
+        assert_eq!(csv_fields(r#""""#), [""]);
+        assert_eq!(csv_fields(r#"a"b"c"#), ["abc"]);
+    }
+}
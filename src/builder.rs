@@ -1,106 +1,401 @@
-use std::result::Result as StdResult;
+use alloc::borrow::Cow;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::result::Result as StdResult;
 
-use qrcodegen::QrCode;
-
-use crate::draw::draw;
+use crate::grid::ModuleGrid;
+use crate::options::{Options, Payload};
 use crate::rendering::display_list::{DisplayList, DisplayListOpReceiver};
-use crate::types::{ErrorCorrectionLevel, Size};
+use crate::rendering::geometry::Point;
+#[cfg(feature = "std")]
+use crate::rendering::terminal::{self, TerminalOptions};
+use crate::types::{CornerRadii, ErrorCorrectionLevel, Length, LogoRegion, ModuleShape, Size};
 
-/// The data source that is used as the input when generating a QR Code.
-#[derive(Debug, Clone)]
-enum DataSource<'a> {
-    Binary(&'a [u8]),
-    Text(&'a str),
+/// The error type when a QR Code cannot be built from the settings of a builder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailedToGenerate {
+    /// The supplied data cannot be converted to a QR Code.
+    DataTooLong,
+    /// The modules are smaller than the minimum module size at the resolution of the image.
+    ModuleTooSmall {
+        /// The side of the modules, in millimeters.
+        module_size: f64,
+        /// The minimum side of the modules, in millimeters.
+        min_module_size: f64,
+    },
+    /// The quiet zone is wider than [`Options::MAX_QUIET_ZONE`].
+    QuietZoneTooWide {
+        /// The width of the quiet zone, in modules.
+        quiet_zone: u32,
+    },
+}
+
+impl Display for FailedToGenerate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailedToGenerate::DataTooLong => f.write_str("the data is too long to fit in a QR Code"),
+            FailedToGenerate::ModuleTooSmall {
+                module_size,
+                min_module_size,
+            } => write!(
+                f,
+                "the modules are {module_size:.3} mm wide, below the minimum of {min_module_size:.3} mm"
+            ),
+            FailedToGenerate::QuietZoneTooWide { quiet_zone } => write!(
+                f,
+                "the quiet zone is {quiet_zone} modules wide, above the maximum of {}",
+                Options::MAX_QUIET_ZONE
+            ),
+        }
+    }
 }
 
-/// The error type when the supplied data cannot be converted to a QR Code.
-#[derive(Debug)]
-pub struct FailedToGenerate;
+impl Error for FailedToGenerate {}
 
 pub type Result<T> = StdResult<T, FailedToGenerate>;
 
+/// Where a built QR Code is drawn in its image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// The side of a module, in the units of the image.
+    pub module_size: f64,
+    /// The position of the top-left corner of the quiet zone.
+    pub origin: Point,
+    /// The size of the image.
+    pub size: Size,
+}
+
+impl Placement {
+    /// Returns the position of the top-left corner of the module at the given position
+    /// in a grid that includes the quiet zone.
+    pub fn module_origin(&self, x: f64, y: f64) -> Point {
+        Point::new(
+            self.origin.x + x * self.module_size,
+            self.origin.y + y * self.module_size,
+        )
+    }
+}
+
 /// Builds a QR Code.
+///
+/// A builder pairs the data of a code with its [`Options`], which can be taken out with
+/// [`Builder::get_options`] to build codes for other data with the same settings.
+///
+/// The data is borrowed or owned, as it is passed to the builder. A builder that owns
+/// its data is a `Builder<'static>`, which can be stored and sent to other threads,
+/// and [`Builder::into_owned`] copies borrowed data to make one.
 #[derive(Debug, Clone)]
 pub struct Builder<'a> {
-    data: DataSource<'a>,
-    ecl: ErrorCorrectionLevel,
-    size: Size,
+    input: Input<'a>,
+    options: Options,
+}
+
+/// The data of a [`Builder`], borrowed or owned.
+#[derive(Debug, Clone)]
+enum Input<'a> {
+    Binary(Cow<'a, [u8]>),
+    Text(Cow<'a, str>),
 }
 
 impl<'a> Builder<'a> {
     /// Creates a `Builder` with the specified bytes as input.
-    pub fn binary(bytes: &'a [u8]) -> Self {
-        Self::new(DataSource::Binary(bytes))
+    pub fn binary(bytes: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self::new(Input::Binary(bytes.into()))
     }
 
     /// Creates a `Builder` with the specified Unicode string as input.
-    pub fn text(str: &'a str) -> Self {
-        Self::new(DataSource::Text(str))
+    pub fn text(str: impl Into<Cow<'a, str>>) -> Self {
+        Self::new(Input::Text(str.into()))
     }
 
-    /// Sets the error correction level for the QR Code.
-    pub fn error_correction_level(self, ecl: ErrorCorrectionLevel) -> Self {
-        Self {
-            data: self.data,
-            ecl,
-            size: self.size,
+    /// Returns a builder with the same settings that owns a copy of the data.
+    pub fn into_owned(self) -> Builder<'static> {
+        let input = match self.input {
+            Input::Binary(bytes) => Input::Binary(Cow::Owned(bytes.into_owned())),
+            Input::Text(text) => Input::Text(Cow::Owned(text.into_owned())),
+        };
+        Builder {
+            input,
+            options: self.options,
         }
     }
 
+    /// Replaces all the settings of the builder with the options.
+    pub fn options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Sets the error correction level for the QR Code.
+    pub fn error_correction_level(mut self, ecl: ErrorCorrectionLevel) -> Self {
+        self.options.error_correction_level = ecl;
+        self
+    }
+
     /// Sets the size of output image.
-    pub fn size(self, size: Size) -> Self {
-        Self {
-            data: self.data,
-            ecl: self.ecl,
-            size,
+    ///
+    /// The code and its quiet zone are scaled to fit in the shorter side of the image,
+    /// unless a [module size](Builder::module_size) is set.
+    pub fn size(mut self, size: Size) -> Self {
+        self.options.size = size;
+        self
+    }
+
+    /// Sets the width of the empty border around the QR Code, in modules.
+    ///
+    /// The QR Code specification requires a quiet zone of 4 modules. Building fails with
+    /// [`FailedToGenerate::QuietZoneTooWide`] above [`Options::MAX_QUIET_ZONE`] modules.
+    pub fn quiet_zone(mut self, modules: u32) -> Self {
+        self.options.quiet_zone = modules;
+        self
+    }
+
+    /// Sets the radii of the rounded corners.
+    pub fn corner_radii(mut self, radii: CornerRadii) -> Self {
+        self.options.corner_radii = radii;
+        self
+    }
+
+    /// Sets how the dark modules are drawn, connected or as separate dots.
+    pub fn module_shape(mut self, shape: ModuleShape) -> Self {
+        self.options.shape = shape;
+        self
+    }
+
+    /// Sets the region in the middle of the QR Code that is left empty for a logo.
+    ///
+    /// The cleared modules must be recovered by error correction, so the region
+    /// should stay small compared to the capacity of the error correction level.
+    pub fn logo_region(mut self, logo: LogoRegion) -> Self {
+        self.options.logo_region = logo;
+        self
+    }
+
+    /// Sets whether the light modules are drawn instead of the dark ones.
+    ///
+    /// Inverted codes fill the light modules and the quiet zone around them, for codes
+    /// that are painted in a light color on a dark background. The corners are rounded
+    /// so that the unfilled dark modules keep the look of a regular code, which is why
+    /// a quiet zone should be set as well.
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.options.invert = invert;
+        self
+    }
+
+    /// Sets the resolution of the image, in units per inch, which relates its units to
+    /// physical lengths. Defaults to 72, which makes the units points.
+    pub fn dpi(mut self, dpi: f64) -> Self {
+        self.options.dpi = dpi;
+        self
+    }
+
+    /// Sets the physical side of every module, which makes the image as large as
+    /// the code and its quiet zone instead of the [size](Builder::size) of the builder.
+    pub fn module_size(mut self, module_size: Length) -> Self {
+        self.options.module_size = Some(module_size);
+        self
+    }
+
+    /// Sets the smallest physical side of the modules, below which building fails
+    /// with [`FailedToGenerate::ModuleTooSmall`], such as the smallest module
+    /// a printer or a scanner can resolve.
+    pub fn min_module_size(mut self, min_module_size: Length) -> Self {
+        self.options.min_module_size = Some(min_module_size);
+        self
+    }
+
+    /// Sets whether modules are a whole number of units wide, for raster images
+    /// whose units are pixels.
+    ///
+    /// The modules take the largest whole size at which the code fits in the image,
+    /// and the code is centered with the remaining pixels added to the quiet zone.
+    /// Images with less than a pixel per module keep modules of fractional sizes.
+    /// A [module size](Builder::module_size) is rounded to the nearest whole size.
+    pub fn snap_to_pixels(mut self, snap_to_pixels: bool) -> Self {
+        self.options.snap_to_pixels = snap_to_pixels;
+        self
+    }
+
+    pub fn get_payload(&self) -> Payload<'_> {
+        match &self.input {
+            Input::Binary(bytes) => Payload::Binary(bytes),
+            Input::Text(text) => Payload::Text(text),
         }
     }
 
+    pub fn get_options(&self) -> &Options {
+        &self.options
+    }
+
     pub fn get_size(&self) -> Size {
-        self.size
+        self.options.size
     }
 
-    pub fn build_with_receiver<R>(self, receiver: &mut R) -> Result<()>
+    pub fn get_quiet_zone(&self) -> u32 {
+        self.options.quiet_zone
+    }
+
+    pub fn get_corner_radii(&self) -> CornerRadii {
+        self.options.corner_radii
+    }
+
+    pub fn get_module_shape(&self) -> ModuleShape {
+        self.options.shape
+    }
+
+    pub fn get_logo_region(&self) -> LogoRegion {
+        self.options.logo_region
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.options.invert
+    }
+
+    pub fn get_dpi(&self) -> f64 {
+        self.options.dpi
+    }
+
+    pub fn get_module_size(&self) -> Option<Length> {
+        self.options.module_size
+    }
+
+    pub fn get_min_module_size(&self) -> Option<Length> {
+        self.options.min_module_size
+    }
+
+    pub fn is_snapped_to_pixels(&self) -> bool {
+        self.options.snap_to_pixels
+    }
+
+    /// Encodes the QR Code and returns where it is drawn, including the size of the image.
+    pub fn placement(&self) -> Result<Placement> {
+        self.options.placement(self.get_payload())
+    }
+
+    /// Builds the QR Code and returns the display list of its drawing operations.
+    pub fn build(&self) -> Result<DisplayList> {
+        self.options.build(self.get_payload())
+    }
+
+    /// Builds the QR Code and returns its modules, surrounded by the quiet zone.
+    pub fn build_module_grid(&self) -> Result<ModuleGrid> {
+        self.options.build_module_grid(self.get_payload())
+    }
+
+    /// Builds the QR Code and draws it as text for display in a terminal,
+    /// dark modules on a light background.
+    #[cfg(feature = "std")]
+    pub fn to_terminal_string(&self) -> Result<String> {
+        self.to_terminal_string_with(&TerminalOptions::default())
+    }
+
+    /// Builds the QR Code and draws it as text for display in a terminal, with the given options.
+    #[cfg(feature = "std")]
+    pub fn to_terminal_string_with(&self, options: &TerminalOptions) -> Result<String> {
+        let grid = self.build_module_grid()?;
+        Ok(terminal::render(&grid, options))
+    }
+
+    /// Builds the QR Code and sends its drawing operations to the receiver as they are
+    /// produced, without recording them in a display list first.
+    ///
+    /// Nothing is sent if the code cannot be built.
+    pub fn build_with_receiver<R>(&self, receiver: &mut R) -> Result<()>
     where
-        R: DisplayListOpReceiver,
+        R: DisplayListOpReceiver + ?Sized,
     {
-        let display_list = self.build_display_list()?;
-        display_list.present(receiver);
-        Ok(())
+        self.options
+            .build_with_receiver(self.get_payload(), receiver)
     }
 }
 
 impl<'a> Builder<'a> {
-    fn new(data: DataSource<'a>) -> Self {
+    fn new(input: Input<'a>) -> Self {
         Self {
-            data,
-            ecl: ErrorCorrectionLevel::Medium,
-            size: Size {
-                width: 256.0,
-                height: 256.0,
-            },
+            input,
+            options: Options::default(),
         }
     }
 
-    fn build_qr_code(&self) -> StdResult<QrCode, ()> {
-        let ecl = self.ecl;
-        let result = match self.data {
-            DataSource::Binary(data) => QrCode::encode_binary(data, ecl),
-            DataSource::Text(text) => QrCode::encode_text(text, ecl),
-        };
-        result.map_err(|_| ())
+    /// Returns the builder with its settings and the text as its input.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_text(self, text: &'a str) -> Self {
+        Self {
+            input: Input::Text(Cow::Borrowed(text)),
+            ..self
+        }
+    }
+
+    /// Encodes the input data to QR Code modules, clearing the logo region.
+    #[cfg(feature = "std")]
+    pub(crate) fn build_modules(&self) -> Result<ModuleGrid> {
+        self.options.build_modules(self.get_payload())
+    }
+
+    /// Returns where a code with the given number of modules per side is drawn.
+    #[cfg(feature = "std")]
+    pub(crate) fn placement_for(&self, code_size: i32) -> Result<Placement> {
+        self.options.placement_for(code_size)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
 
-    fn build_display_list(&self) -> Result<DisplayList> {
-        let size = self.size;
-        // Encode the input data to QR Code modules.
-        let code = self.build_qr_code().map_err(|_| FailedToGenerate)?;
+    use super::*;
 
-        let mut display_list = DisplayList::new();
-        let mut recorder = display_list.begin_recording();
-        // Produce draw calls for the image derived from the code.
-        draw(code, size, &mut recorder);
+    #[test]
+    fn builders_that_own_their_data_move_across_threads() {
+        let expected = Builder::text("rounded-qr").quiet_zone(2).build();
+        let builder: Builder<'static> = Builder::text(String::from("rounded-qr")).quiet_zone(2);
+        let built = thread::spawn(move || builder.build()).join().unwrap();
+        assert_eq!(built, expected);
+    }
+
+    #[test]
+    fn owned_builders_outlive_borrowed_data() {
+        let text = String::from("rounded-qr");
+        let builder = Builder::text(&text).invert(true).into_owned();
+        drop(text);
+        assert_eq!(builder.get_payload(), Payload::Text("rounded-qr"));
+        assert_eq!(
+            builder.build(),
+            Builder::text("rounded-qr").invert(true).build()
+        );
+    }
 
-        Ok(display_list)
+    #[test]
+    fn options_are_shared_between_threads() {
+        let options = Options {
+            quiet_zone: 2,
+            ..Options::default()
+        };
+        let expected = options.build("rounded-qr");
+        let handles = (0..4)
+            .map(|_| {
+                let options = options.clone();
+                thread::spawn(move || options.build("rounded-qr"))
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_quiet_zones_above_the_maximum() {
+        for quiet_zone in [Options::MAX_QUIET_ZONE + 1, 40_000, u32::MAX] {
+            let error = FailedToGenerate::QuietZoneTooWide { quiet_zone };
+            let builder = Builder::text("x").quiet_zone(quiet_zone);
+            assert_eq!(builder.build().unwrap_err(), error);
+            assert_eq!(builder.clone().invert(true).build().unwrap_err(), error);
+            assert_eq!(builder.build_module_grid().unwrap_err(), error);
+            assert_eq!(builder.to_terminal_string().unwrap_err(), error);
+            assert_eq!(builder.placement().unwrap_err(), error);
+        }
+        let builder = Builder::text("x").quiet_zone(Options::MAX_QUIET_ZONE);
+        let grid = builder.build_module_grid().unwrap();
+        assert_eq!(grid.size(), 21 + 2 * Options::MAX_QUIET_ZONE as i32);
+        assert!(builder.invert(true).build().is_ok());
     }
 }
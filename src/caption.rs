@@ -0,0 +1,169 @@
+use crate::builder::{Builder, Placement, Result};
+use crate::rendering::display_list::DisplayList;
+use crate::rendering::geometry::{Point, Rect, Size};
+use crate::text::Font;
+
+/// Builds a QR Code with a single line of text below it, such as the encoded URL.
+///
+/// The caption is centered below the quiet zone and made smaller if it is wider than the code.
+/// The code keeps the size of the builder it is made from, and [`CaptionBuilder::get_size`]
+/// returns the size of the code and its caption.
+#[derive(Debug, Clone)]
+pub struct CaptionBuilder<'a> {
+    builder: Builder<'a>,
+    text: &'a str,
+    font: &'a Font,
+    font_size: Option<f64>,
+}
+
+impl<'a> CaptionBuilder<'a> {
+    /// Creates a caption below the QR Code of the builder, set in the embedded font.
+    pub fn new(builder: Builder<'a>, text: &'a str) -> Self {
+        Self {
+            builder,
+            text,
+            font: Font::embedded(),
+            font_size: None,
+        }
+    }
+
+    pub fn text(self, text: &'a str) -> Self {
+        Self { text, ..self }
+    }
+
+    /// Sets the font of the caption.
+    pub fn font(self, font: &'a Font) -> Self {
+        Self { font, ..self }
+    }
+
+    /// Sets the largest font size of the caption, which defaults to
+    /// a sixteenth of the shorter side of the image of the code.
+    pub fn font_size(self, font_size: f64) -> Self {
+        Self {
+            font_size: Some(font_size),
+            ..self
+        }
+    }
+
+    pub fn get_text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn get_font_size(&self) -> Option<f64> {
+        self.font_size
+    }
+
+    /// Returns the size of the code and its caption.
+    pub fn get_size(&self) -> Result<Size> {
+        let (placement, _) = self.layout()?;
+        let font_size = self.font_size_for(&placement);
+        Ok(Size::new(
+            placement.size.width,
+            placement.size.height + line_height(font_size),
+        ))
+    }
+
+    /// Encodes the QR Code and returns where it is drawn and its number of modules per side,
+    /// quiet zone excluded.
+    fn layout(&self) -> Result<(Placement, i32)> {
+        let modules = self.builder.build_modules()?;
+        Ok((self.builder.placement_for(modules.size())?, modules.size()))
+    }
+
+    /// Sizes the image of the code so that the code and its caption fit in the given size.
+    pub(crate) fn fit(self, size: Size) -> Self {
+        let side = match self.font_size {
+            None => size
+                .width
+                .min(size.height / (1.0 + line_height(1.0 / 16.0))),
+            Some(font_size) => size.width.min(size.height - line_height(font_size)),
+        }
+        .max(0.0);
+        Self {
+            builder: self.builder.size(Size::new(side, side)),
+            ..self
+        }
+    }
+
+    fn font_size_for(&self, placement: &Placement) -> f64 {
+        let side = placement.size.width.min(placement.size.height);
+        self.font_size.unwrap_or(side / 16.0)
+    }
+
+    /// Builds the QR Code and its caption and returns the display list of their drawing operations.
+    ///
+    /// The caption is drawn first, in the [data layer](crate::rendering::display_list::Layer::Data).
+    pub fn build(self) -> Result<DisplayList> {
+        let (placement, modules) = self.layout()?;
+        let font_size = self.font_size_for(&placement);
+        // The caption is as wide as the code, without the quiet zone.
+        let quiet_zone = self.builder.get_quiet_zone() as f64;
+        let left = placement.module_origin(quiet_zone, 0.0).x;
+        let area = Rect {
+            origin: Point::new(left, placement.size.height),
+            size: Size::new(
+                modules as f64 * placement.module_size,
+                line_height(font_size),
+            ),
+        };
+
+        let mut display_list = self.font.centered_text_outline(self.text, font_size, area);
+        display_list.extend(self.builder.build()?);
+        Ok(display_list)
+    }
+}
+
+/// Returns the height of the band below the code that holds a caption of the given font size.
+fn line_height(font_size: f64) -> f64 {
+    font_size * 1.6
+}
+
+impl<'a> Builder<'a> {
+    /// Returns a builder of the QR Code with the text as a caption below it.
+    pub fn caption(self, text: &'a str) -> CaptionBuilder<'a> {
+        CaptionBuilder::new(self, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::raster::render_mask;
+
+    /// Builds the code with the caption and returns the horizontal span of the pixels
+    /// covered below the code, along with the span of the code without its quiet zone.
+    fn caption_span(caption: &str) -> ((f64, f64), (f64, f64)) {
+        let builder = Builder::text("https://example.com").size(Size::new(256.0, 256.0));
+        let placement = builder.placement().unwrap();
+        let left = placement
+            .module_origin(builder.get_quiet_zone() as f64, 0.0)
+            .x;
+        let code = (left, placement.size.width - left);
+
+        let caption = builder.caption(caption);
+        let size = caption.get_size().unwrap();
+        let (width, height) = (size.width.ceil() as usize, size.height.ceil() as usize);
+        let mask = &render_mask(&caption.build().unwrap(), width, height);
+        let covered = (placement.size.height.ceil() as usize..height)
+            .flat_map(|y| (0..width).filter(move |&x| mask.coverage(x, y) >= 128))
+            .collect::<Vec<_>>();
+        let min = *covered.iter().min().unwrap() as f64;
+        let max = (*covered.iter().max().unwrap() + 1) as f64;
+        ((min, max), code)
+    }
+
+    #[test]
+    fn centers_the_caption_under_the_code() {
+        let ((min, max), (left, right)) = caption_span("SCAN ME");
+        assert!(left < min && max < right);
+        assert!(((min + max) / 2.0 - 128.0).abs() <= 1.0, "{min}..{max}");
+    }
+
+    #[test]
+    fn shrinks_long_captions_to_the_width_of_the_code() {
+        let caption = "https://example.com/a/caption/far/wider/than/the/code";
+        let ((min, max), (left, right)) = caption_span(caption);
+        assert!(left - 1.0 <= min && max <= right + 1.0, "{min}..{max}");
+        assert!(max - min > 0.95 * (right - left), "{min}..{max}");
+    }
+}
@@ -1,13 +1,18 @@
+use alloc::vec::Vec;
 use core::f32;
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use core::hash::Hash;
 
 use bitflags::bitflags;
+use foldhash::fast::FixedState;
 use itertools::iproduct;
-use qrcodegen::QrCode;
 
-use crate::rendering::display_list::DisplayListRecorder;
-use crate::rendering::geometry::{Point, Size};
+use crate::builder::Placement;
+use crate::grid::ModuleGrid;
+use crate::rendering::display_list::{
+    ArcTo, BeginLayer, ClosePath, DisplayListOpReceiver, Layer, LineTo, MoveTo,
+};
+use crate::rendering::geometry::Point;
+use crate::types::{CornerRadii, ModuleShape};
 
 type Id = usize;
 
@@ -159,13 +164,141 @@ impl Segment {
     }
 }
 
-/// Draws the given QR Code.
-pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder) {
-    let mut unit_map = HashMap::new();
-    let mut unions = HashMap::new();
+/// How a segment of a traced path is shortened to make room for the rounded corner at its end.
+#[derive(Debug, Default, Clone, Copy)]
+struct DrawAdj {
+    start_offset: f64,
+    end_offset: f64,
+    corner_radius: f64,
 
-    for (x, y) in iproduct!(0..code.size(), 0..code.size()) {
-        if !code.get_module(x, y) {
+    /// A signum representation of the direction of the corner.
+    corner_direction: (f64, f64),
+    clockwise: bool,
+}
+
+/// Maps with a fixed seed, since the order in which they are iterated decides the order
+/// of the traced paths, which must be the same every time a code is drawn.
+type HashMap<K, V> = hashbrown::HashMap<K, V, FixedState>;
+type HashSet<T> = hashbrown::HashSet<T, FixedState>;
+
+/// The buffers used while tracing a QR Code, which can be reused to draw many codes
+/// without allocating for every one of them.
+#[derive(Debug, Default)]
+pub(crate) struct DrawScratch {
+    /// The grid of light modules, when the code is inverted.
+    inverted: ModuleGrid,
+    unit_map: HashMap<(i32, i32), Unit>,
+    unions: HashMap<Id, HashSet<(i32, i32)>>,
+    /// Empty sets that held the units of unions, kept to hold the units of later ones.
+    spare_sets: Vec<HashSet<(i32, i32)>>,
+    /// The ids of the unions and the units of the traced union, in sorted order, since
+    /// the order in which reused maps and sets iterate depends on their capacity.
+    union_ids: Vec<Id>,
+    units: Vec<(i32, i32)>,
+    path: Vec<Segment>,
+    complete: HashSet<(i32, i32)>,
+    corners: HashMap<usize, DrawAdj>,
+}
+
+/// Returns `true` if the module lies in one of the finder patterns of a QR Code of the given size.
+pub(crate) fn is_in_finder(x: i32, y: i32, size: i32) -> bool {
+    let near = |v: i32| (0..7).contains(&v);
+    let far = |v: i32| (size - 7..size).contains(&v);
+    (near(x) && near(y)) || (far(x) && near(y)) || (near(x) && far(y))
+}
+
+/// Returns the radius of the corner at the given point if it is a corner of the
+/// rings or the center of a finder pattern, which are rounded more than modules.
+fn finder_corner_radius(point: (i32, i32), size: i32, radii: CornerRadii) -> Option<f64> {
+    let rings = [
+        (0, radii.finder_outer),
+        (1, radii.finder_inner),
+        (2, radii.finder_center),
+    ];
+    for (left, top) in [(0, 0), (size - 7, 0), (0, size - 7)] {
+        let (dx, dy) = (point.0 - left, point.1 - top);
+        for (inset, radius) in rings {
+            if (dx == inset || dx == 7 - inset) && (dy == inset || dy == 7 - inset) {
+                return Some(radius);
+            }
+        }
+    }
+    None
+}
+
+/// Draws the modules of the given QR Code, surrounded by `quiet_zone` modules of empty space.
+///
+/// If `invert` is `true`, the light modules and the quiet zone are drawn instead,
+/// rounded so that the unfilled dark modules keep the look of a regular code.
+#[cfg(feature = "std")]
+pub(crate) fn draw<R>(
+    code: &ModuleGrid,
+    placement: Placement,
+    quiet_zone: i32,
+    radii: CornerRadii,
+    shape: ModuleShape,
+    invert: bool,
+    receiver: &mut R,
+) where
+    R: DisplayListOpReceiver + ?Sized,
+{
+    draw_with(
+        &mut DrawScratch::default(),
+        code,
+        placement,
+        quiet_zone,
+        radii,
+        shape,
+        invert,
+        receiver,
+    );
+}
+
+/// Draws the modules of the given QR Code like [`draw`], using the buffers of `scratch`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_with<R>(
+    scratch: &mut DrawScratch,
+    code: &ModuleGrid,
+    placement: Placement,
+    quiet_zone: i32,
+    radii: CornerRadii,
+    shape: ModuleShape,
+    invert: bool,
+    receiver: &mut R,
+) where
+    R: DisplayListOpReceiver + ?Sized,
+{
+    let DrawScratch {
+        inverted,
+        unit_map,
+        unions,
+        spare_sets,
+        union_ids,
+        units,
+        path,
+        complete,
+        corners,
+    } = scratch;
+    unit_map.clear();
+    for (_, mut units) in unions.drain() {
+        units.clear();
+        spare_sets.push(units);
+    }
+
+    // When inverted, the quiet zone is part of the traced grid, so the coordinates
+    // of the code within the grid are offset by its width.
+    let (grid, offset) = if invert {
+        inverted.copy_with_quiet_zone(code, quiet_zone);
+        for (x, y) in iproduct!(0..inverted.size(), 0..inverted.size()) {
+            inverted.set(x, y, !inverted.get(x, y));
+        }
+        (&*inverted, quiet_zone)
+    } else {
+        (code, 0)
+    };
+
+    for (x, y) in iproduct!(0..grid.size(), 0..grid.size()) {
+        if !grid.get(x, y) {
             continue;
         }
 
@@ -177,17 +310,27 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
             union_id,
         };
         unit_map.insert((x, y), unit);
-        unions.insert(union_id, vec![(x, y)].into_iter().collect::<HashSet<_>>());
+        let mut units = spare_sets.pop().unwrap_or_default();
+        units.insert((x, y));
+        unions.insert(union_id, units);
     }
 
-    for (x, y) in iproduct!(0..code.size(), 0..code.size()) {
+    // Dots keep the data modules apart, so only the modules of the finder patterns are merged.
+    let separate = shape == ModuleShape::Dots && !invert;
+    for (x, y) in iproduct!(0..grid.size(), 0..grid.size()) {
         let Some(current) = unit_map.get(&(x, y)).cloned() else {
             continue;
         };
         let current_union_id = current.union_id;
 
         let mut removed_edge_for_current = UnitEdge::empty();
-        let mut unify_unit = |unit_id| {
+        let mut unify_unit = |unit_id: (i32, i32)| {
+            if separate
+                && !(is_in_finder(x, y, grid.size())
+                    && is_in_finder(unit_id.0, unit_id.1, grid.size()))
+            {
+                return;
+            }
             let unit = unit_map.get_mut(&unit_id);
             if let Some(unit) = unit {
                 if unit_id.0 > current.x {
@@ -203,22 +346,20 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
                     return;
                 }
 
-                let right_units = unions
-                    .get(&near_union_id)
-                    .expect("should get units")
-                    .clone();
-                unions.remove(&near_union_id);
+                let mut right_units = unions.remove(&near_union_id).expect("should get units");
                 unions
                     .get_mut(&current_union_id)
                     .expect("should get units")
                     .extend(right_units.iter());
 
-                for id in right_units {
-                    let Some(u) = unit_map.get_mut(&id) else {
+                for id in &right_units {
+                    let Some(u) = unit_map.get_mut(id) else {
                         continue;
                     };
                     u.union_id = current_union_id;
                 }
+                right_units.clear();
+                spare_sets.push(right_units);
             }
         };
 
@@ -235,41 +376,40 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
         });
     });
 
-    for (_, unit_ids) in unions {
-        let mut complete = HashSet::new();
-        let mut path: Vec<Segment> = vec![];
-        let min_x = *unit_ids
+    // The unions of the data modules are traced before the ones of the finder patterns,
+    // so that every layer is drawn in one run, in the order of `Style::fills`.
+    let in_finder = |units: &HashSet<(i32, i32)>| {
+        units
             .iter()
-            .min_by_key(|v| v.0)
-            .expect("should have values");
-        let mut next = *unit_ids
-            .iter()
-            .filter(|v| v.0 == min_x.0)
-            .min_by_key(|v| v.1)
-            .expect("should have value");
+            .all(|&(x, y)| is_in_finder(x - offset, y - offset, code.size()))
+    };
+    let mut current_layer = None;
+    union_ids.clear();
+    union_ids.extend(unions.keys());
+    union_ids.sort_unstable_by_key(|id| (in_finder(&unions[id]), *id));
+    for union_id in union_ids.iter() {
+        let mut unit_ids = unions.remove(union_id).expect("should get units");
+        units.clear();
+        units.extend(unit_ids.drain());
+        units.sort_unstable();
+        complete.clear();
+        path.clear();
+        // Start at the topmost of the leftmost units.
+        let mut next = *units.first().expect("should have values");
         let mut start_edge = UnitEdge::TOP;
 
-        /// The type of the radius.
-        enum RadiusType {
-            Large,
-            Medium,
-            Normal,
-        }
-
-        let radius_type = if unit_ids.contains(&(0, 0))
-            || unit_ids.contains(&(0, code.size() - 1))
-            || unit_ids.contains(&(code.size() - 1, 0))
-        {
-            // The large radius is used for the corners of the QR code.
-            RadiusType::Large
-        } else if unit_ids.contains(&(2, 2))
-            || unit_ids.contains(&(2, code.size() - 3))
-            || unit_ids.contains(&(code.size() - 3, 2))
+        let layer = if units
+            .iter()
+            .all(|&(x, y)| is_in_finder(x - offset, y - offset, code.size()))
         {
-            RadiusType::Medium
+            Layer::Finder
         } else {
-            RadiusType::Normal
+            Layer::Data
         };
+        if current_layer != Some(layer) {
+            receiver.dispatch_begin_layer(BeginLayer { layer });
+            current_layer = Some(layer);
+        }
 
         loop {
             let unit = unit_map.get_mut(&next).expect("should get unit");
@@ -318,41 +458,30 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
                 complete.insert(next);
             }
 
-            if complete.len() == unit_ids.len() {
+            if complete.len() == units.len() {
                 break;
             }
 
-            if let Some(n) = unit_ids.iter().find(|u| {
-                let unit = unit_map.get(u).expect("should get unit");
+            if let Some(n) = units.iter().find(|u| {
+                let unit = unit_map.get(*u).expect("should get unit");
                 **u != next
                     && unit.contains(path.last().expect("should have at least one segment").end)
-                    && !complete.contains(u)
+                    && !complete.contains(*u)
             }) {
                 // Find the unit that intersect with the current end path.
                 next = *n;
             } else {
                 // If there are no intersecting paths, randomly select one from the units that have never been merged.
-                let n = unit_ids
+                let n = units
                     .iter()
-                    .find(|u| !complete.contains(u))
+                    .find(|u| !complete.contains(*u))
                     .expect("should find the unit id");
                 next = *n;
             }
         }
 
-        #[derive(Debug, Default, Clone, Copy)]
-        struct DrawAdj {
-            start_offset: f64,
-            end_offset: f64,
-            corner_radius: f64,
-
-            /// A signum representation of the direction of the corner.
-            corner_direction: (f64, f64),
-            clockwise: bool,
-        }
-
         // Add rounded corners to the path.
-        let mut corners = HashMap::new();
+        corners.clear();
         let mut closed_path_start_idx = None;
         for idx in 0..path.len() + 1 {
             let current_idx = idx % path.len();
@@ -380,19 +509,24 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
             let is_clockwise = Segment::is_clockwise(a, b);
 
             let a_adj = corners.entry(current_idx).or_insert(DrawAdj::default());
-            let radius = if is_clockwise {
-                let radius_factor = match radius_type {
-                    RadiusType::Large => 4.2_f64,
-                    RadiusType::Medium => 1.2_f64,
-                    RadiusType::Normal => 1_f64,
-                };
-                0.5 * radius_factor
+            let corner = a.end;
+            let radius = if invert
+                && (corner.0 == 0
+                    || corner.1 == 0
+                    || corner.0 == grid.size()
+                    || corner.1 == grid.size())
+            {
+                // Keep the outer edge of the quiet zone square.
+                0.0
+            } else if let Some(radius) =
+                finder_corner_radius((corner.0 - offset, corner.1 - offset), code.size(), radii)
+            {
+                radius
+            } else if is_clockwise != invert {
+                // A convex corner of the dark modules, which is concave when tracing light ones.
+                radii.module
             } else {
-                if matches!(radius_type, RadiusType::Large) {
-                    1.3_f64
-                } else {
-                    0.25
-                }
+                radii.concave
             };
             a_adj.end_offset = radius;
             a_adj.corner_radius = radius;
@@ -406,7 +540,8 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
             b_adj.start_offset = radius;
         }
 
-        let draw_unit = size.width.min(size.height) / code.size() as f64;
+        // The position of the traced grid within the grid that includes the quiet zone.
+        let origin = (quiet_zone - offset) as f64;
         let mut end = None;
         for (idx, seg) in path.iter().enumerate() {
             let adj = corners.get(&idx).cloned().unwrap_or_default();
@@ -429,10 +564,14 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
             );
 
             if end.map(|end| end != seg.start).unwrap_or(true) {
-                recorder.move_to(Point::new(new_start.0 * draw_unit, new_start.1 * draw_unit));
+                receiver.dispatch_move_to(MoveTo {
+                    point: placement.module_origin(new_start.0 + origin, new_start.1 + origin),
+                });
             }
             if new_start != new_end {
-                recorder.line_to(Point::new(new_end.0 * draw_unit, new_end.1 * draw_unit));
+                receiver.dispatch_line_to(LineTo {
+                    point: placement.module_origin(new_end.0 + origin, new_end.1 + origin),
+                });
             }
 
             let corner_radius = adj.corner_radius;
@@ -469,17 +608,99 @@ pub(crate) fn draw(code: QrCode, size: Size, recorder: &mut DisplayListRecorder)
                 };
                 let end_angle = start_angle
                     + f32::consts::PI / 2_f32 * if is_clockwise { 1_f32 } else { -1_f32 };
-                recorder.arc_to(
-                    center * draw_unit,
-                    corner_radius * draw_unit,
+                receiver.dispatch_arc_to(ArcTo {
+                    center: placement.module_origin(center.x + origin, center.y + origin),
+                    radius: corner_radius * placement.module_size,
                     start_angle,
                     end_angle,
-                    is_clockwise,
-                );
+                    clockwise: is_clockwise,
+                });
             }
 
             end = Some(seg.end);
         }
-        recorder.close_path();
+        receiver.dispatch_close_path(ClosePath {});
+
+        spare_sets.push(unit_ids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::iproduct;
+
+    use super::is_in_finder;
+    use crate::builder::Builder;
+    use crate::rendering::geometry::Size;
+    use crate::rendering::raster::render_mask;
+    use crate::types::CornerRadii;
+
+    /// The number of pixels on the side of every module when rasterizing.
+    const SCALE: usize = 8;
+
+    /// Rasterizes the code at [`SCALE`] pixels per module and checks that the center of
+    /// every module is filled where the module is dark, or light if the code is inverted.
+    ///
+    /// The corners of the finder patterns are rounded by more than half a module,
+    /// so their modules are only checked when the corners are square.
+    fn assert_draws_modules(builder: Builder) {
+        let quiet_zone = builder.get_quiet_zone() as i32;
+        let square = builder.get_corner_radii() == CornerRadii::SQUARE;
+        let inverted = builder.is_inverted();
+        let grid = builder.build_module_grid().unwrap();
+        let side = grid.size() as usize * SCALE;
+        let display_list = builder
+            .size(Size::new(side as f64, side as f64))
+            .build()
+            .unwrap();
+        let mask = render_mask(&display_list, side, side);
+        for (x, y) in iproduct!(0..grid.size(), 0..grid.size()) {
+            let code_size = grid.size() - 2 * quiet_zone;
+            if !square && is_in_finder(x - quiet_zone, y - quiet_zone, code_size) {
+                continue;
+            }
+            let center = |v: i32| v as usize * SCALE + SCALE / 2;
+            let filled = mask.coverage(center(x), center(y)) >= 128;
+            assert_eq!(filled, grid.get(x, y) != inverted, "module ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn paths_are_traced_in_the_same_order_every_time() {
+        let builder = Builder::text("rounded-qr").quiet_zone(2);
+        assert_eq!(builder.build().unwrap(), builder.build().unwrap());
+        let inverted = builder.invert(true);
+        assert_eq!(inverted.build().unwrap(), inverted.build().unwrap());
+    }
+
+    #[test]
+    fn draws_the_dark_modules() {
+        let builder = Builder::text("rounded-qr").quiet_zone(2);
+        assert_draws_modules(builder.clone());
+        assert_draws_modules(builder.corner_radii(CornerRadii::SQUARE));
+    }
+
+    #[test]
+    fn inverted_codes_draw_the_complement_of_the_dark_modules() {
+        let builder = Builder::text("rounded-qr").quiet_zone(2).invert(true);
+        assert_draws_modules(builder.clone());
+        assert_draws_modules(builder.corner_radii(CornerRadii::SQUARE));
+    }
+
+    #[test]
+    fn inverted_codes_keep_the_outer_edge_of_the_quiet_zone_square() {
+        let display_list = Builder::text("rounded-qr")
+            .quiet_zone(2)
+            .invert(true)
+            .size(Size::new(200.0, 200.0))
+            .build()
+            .unwrap();
+        let mask = render_mask(&display_list, 200, 200);
+        let last = mask.width - 1;
+        for i in 0..mask.width {
+            for (x, y) in [(i, 0), (i, last), (0, i), (last, i)] {
+                assert_eq!(mask.coverage(x, y), 255, "pixel ({x}, {y})");
+            }
+        }
     }
 }
@@ -1,45 +1,582 @@
-use std::ffi::{c_float, c_int, c_void};
+//! The C interface of the crate, declared in `include/rounded_qr.h`.
+//!
+//! Builders are opaque handles created by `rqr_builder_create_*` and released with
+//! `rqr_builder_free`. Functions that can fail return an [`RqrStatus`], and a description
+//! of the last failure on the calling thread is available from [`rqr_last_error_message`].
 
-use crate::builder::Builder;
-use crate::rendering::display_list::{ArcTo, ClosePath, DisplayListOpReceiver, LineTo, MoveTo};
-use crate::types::{ErrorCorrectionLevel, Size};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_float, c_int, c_void, CStr, CString};
+use std::ptr;
 
+use crate::builder::FailedToGenerate;
+use crate::options::{Options, Payload};
+use crate::rendering::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use crate::rendering::geometry::Point;
+use crate::types::{CornerRadii, ErrorCorrectionLevel, Length, LogoRegion, ModuleShape, Size};
+
+/// The result of a fallible function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrStatus {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was out of range.
+    InvalidArgument = 2,
+    /// The text was not valid UTF-8.
+    InvalidUtf8 = 3,
+    /// The data does not fit in a QR Code at the requested error correction level.
+    DataTooLong = 4,
+    /// The modules are smaller than the minimum module size at the resolution of the image.
+    ModuleTooSmall = 5,
+}
+
+impl From<FailedToGenerate> for RqrStatus {
+    fn from(error: FailedToGenerate) -> Self {
+        match error {
+            FailedToGenerate::DataTooLong => RqrStatus::DataTooLong,
+            FailedToGenerate::ModuleTooSmall { .. } => RqrStatus::ModuleTooSmall,
+            FailedToGenerate::QuietZoneTooWide { .. } => RqrStatus::InvalidArgument,
+        }
+    }
+}
+
+/// The error correction level of a QR Code.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrErrorCorrectionLevel {
+    Low = 0,
+    Medium = 1,
+    Quartile = 2,
+    High = 3,
+}
+
+impl RqrErrorCorrectionLevel {
+    /// Returns the level with the given value, which comes from C and may be out of range.
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(Self::Low),
+            1 => Some(Self::Medium),
+            2 => Some(Self::Quartile),
+            3 => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+impl From<RqrErrorCorrectionLevel> for ErrorCorrectionLevel {
+    fn from(ecl: RqrErrorCorrectionLevel) -> Self {
+        match ecl {
+            RqrErrorCorrectionLevel::Low => ErrorCorrectionLevel::Low,
+            RqrErrorCorrectionLevel::Medium => ErrorCorrectionLevel::Medium,
+            RqrErrorCorrectionLevel::Quartile => ErrorCorrectionLevel::Quartile,
+            RqrErrorCorrectionLevel::High => ErrorCorrectionLevel::High,
+        }
+    }
+}
+
+/// How the dark modules of a QR Code are drawn.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrModuleShape {
+    /// Neighboring modules are merged into shapes with rounded corners.
+    Connected = 0,
+    /// Every data module is drawn on its own, as a dot.
+    Dots = 1,
+}
+
+impl RqrModuleShape {
+    /// Returns the shape with the given value, which comes from C and may be out of range.
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(Self::Connected),
+            1 => Some(Self::Dots),
+            _ => None,
+        }
+    }
+}
+
+impl From<RqrModuleShape> for ModuleShape {
+    fn from(shape: RqrModuleShape) -> Self {
+        match shape {
+            RqrModuleShape::Connected => ModuleShape::Connected,
+            RqrModuleShape::Dots => ModuleShape::Dots,
+        }
+    }
+}
+
+/// The unit of a physical length.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrLengthUnit {
+    Millimeters = 0,
+    Inches = 1,
+    /// Typographic points, 72 to the inch.
+    Points = 2,
+}
+
+impl RqrLengthUnit {
+    /// Returns the unit with the given value, which comes from C and may be out of range.
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(Self::Millimeters),
+            1 => Some(Self::Inches),
+            2 => Some(Self::Points),
+            _ => None,
+        }
+    }
+
+    fn length(self, value: f64) -> Length {
+        match self {
+            RqrLengthUnit::Millimeters => Length::Millimeters(value),
+            RqrLengthUnit::Inches => Length::Inches(value),
+            RqrLengthUnit::Points => Length::Points(value),
+        }
+    }
+}
+
+/// The radii of the rounded corners, in modules.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RqrCornerRadii {
+    pub module: c_float,
+    pub concave: c_float,
+    pub finder_outer: c_float,
+    pub finder_inner: c_float,
+    pub finder_center: c_float,
+}
+
+/// The input data of a builder, owned by the builder.
+#[derive(Debug, Clone)]
+enum OwnedData {
+    Binary(Vec<u8>),
+    Text(String),
+}
+
+/// An opaque handle to a QR Code builder.
+#[derive(Debug, Clone)]
+pub struct RqrBuilder {
+    data: OwnedData,
+    options: Options,
+}
+
+impl RqrBuilder {
+    fn new(data: OwnedData) -> Self {
+        Self {
+            data,
+            options: Options::default(),
+        }
+    }
+
+    fn payload(&self) -> Payload<'_> {
+        match &self.data {
+            OwnedData::Binary(bytes) => Payload::Binary(bytes),
+            OwnedData::Text(text) => Payload::Text(text),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the error message for [`rqr_last_error_message`] and returns the status.
+fn fail(status: RqrStatus, message: &str) -> RqrStatus {
+    let message = CString::new(message).expect("error messages should not contain NUL bytes");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// Returns a mutable reference to the builder behind the handle, or records an error.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+unsafe fn builder_mut<'a>(builder: *mut RqrBuilder) -> Result<&'a mut RqrBuilder, RqrStatus> {
+    builder
+        .as_mut()
+        .ok_or_else(|| fail(RqrStatus::NullPointer, "builder is null"))
+}
+
+/// Returns a description of the last error that occurred on the calling thread,
+/// or null if no error occurred yet.
+///
+/// The string is valid until the next failing call on the same thread.
 #[no_mangle]
-pub extern "C" fn rqr_builder_create_with_data(
+pub extern "C" fn rqr_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Creates a builder that encodes a copy of the given bytes.
+///
+/// # Safety
+///
+/// `data` must point to `length` readable bytes, or may be null if `length` is 0.
+/// `out_builder` must point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_create_with_data(
     data: *const u8,
     length: usize,
+    out_builder: *mut *mut RqrBuilder,
+) -> RqrStatus {
+    if out_builder.is_null() {
+        return fail(RqrStatus::NullPointer, "out_builder is null");
+    }
+    let bytes = if length == 0 {
+        vec![]
+    } else if data.is_null() {
+        return fail(RqrStatus::NullPointer, "data is null");
+    } else {
+        std::slice::from_raw_parts(data, length).to_vec()
+    };
+    let builder = Box::new(RqrBuilder::new(OwnedData::Binary(bytes)));
+    *out_builder = Box::into_raw(builder);
+    RqrStatus::Ok
+}
+
+/// Creates a builder that encodes a copy of the given NUL-terminated UTF-8 text.
+///
+/// # Safety
+///
+/// `text` must point to a NUL-terminated string.
+/// `out_builder` must point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_create_with_text(
+    text: *const c_char,
+    out_builder: *mut *mut RqrBuilder,
+) -> RqrStatus {
+    if out_builder.is_null() {
+        return fail(RqrStatus::NullPointer, "out_builder is null");
+    }
+    if text.is_null() {
+        return fail(RqrStatus::NullPointer, "text is null");
+    }
+    let Ok(text) = CStr::from_ptr(text).to_str() else {
+        return fail(RqrStatus::InvalidUtf8, "text is not valid UTF-8");
+    };
+    let builder = Box::new(RqrBuilder::new(OwnedData::Text(text.to_owned())));
+    *out_builder = Box::into_raw(builder);
+    RqrStatus::Ok
+}
+
+/// Releases a builder. Passing null does nothing.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+/// and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_free(builder: *mut RqrBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+/// Sets the error correction level, one of the `RqrErrorCorrectionLevel` values.
+/// Defaults to medium.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_error_correction_level(
+    builder: *mut RqrBuilder,
     ecl: c_int,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    let Some(ecl) = RqrErrorCorrectionLevel::from_raw(ecl) else {
+        return fail(RqrStatus::InvalidArgument, "unknown error correction level");
+    };
+    builder.options.error_correction_level = ecl.into();
+    RqrStatus::Ok
+}
+
+/// Sets the size of the output image. Both dimensions must be finite and positive.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_size(
+    builder: *mut RqrBuilder,
     width: c_float,
     height: c_float,
-) -> *mut c_void {
-    let bytes = unsafe { std::slice::from_raw_parts(data, length) };
-    let ecl = match ecl {
-        1 => ErrorCorrectionLevel::Medium,
-        2 => ErrorCorrectionLevel::Quartile,
-        3 => ErrorCorrectionLevel::High,
-        _ => ErrorCorrectionLevel::Low,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    let valid = |value: c_float| value.is_finite() && value > 0.0;
+    if !valid(width) || !valid(height) {
+        return fail(
+            RqrStatus::InvalidArgument,
+            "width and height must be finite and positive",
+        );
+    }
+    builder.options.size = Size::new(width, height);
+    RqrStatus::Ok
+}
+
+/// Sets the width of the empty border around the QR Code, in modules, which must be
+/// at most 100. Defaults to 0.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_quiet_zone(
+    builder: *mut RqrBuilder,
+    modules: u32,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    if modules > Options::MAX_QUIET_ZONE {
+        return fail(RqrStatus::InvalidArgument, "quiet zone is too wide");
+    }
+    builder.options.quiet_zone = modules;
+    RqrStatus::Ok
+}
+
+/// Sets the radii of the rounded corners, in modules. Every radius must be finite and
+/// non-negative, and the module and concave radii must not exceed 0.5.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+/// and `radii` must be null or point to a readable `RqrCornerRadii`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_corner_radii(
+    builder: *mut RqrBuilder,
+    radii: *const RqrCornerRadii,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    let Some(radii) = radii.as_ref() else {
+        return fail(RqrStatus::NullPointer, "radii is null");
+    };
+    let radii = CornerRadii {
+        module: radii.module as f64,
+        concave: radii.concave as f64,
+        finder_outer: radii.finder_outer as f64,
+        finder_inner: radii.finder_inner as f64,
+        finder_center: radii.finder_center as f64,
+    };
+    if radii.validate().is_err() {
+        return fail(RqrStatus::InvalidArgument, "corner radii are out of range");
+    }
+    builder.options.corner_radii = radii;
+    RqrStatus::Ok
+}
+
+/// Sets the size of the region in the middle of the QR Code that is left empty
+/// for a logo, in modules. A zero width or height disables the region, which is the default.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_logo_region(
+    builder: *mut RqrBuilder,
+    width: u32,
+    height: u32,
+) -> RqrStatus {
+    match builder_mut(builder) {
+        Ok(builder) => {
+            builder.options.logo_region = LogoRegion::new(width, height);
+            RqrStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Sets whether the light modules and the quiet zone are drawn instead of the dark modules,
+/// for codes drawn in a light color on a dark background. Disabled by default.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_invert(
+    builder: *mut RqrBuilder,
+    invert: bool,
+) -> RqrStatus {
+    match builder_mut(builder) {
+        Ok(builder) => {
+            builder.options.invert = invert;
+            RqrStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Sets how the dark modules are drawn, one of the `RqrModuleShape` values.
+/// Defaults to connected.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_module_shape(
+    builder: *mut RqrBuilder,
+    shape: c_int,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    let Some(shape) = RqrModuleShape::from_raw(shape) else {
+        return fail(RqrStatus::InvalidArgument, "unknown module shape");
+    };
+    builder.options.shape = shape.into();
+    RqrStatus::Ok
+}
+
+/// Sets the resolution of the image, in units per inch, which relates its units to
+/// physical lengths. The resolution must be finite and positive. Defaults to 72.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_dpi(builder: *mut RqrBuilder, dpi: c_float) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    if !(dpi.is_finite() && dpi > 0.0) {
+        return fail(
+            RqrStatus::InvalidArgument,
+            "dpi must be finite and positive",
+        );
+    }
+    builder.options.dpi = dpi as f64;
+    RqrStatus::Ok
+}
+
+/// Returns the length of the value in the unit, where zero means no length,
+/// or records an error.
+fn optional_length(value: c_float, unit: c_int) -> Result<Option<Length>, RqrStatus> {
+    let Some(unit) = RqrLengthUnit::from_raw(unit) else {
+        return Err(fail(RqrStatus::InvalidArgument, "unknown length unit"));
+    };
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(fail(
+            RqrStatus::InvalidArgument,
+            "lengths must be finite and non-negative",
+        ));
+    }
+    Ok((value > 0.0).then(|| unit.length(value as f64)))
+}
+
+/// Sets the physical side of every module in the unit, one of the `RqrLengthUnit` values,
+/// which makes the image as large as the code and its quiet zone instead of its size.
+/// A length of zero removes the module size, which is the default.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_module_size(
+    builder: *mut RqrBuilder,
+    value: c_float,
+    unit: c_int,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
     };
-    let builder = Box::new(
-        Builder::binary(bytes)
-            .error_correction_level(ecl)
-            .size(Size::new(width as f32, height as f32)),
-    );
-    Box::into_raw(builder) as *mut c_void
+    match optional_length(value, unit) {
+        Ok(length) => {
+            builder.options.module_size = length;
+            RqrStatus::Ok
+        }
+        Err(status) => status,
+    }
 }
 
+/// Sets the smallest physical side of the modules in the unit, one of the `RqrLengthUnit`
+/// values, below which building fails with `RQR_STATUS_MODULE_TOO_SMALL`.
+/// A length of zero removes the minimum, which is the default.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
 #[no_mangle]
-pub extern "C" fn rqr_builder_build_path(
-    builder: *mut c_void,
+pub unsafe extern "C" fn rqr_builder_set_min_module_size(
+    builder: *mut RqrBuilder,
+    value: c_float,
+    unit: c_int,
+) -> RqrStatus {
+    let builder = match builder_mut(builder) {
+        Ok(builder) => builder,
+        Err(status) => return status,
+    };
+    match optional_length(value, unit) {
+        Ok(length) => {
+            builder.options.min_module_size = length;
+            RqrStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Sets whether modules are a whole number of units wide, for raster images whose
+/// units are pixels. Disabled by default.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_set_snap_to_pixels(
+    builder: *mut RqrBuilder,
+    snap_to_pixels: bool,
+) -> RqrStatus {
+    match builder_mut(builder) {
+        Ok(builder) => {
+            builder.options.snap_to_pixels = snap_to_pixels;
+            RqrStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Builds the QR Code and calls the functions with the path operations that outline it.
+///
+/// The builder is left untouched and can be built again or reconfigured.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`.
+/// The functions are called with `context` as their first argument.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_build_path(
+    builder: *const RqrBuilder,
     context: *mut c_void,
     move_to_point_fn: extern "C" fn(*mut c_void, c_float, c_float),
     line_to_point_fn: extern "C" fn(*mut c_void, c_float, c_float),
     arc_to_fn: extern "C" fn(*mut c_void, c_float, c_float, c_float, c_float, c_float, c_int),
     close_path_fn: extern "C" fn(*mut c_void),
-) {
-    let builder = unsafe { Box::<Builder>::from_raw(builder as *mut _) };
+) -> RqrStatus {
+    let Some(builder) = builder.as_ref() else {
+        return fail(RqrStatus::NullPointer, "builder is null");
+    };
 
     struct ExternReceiver {
         context: *mut c_void,
+        current: Point,
         move_to_point_fn: extern "C" fn(*mut c_void, c_float, c_float),
         line_to_point_fn: extern "C" fn(*mut c_void, c_float, c_float),
         arc_to_fn: extern "C" fn(*mut c_void, c_float, c_float, c_float, c_float, c_float, c_int),
@@ -49,10 +586,12 @@ pub extern "C" fn rqr_builder_build_path(
     impl DisplayListOpReceiver for ExternReceiver {
         fn dispatch_move_to(&mut self, op: MoveTo) {
             (self.move_to_point_fn)(self.context, op.point.x as c_float, op.point.y as c_float);
+            self.current = op.point;
         }
 
         fn dispatch_line_to(&mut self, op: LineTo) {
             (self.line_to_point_fn)(self.context, op.point.x as c_float, op.point.y as c_float);
+            self.current = op.point;
         }
 
         fn dispatch_arc_to(&mut self, op: ArcTo) {
@@ -65,6 +604,18 @@ pub extern "C" fn rqr_builder_build_path(
                 op.end_angle as c_float,
                 op.clockwise as c_int,
             );
+            self.current = op.end_point();
+        }
+
+        fn dispatch_cubic_to(&mut self, op: CubicTo) {
+            // There is no function for curves, so they are approximated with lines.
+            for point in op.flatten(self.current, 0.05) {
+                self.dispatch_line_to(LineTo { point });
+            }
+        }
+
+        fn dispatch_quad_to(&mut self, op: QuadTo) {
+            self.dispatch_cubic_to(op.to_cubic(self.current));
         }
 
         fn dispatch_close_path(&mut self, _op: ClosePath) {
@@ -74,10 +625,435 @@ pub extern "C" fn rqr_builder_build_path(
 
     let mut receiver = ExternReceiver {
         context,
+        current: Point::default(),
         move_to_point_fn,
         line_to_point_fn,
         arc_to_fn,
         close_path_fn,
     };
-    _ = builder.build_with_receiver(&mut receiver);
+    match builder
+        .options
+        .build_with_receiver(builder.payload(), &mut receiver)
+    {
+        Ok(()) => RqrStatus::Ok,
+        Err(error) => fail(error.into(), &error.to_string()),
+    }
+}
+
+/// The kind of an [`RqrOp`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrOpKind {
+    /// Starts a new subpath at `(x, y)`.
+    MoveTo = 0,
+    /// Adds a line to `(x, y)`.
+    LineTo = 1,
+    /// Adds an arc of the circle centered at `(x, y)` with `radius`, from `start_angle`
+    /// to `end_angle` in radians, in the direction given by `clockwise`. The arc starts
+    /// at the current point.
+    ArcTo = 2,
+    /// Adds a cubic Bézier curve to `(x, y)` with the control points `(x1, y1)` and `(x2, y2)`.
+    CubicTo = 3,
+    /// Closes the current subpath.
+    ClosePath = 4,
+}
+
+/// A path operation. The fields that are not used by its kind are zero.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RqrOp {
+    pub kind: RqrOpKind,
+    pub x: c_float,
+    pub y: c_float,
+    pub x1: c_float,
+    pub y1: c_float,
+    pub x2: c_float,
+    pub y2: c_float,
+    pub radius: c_float,
+    pub start_angle: c_float,
+    pub end_angle: c_float,
+    pub clockwise: c_int,
+}
+
+impl RqrOp {
+    fn new(kind: RqrOpKind) -> Self {
+        Self {
+            kind,
+            x: 0.0,
+            y: 0.0,
+            x1: 0.0,
+            y1: 0.0,
+            x2: 0.0,
+            y2: 0.0,
+            radius: 0.0,
+            start_angle: 0.0,
+            end_angle: 0.0,
+            clockwise: 0,
+        }
+    }
+
+    fn with_point(kind: RqrOpKind, point: Point) -> Self {
+        Self {
+            x: point.x as c_float,
+            y: point.y as c_float,
+            ..Self::new(kind)
+        }
+    }
+}
+
+/// How arcs are represented in an [`RqrOpBuffer`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RqrArcMode {
+    /// Arcs are kept as `RQR_OP_KIND_ARC_TO` operations.
+    Arcs = 0,
+    /// Arcs are approximated with `RQR_OP_KIND_CUBIC_TO` operations,
+    /// for graphics APIs that cannot draw arcs from their center.
+    Cubics = 1,
+}
+
+impl RqrArcMode {
+    /// Returns the mode with the given value, which comes from C and may be out of range.
+    fn from_raw(value: c_int) -> Option<Self> {
+        match value {
+            0 => Some(Self::Arcs),
+            1 => Some(Self::Cubics),
+            _ => None,
+        }
+    }
+}
+
+/// An array of path operations allocated by [`rqr_builder_build_ops`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct RqrOpBuffer {
+    pub ops: *mut RqrOp,
+    pub count: usize,
+}
+
+/// A receiver that collects drawing operations as [`RqrOp`] values.
+struct OpCollector {
+    ops: Vec<RqrOp>,
+    arc_mode: RqrArcMode,
+    current: Point,
+}
+
+impl DisplayListOpReceiver for OpCollector {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.ops
+            .push(RqrOp::with_point(RqrOpKind::MoveTo, op.point));
+        self.current = op.point;
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.ops
+            .push(RqrOp::with_point(RqrOpKind::LineTo, op.point));
+        self.current = op.point;
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        if self.arc_mode == RqrArcMode::Arcs {
+            self.ops.push(RqrOp {
+                radius: op.radius as c_float,
+                start_angle: op.start_angle,
+                end_angle: op.end_angle,
+                clockwise: op.clockwise as c_int,
+                ..RqrOp::with_point(RqrOpKind::ArcTo, op.center)
+            });
+            self.current = op.end_point();
+            return;
+        }
+
+        let start = op.start_point();
+        if (start.x - self.current.x).hypot(start.y - self.current.y) > 1e-3 {
+            self.ops.push(RqrOp::with_point(RqrOpKind::LineTo, start));
+        }
+        for [control1, control2, end] in op.to_cubics() {
+            self.ops.push(RqrOp {
+                x1: control1.x as c_float,
+                y1: control1.y as c_float,
+                x2: control2.x as c_float,
+                y2: control2.y as c_float,
+                ..RqrOp::with_point(RqrOpKind::CubicTo, end)
+            });
+            self.current = end;
+        }
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.ops.push(RqrOp {
+            x1: op.control1.x as c_float,
+            y1: op.control1.y as c_float,
+            x2: op.control2.x as c_float,
+            y2: op.control2.y as c_float,
+            ..RqrOp::with_point(RqrOpKind::CubicTo, op.point)
+        });
+        self.current = op.point;
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        // Quadratic curves are represented exactly by cubic ones, which keeps the set of kinds small.
+        self.dispatch_cubic_to(op.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.ops.push(RqrOp::new(RqrOpKind::ClosePath));
+    }
+}
+
+/// Builds the QR Code and stores the path operations that outline it in `out_buffer`,
+/// which must be released with [`rqr_op_buffer_free`]. `arc_mode` is one of the
+/// `RqrArcMode` values.
+///
+/// The builder is left untouched and can be built again or reconfigured.
+///
+/// # Safety
+///
+/// The handle must be null or a live pointer returned by `rqr_builder_create_*`,
+/// and `out_buffer` must point to a writable `RqrOpBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_builder_build_ops(
+    builder: *const RqrBuilder,
+    arc_mode: c_int,
+    out_buffer: *mut RqrOpBuffer,
+) -> RqrStatus {
+    let Some(builder) = builder.as_ref() else {
+        return fail(RqrStatus::NullPointer, "builder is null");
+    };
+    let Some(out_buffer) = out_buffer.as_mut() else {
+        return fail(RqrStatus::NullPointer, "out_buffer is null");
+    };
+    let Some(arc_mode) = RqrArcMode::from_raw(arc_mode) else {
+        return fail(RqrStatus::InvalidArgument, "unknown arc mode");
+    };
+
+    let mut collector = OpCollector {
+        ops: vec![],
+        arc_mode,
+        current: Point::default(),
+    };
+    if let Err(error) = builder
+        .options
+        .build_with_receiver(builder.payload(), &mut collector)
+    {
+        return fail(error.into(), &error.to_string());
+    }
+    let ops = Box::into_raw(collector.ops.into_boxed_slice());
+    *out_buffer = RqrOpBuffer {
+        ops: ops as *mut RqrOp,
+        count: ops.len(),
+    };
+    RqrStatus::Ok
+}
+
+/// Releases the operations of a buffer filled by [`rqr_builder_build_ops`] and resets it.
+/// Passing null or an empty buffer does nothing.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to a buffer filled by `rqr_builder_build_ops`
+/// that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rqr_op_buffer_free(buffer: *mut RqrOpBuffer) {
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+    if !buffer.ops.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.ops,
+            buffer.count,
+        )));
+    }
+    buffer.ops = ptr::null_mut();
+    buffer.count = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(text: &str) -> *mut RqrBuilder {
+        let text = CString::new(text).unwrap();
+        let mut builder = ptr::null_mut();
+        let status = unsafe { rqr_builder_create_with_text(text.as_ptr(), &mut builder) };
+        assert_eq!(status, RqrStatus::Ok);
+        builder
+    }
+
+    fn build_ops(builder: *const RqrBuilder) -> Result<usize, RqrStatus> {
+        let mut buffer = RqrOpBuffer {
+            ops: ptr::null_mut(),
+            count: 0,
+        };
+        let status =
+            unsafe { rqr_builder_build_ops(builder, RqrArcMode::Arcs as c_int, &mut buffer) };
+        let count = buffer.count;
+        unsafe { rqr_op_buffer_free(&mut buffer) };
+        match status {
+            RqrStatus::Ok => Ok(count),
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn setters_update_the_options() {
+        let builder = create("rounded-qr");
+        unsafe {
+            let radii = RqrCornerRadii {
+                module: 0.5,
+                concave: 0.0,
+                finder_outer: 1.0,
+                finder_inner: 1.0,
+                finder_center: 0.5,
+            };
+            let ecl = RqrErrorCorrectionLevel::High as c_int;
+            assert_eq!(
+                rqr_builder_set_error_correction_level(builder, ecl),
+                RqrStatus::Ok
+            );
+            assert_eq!(rqr_builder_set_size(builder, 100.0, 200.0), RqrStatus::Ok);
+            assert_eq!(rqr_builder_set_quiet_zone(builder, 4), RqrStatus::Ok);
+            assert_eq!(rqr_builder_set_corner_radii(builder, &radii), RqrStatus::Ok);
+            assert_eq!(rqr_builder_set_logo_region(builder, 5, 3), RqrStatus::Ok);
+            assert_eq!(rqr_builder_set_invert(builder, true), RqrStatus::Ok);
+            let dots = RqrModuleShape::Dots as c_int;
+            assert_eq!(rqr_builder_set_module_shape(builder, dots), RqrStatus::Ok);
+            assert_eq!(rqr_builder_set_dpi(builder, 300.0), RqrStatus::Ok);
+            let inches = RqrLengthUnit::Inches as c_int;
+            assert_eq!(
+                rqr_builder_set_module_size(builder, 0.5, inches),
+                RqrStatus::Ok
+            );
+            let points = RqrLengthUnit::Points as c_int;
+            assert_eq!(
+                rqr_builder_set_min_module_size(builder, 2.0, points),
+                RqrStatus::Ok
+            );
+            assert_eq!(rqr_builder_set_snap_to_pixels(builder, true), RqrStatus::Ok);
+
+            let expected = Options {
+                error_correction_level: ErrorCorrectionLevel::High,
+                size: Size::new(100.0, 200.0),
+                quiet_zone: 4,
+                corner_radii: CornerRadii {
+                    module: 0.5,
+                    concave: 0.0,
+                    finder_outer: 1.0,
+                    finder_inner: 1.0,
+                    finder_center: 0.5,
+                },
+                shape: ModuleShape::Dots,
+                logo_region: LogoRegion::new(5, 3),
+                invert: true,
+                dpi: 300.0,
+                module_size: Some(Length::Inches(0.5)),
+                min_module_size: Some(Length::Points(2.0)),
+                snap_to_pixels: true,
+            };
+            assert_eq!((*builder).options, expected);
+
+            // A length of zero removes the size again.
+            let millimeters = RqrLengthUnit::Millimeters as c_int;
+            assert_eq!(
+                rqr_builder_set_module_size(builder, 0.0, millimeters),
+                RqrStatus::Ok
+            );
+            assert_eq!((*builder).options.module_size, None);
+            rqr_builder_free(builder);
+        }
+    }
+
+    #[test]
+    fn unknown_enum_values_are_rejected() {
+        let builder = create("rounded-qr");
+        unsafe {
+            let status = rqr_builder_set_error_correction_level(builder, 4);
+            assert_eq!(status, RqrStatus::InvalidArgument);
+            assert_eq!(
+                rqr_builder_set_module_shape(builder, -1),
+                RqrStatus::InvalidArgument
+            );
+            assert_eq!(
+                rqr_builder_set_module_size(builder, 1.0, 3),
+                RqrStatus::InvalidArgument
+            );
+            let mut buffer = RqrOpBuffer {
+                ops: ptr::null_mut(),
+                count: 0,
+            };
+            let status = rqr_builder_build_ops(builder, 2, &mut buffer);
+            assert_eq!(status, RqrStatus::InvalidArgument);
+            assert_eq!((*builder).options, Options::default());
+            rqr_builder_free(builder);
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let builder = create("rounded-qr");
+        unsafe {
+            let radii = RqrCornerRadii {
+                module: 0.6,
+                concave: 0.25,
+                finder_outer: 2.0,
+                finder_inner: 1.0,
+                finder_center: 0.5,
+            };
+            let status = rqr_builder_set_corner_radii(builder, &radii);
+            assert_eq!(status, RqrStatus::InvalidArgument);
+            assert_eq!(
+                rqr_builder_set_size(builder, f32::NAN, 10.0),
+                RqrStatus::InvalidArgument
+            );
+            assert_eq!(
+                rqr_builder_set_dpi(builder, 0.0),
+                RqrStatus::InvalidArgument
+            );
+            let millimeters = RqrLengthUnit::Millimeters as c_int;
+            let status = rqr_builder_set_min_module_size(builder, -1.0, millimeters);
+            assert_eq!(status, RqrStatus::InvalidArgument);
+            for modules in [Options::MAX_QUIET_ZONE + 1, u32::MAX] {
+                assert_eq!(
+                    rqr_builder_set_quiet_zone(builder, modules),
+                    RqrStatus::InvalidArgument
+                );
+            }
+            assert!(!rqr_last_error_message().is_null());
+            assert_eq!((*builder).options, Options::default());
+            rqr_builder_free(builder);
+        }
+    }
+
+    #[test]
+    fn null_pointers_are_rejected() {
+        unsafe {
+            assert_eq!(
+                rqr_builder_set_quiet_zone(ptr::null_mut(), 4),
+                RqrStatus::NullPointer
+            );
+            let mut builder = ptr::null_mut();
+            let status = rqr_builder_create_with_text(ptr::null(), &mut builder);
+            assert_eq!(status, RqrStatus::NullPointer);
+        }
+        assert_eq!(build_ops(ptr::null()), Err(RqrStatus::NullPointer));
+    }
+
+    #[test]
+    fn build_errors_have_their_own_status() {
+        let builder = create("rounded-qr");
+        assert!(build_ops(builder).unwrap() > 0);
+        unsafe {
+            let millimeters = RqrLengthUnit::Millimeters as c_int;
+            assert_eq!(
+                rqr_builder_set_min_module_size(builder, 50.0, millimeters),
+                RqrStatus::Ok
+            );
+        }
+        assert_eq!(build_ops(builder), Err(RqrStatus::ModuleTooSmall));
+        unsafe { rqr_builder_free(builder) };
+
+        let builder = create(&"a".repeat(8000));
+        assert_eq!(build_ops(builder), Err(RqrStatus::DataTooLong));
+        unsafe { rqr_builder_free(builder) };
+    }
 }
@@ -0,0 +1,241 @@
+use crate::builder::{Builder, Result};
+use crate::rendering::display_list::{DisplayList, DisplayListOp, LineTo};
+use crate::rendering::geometry::{Point, Rect, Size};
+use crate::text::Font;
+
+/// The shapes that can surround a QR Code.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FrameTemplate {
+    /// A border with rounded corners around the code, with the label below the code inside of it.
+    #[default]
+    RoundedBorder,
+    /// A border around the code above a speech bubble that points at it,
+    /// with the label cut out of the bubble.
+    SpeechBubble,
+    /// A border around the code that widens into a banner below it,
+    /// with the label cut out of the banner.
+    BottomBanner,
+}
+
+/// Builds a QR Code surrounded by a frame with a call-to-action label.
+///
+/// The code keeps the size of the builder it is made from, and the frame makes the image
+/// larger; [`FrameBuilder::get_size`] returns the size of the whole composition.
+#[derive(Debug, Clone)]
+pub struct FrameBuilder<'a> {
+    builder: Builder<'a>,
+    template: FrameTemplate,
+    label: &'a str,
+    font: &'a Font,
+}
+
+/// The dimensions of a frame, derived from the size of the code it surrounds.
+struct Layout {
+    /// The side of the code.
+    code: f64,
+    /// The thickness of the border.
+    border: f64,
+    /// The space between the code and the border.
+    padding: f64,
+    /// The radius of the outer corners of the border.
+    corner_radius: f64,
+    /// The largest font size of the label.
+    font_size: f64,
+    /// The height of the area that holds the label.
+    label_height: f64,
+}
+
+impl Layout {
+    fn new(code: f64) -> Self {
+        let font_size = code * 0.11;
+        Self {
+            code,
+            border: code * 0.035,
+            padding: code * 0.04,
+            corner_radius: code * 0.08,
+            font_size,
+            label_height: font_size * 1.8,
+        }
+    }
+
+    /// Returns the side of the square border around the code.
+    fn width(&self) -> f64 {
+        self.code + 2.0 * (self.border + self.padding)
+    }
+
+    /// Returns the distance between the tip of the pointer of a speech bubble
+    /// and the border above it, and the height of the pointer.
+    fn pointer(&self) -> (f64, f64) {
+        (self.code * 0.03, self.code * 0.05)
+    }
+}
+
+impl<'a> FrameBuilder<'a> {
+    /// Creates a frame around the QR Code of the builder, with the label "SCAN ME"
+    /// set in the embedded font.
+    pub fn new(builder: Builder<'a>) -> Self {
+        Self {
+            builder,
+            template: FrameTemplate::default(),
+            label: "SCAN ME",
+            font: Font::embedded(),
+        }
+    }
+
+    pub fn template(self, template: FrameTemplate) -> Self {
+        Self { template, ..self }
+    }
+
+    /// Sets the text of the label, which is set on a single line and made smaller
+    /// if it does not fit in the width of the frame.
+    pub fn label(self, label: &'a str) -> Self {
+        Self { label, ..self }
+    }
+
+    /// Sets the font of the label.
+    pub fn font(self, font: &'a Font) -> Self {
+        Self { font, ..self }
+    }
+
+    pub fn get_template(&self) -> FrameTemplate {
+        self.template
+    }
+
+    pub fn get_label(&self) -> &'a str {
+        self.label
+    }
+
+    fn layout(&self) -> Result<Layout> {
+        let size = self.builder.placement()?.size;
+        Ok(Layout::new(size.width.min(size.height)))
+    }
+
+    /// Returns the size of the code and its frame.
+    pub fn get_size(&self) -> Result<Size> {
+        Ok(self.size(&self.layout()?))
+    }
+
+    fn size(&self, layout: &Layout) -> Size {
+        let width = layout.width();
+        let height = match self.template {
+            FrameTemplate::RoundedBorder => width + layout.label_height,
+            FrameTemplate::SpeechBubble => {
+                let (gap, pointer) = layout.pointer();
+                width + gap + pointer + layout.label_height
+            }
+            FrameTemplate::BottomBanner => width - layout.border + layout.label_height,
+        };
+        Size::new(width, height)
+    }
+
+    /// Builds the QR Code and its frame and returns the display list of their drawing operations.
+    ///
+    /// The frame is drawn first, in the [data layer](crate::rendering::display_list::Layer::Data).
+    pub fn build(self) -> Result<DisplayList> {
+        let layout = self.layout()?;
+        let size = self.size(&layout);
+        let Layout {
+            border,
+            padding,
+            corner_radius,
+            ..
+        } = layout;
+        let width = layout.width();
+        let inner_radius = (corner_radius - border).max(0.0);
+        let rect = |x: f64, y: f64, width: f64, height: f64| Rect {
+            origin: Point::new(x, y),
+            size: Size::new(width, height),
+        };
+
+        let mut display_list = DisplayList::new();
+        // The area that holds the label, and whether the label is cut out of a filled shape.
+        let (label_area, knockout) = match self.template {
+            FrameTemplate::RoundedBorder => {
+                let outer = rect(0.0, 0.0, size.width, size.height);
+                display_list.extend(DisplayList::rounded_rect(outer, corner_radius));
+                let inner = rect(
+                    border,
+                    border,
+                    width - 2.0 * border,
+                    size.height - 2.0 * border,
+                );
+                display_list.extend(DisplayList::rounded_rect(inner, inner_radius).reversed());
+                let top = border + padding + layout.code;
+                (
+                    rect(border, top, inner.size.width, size.height - border - top),
+                    false,
+                )
+            }
+            FrameTemplate::SpeechBubble => {
+                let outer = rect(0.0, 0.0, width, width);
+                display_list.extend(DisplayList::rounded_rect(outer, corner_radius));
+                let inner = rect(border, border, width - 2.0 * border, width - 2.0 * border);
+                display_list.extend(DisplayList::rounded_rect(inner, inner_radius).reversed());
+
+                let (gap, pointer) = layout.pointer();
+                let bubble = rect(0.0, width + gap + pointer, width, layout.label_height);
+                let tip = Point::new(width / 2.0, width + gap);
+                display_list.extend(with_pointer(
+                    DisplayList::rounded_rect(bubble, corner_radius),
+                    tip,
+                    pointer,
+                ));
+                (bubble, true)
+            }
+            FrameTemplate::BottomBanner => {
+                let outer = rect(0.0, 0.0, size.width, size.height);
+                display_list.extend(DisplayList::rounded_rect(outer, corner_radius));
+                let inner_side = width - 2.0 * border;
+                let inner = rect(border, border, inner_side, inner_side);
+                display_list.extend(DisplayList::rounded_rect(inner, inner_radius).reversed());
+                let top = border + inner_side;
+                (rect(0.0, top, width, size.height - top), true)
+            }
+        };
+
+        let inset = border + padding;
+        let text_area = rect(
+            label_area.origin.x + inset,
+            label_area.origin.y,
+            label_area.size.width - 2.0 * inset,
+            label_area.size.height,
+        );
+        let label = self
+            .font
+            .centered_text_outline(self.label, layout.font_size, text_area);
+        if knockout {
+            display_list.extend(label.reversed());
+        } else {
+            display_list.extend(label);
+        }
+
+        let code = self.builder.build()?;
+        display_list.extend(code.translated(Point::new(inset, inset)));
+        Ok(display_list)
+    }
+}
+
+/// Adds a triangular pointer to the top edge of a rectangle traced by
+/// [`DisplayList::rounded_rect`], with its tip at the given point above the edge.
+fn with_pointer(rounded_rect: DisplayList, tip: Point, height: f64) -> DisplayList {
+    let top = tip.y + height;
+    let mut ops = rounded_rect.into_iter();
+    // The rectangle starts with its top edge, which runs from left to right.
+    let start = ops.next();
+    let pointer = [
+        Point::new(tip.x - height, top),
+        tip,
+        Point::new(tip.x + height, top),
+    ]
+    .map(|point| DisplayListOp::LineTo(LineTo { point }));
+    start.into_iter().chain(pointer).chain(ops).collect()
+}
+
+impl<'a> Builder<'a> {
+    /// Returns a builder of the QR Code surrounded by a frame.
+    pub fn frame(self, template: FrameTemplate) -> FrameBuilder<'a> {
+        FrameBuilder::new(self).template(template)
+    }
+}
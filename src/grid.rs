@@ -0,0 +1,87 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::qrcodegen::QrCode;
+
+/// Returns the number of modules in a grid with the given number of modules per side.
+fn area(size: i32) -> usize {
+    let size = size as usize;
+    size.checked_mul(size)
+        .expect("the grid should have at most usize::MAX modules")
+}
+
+/// A square grid of dark and light modules.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleGrid {
+    size: i32,
+    modules: Vec<bool>,
+}
+
+impl ModuleGrid {
+    /// Creates a grid with the given number of modules per side, all of them light.
+    pub fn new(size: i32) -> Self {
+        let size = size.max(0);
+        Self {
+            size,
+            modules: vec![false; area(size)],
+        }
+    }
+
+    /// Creates a grid holding the modules of the QR Code.
+    pub(crate) fn from_qr_code(code: &QrCode) -> Self {
+        let mut grid = Self::new(code.size());
+        for y in 0..code.size() {
+            for x in 0..code.size() {
+                grid.set(x, y, code.get_module(x, y));
+            }
+        }
+        grid
+    }
+
+    /// Returns a copy of the grid surrounded by `modules` light modules on every side.
+    pub fn with_quiet_zone(&self, modules: i32) -> Self {
+        let mut grid = Self::default();
+        grid.copy_with_quiet_zone(self, modules);
+        grid
+    }
+
+    /// Replaces the modules of this grid with the ones of `grid` surrounded by `modules`
+    /// light modules on every side, reusing the storage of this grid.
+    pub(crate) fn copy_with_quiet_zone(&mut self, grid: &ModuleGrid, modules: i32) {
+        self.size = modules
+            .checked_mul(2)
+            .and_then(|border| grid.size.checked_add(border))
+            .expect("the grid should be at most i32::MAX modules wide")
+            .max(0);
+        self.modules.clear();
+        self.modules.resize(area(self.size), false);
+        for y in 0..grid.size {
+            for x in 0..grid.size {
+                self.set(x + modules, y + modules, grid.get(x, y));
+            }
+        }
+    }
+
+    /// Returns the number of modules per side.
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+
+    /// Returns `true` if the module at the given position is dark.
+    ///
+    /// Modules outside of the grid are light.
+    pub fn get(&self, x: i32, y: i32) -> bool {
+        (0..self.size).contains(&x)
+            && (0..self.size).contains(&y)
+            && self.modules[(y * self.size + x) as usize]
+    }
+
+    /// Sets the color of the module at the given position, which must be inside of the grid.
+    pub fn set(&mut self, x: i32, y: i32, dark: bool) {
+        assert!(
+            (0..self.size).contains(&x) && (0..self.size).contains(&y),
+            "module ({x}, {y}) is outside of the grid"
+        );
+        self.modules[(y * self.size + x) as usize] = dark;
+    }
+}
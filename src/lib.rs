@@ -1,7 +1,45 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod builder;
 mod draw;
+pub mod grid;
+mod math;
+pub mod options;
+pub mod preset;
+#[rustfmt::skip]
+#[allow(dead_code, unused_parens, clippy::all)]
+mod qrcodegen;
 pub mod rendering;
 pub mod types;
 
+#[cfg(feature = "std")]
+pub mod animation;
+
+#[cfg(feature = "std")]
+pub mod sheet;
+
+#[cfg(feature = "rayon")]
+pub mod batch;
+
+#[cfg(feature = "text")]
+pub mod caption;
+
+#[cfg(feature = "text")]
+pub mod frame;
+
+#[cfg(feature = "text")]
+pub mod text;
+
 #[cfg(feature = "ffi")]
 pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "jni")]
+pub mod android;
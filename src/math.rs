@@ -0,0 +1,28 @@
+//! Floating-point functions that are provided by `std` or, without it, by `libm`.
+
+macro_rules! math {
+    ($($name:ident($($arg:ident),+) => $libm:ident;)*) => {
+        $(
+            #[inline]
+            pub(crate) fn $name($($arg: f64),+) -> f64 {
+                #[cfg(feature = "std")]
+                return f64::$name($($arg),+);
+                #[cfg(not(feature = "std"))]
+                return libm::$libm($($arg),+);
+            }
+        )*
+    };
+}
+
+math! {
+    acos(x) => acos;
+    ceil(x) => ceil;
+    cos(x) => cos;
+    floor(x) => floor;
+    hypot(x, y) => hypot;
+    powf(x, y) => pow;
+    round(x) => round;
+    sin(x) => sin;
+    sqrt(x) => sqrt;
+    tan(x) => tan;
+}
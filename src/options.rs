@@ -0,0 +1,315 @@
+use core::result::Result as StdResult;
+
+use crate::builder::{FailedToGenerate, Placement, Result};
+use crate::draw::{draw_with, DrawScratch};
+use crate::grid::ModuleGrid;
+use crate::math;
+use crate::qrcodegen::QrCode;
+use crate::rendering::display_list::{DisplayList, DisplayListOpReceiver};
+use crate::rendering::geometry::Point;
+use crate::types::{CornerRadii, ErrorCorrectionLevel, Length, LogoRegion, ModuleShape, Size};
+
+/// The data encoded in a QR Code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payload<'a> {
+    /// Bytes, encoded as they are.
+    Binary(&'a [u8]),
+    /// Unicode text, encoded in the most compact modes for its characters.
+    Text(&'a str),
+}
+
+impl<'a> From<&'a str> for Payload<'a> {
+    fn from(text: &'a str) -> Self {
+        Payload::Text(text)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Payload<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Payload::Binary(bytes)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for Payload<'a> {
+    fn from(bytes: &'a [u8; N]) -> Self {
+        Payload::Binary(bytes)
+    }
+}
+
+/// The settings that control how a QR Code is built, independent of its data.
+///
+/// Unlike a [`Builder`](crate::builder::Builder), options hold no data, so that one
+/// configuration can be stored, shared between threads and used to build any number of codes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub error_correction_level: ErrorCorrectionLevel,
+    /// The size of the image. The code and its quiet zone are scaled to fit in its
+    /// shorter side, unless `module_size` is set.
+    pub size: Size,
+    /// The width of the empty border around the QR Code, in modules, at most
+    /// [`MAX_QUIET_ZONE`](Options::MAX_QUIET_ZONE).
+    pub quiet_zone: u32,
+    pub corner_radii: CornerRadii,
+    pub shape: ModuleShape,
+    /// The region in the middle of the QR Code that is left empty for a logo.
+    pub logo_region: LogoRegion,
+    /// Draws the light modules and the quiet zone instead of the dark modules.
+    pub invert: bool,
+    /// The resolution of the image, in units per inch.
+    pub dpi: f64,
+    /// The physical side of every module, which makes the image as large as the code
+    /// and its quiet zone.
+    pub module_size: Option<Length>,
+    /// The smallest physical side of the modules, below which building fails.
+    pub min_module_size: Option<Length>,
+    /// Makes the modules a whole number of units wide.
+    pub snap_to_pixels: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            error_correction_level: ErrorCorrectionLevel::Medium,
+            size: Size::new(256.0, 256.0),
+            quiet_zone: 0,
+            corner_radii: CornerRadii::default(),
+            shape: ModuleShape::default(),
+            logo_region: LogoRegion::default(),
+            invert: false,
+            dpi: 72.0,
+            module_size: None,
+            min_module_size: None,
+            snap_to_pixels: false,
+        }
+    }
+}
+
+impl Options {
+    /// The widest quiet zone, in modules, far wider than the 4 modules that the
+    /// QR Code specification requires.
+    pub const MAX_QUIET_ZONE: u32 = 100;
+
+    /// Encodes the payload and returns where its QR Code is drawn, including the size of the image.
+    pub fn placement<'a>(&self, payload: impl Into<Payload<'a>>) -> Result<Placement> {
+        let modules = self.build_modules(payload.into())?;
+        self.placement_for(modules.size())
+    }
+
+    /// Builds the QR Code of the payload and returns the display list of its drawing operations.
+    pub fn build<'a>(&self, payload: impl Into<Payload<'a>>) -> Result<DisplayList> {
+        let (display_list, _) = self.build_with(payload.into(), &mut DrawScratch::default())?;
+        Ok(display_list)
+    }
+
+    /// Builds the QR Code of the payload and returns its modules, surrounded by the quiet zone.
+    pub fn build_module_grid<'a>(&self, payload: impl Into<Payload<'a>>) -> Result<ModuleGrid> {
+        let modules = self.build_modules(payload.into())?;
+        Ok(modules.with_quiet_zone(self.quiet_zone_modules()?))
+    }
+
+    /// Builds the QR Code of the payload and sends its drawing operations to the receiver
+    /// as they are produced.
+    ///
+    /// Nothing is sent if the code cannot be built.
+    pub fn build_with_receiver<'a, R>(
+        &self,
+        payload: impl Into<Payload<'a>>,
+        receiver: &mut R,
+    ) -> Result<()>
+    where
+        R: DisplayListOpReceiver + ?Sized,
+    {
+        self.build_into(payload.into(), &mut DrawScratch::default(), receiver)?;
+        Ok(())
+    }
+
+    fn build_qr_code(&self, payload: Payload) -> StdResult<QrCode, ()> {
+        let ecl = self.error_correction_level;
+        let result = match payload {
+            Payload::Binary(data) => QrCode::encode_binary(data, ecl),
+            Payload::Text(text) => QrCode::encode_text(text, ecl),
+        };
+        result.map_err(|_| ())
+    }
+
+    /// Encodes the payload to QR Code modules, clearing the logo region.
+    pub(crate) fn build_modules(&self, payload: Payload) -> Result<ModuleGrid> {
+        self.quiet_zone_modules()?;
+        let code = self
+            .build_qr_code(payload)
+            .map_err(|_| FailedToGenerate::DataTooLong)?;
+        let mut modules = ModuleGrid::from_qr_code(&code);
+        self.logo_region.clear(&mut modules);
+        Ok(modules)
+    }
+
+    /// Returns the width of the quiet zone, if it is at most [`Options::MAX_QUIET_ZONE`].
+    fn quiet_zone_modules(&self) -> Result<i32> {
+        match i32::try_from(self.quiet_zone) {
+            Ok(modules) if self.quiet_zone <= Self::MAX_QUIET_ZONE => Ok(modules),
+            _ => Err(FailedToGenerate::QuietZoneTooWide {
+                quiet_zone: self.quiet_zone,
+            }),
+        }
+    }
+
+    /// Returns where a code with the given number of modules per side is drawn.
+    pub(crate) fn placement_for(&self, code_size: i32) -> Result<Placement> {
+        // Counted in floating point, where no quiet zone is wide enough to overflow.
+        let modules = f64::from(code_size) + 2.0 * f64::from(self.quiet_zone);
+        let available = self.size.width.min(self.size.height);
+        let module_size = match (self.module_size, self.snap_to_pixels) {
+            (Some(module_size), false) => module_size.to_units(self.dpi),
+            (Some(module_size), true) => math::round(module_size.to_units(self.dpi)).max(1.0),
+            (None, false) => available / modules,
+            // An image with less than a pixel per module keeps fractional modules,
+            // since whole ones would not fit in it.
+            (None, true) => match math::floor(available / modules) {
+                snapped if snapped >= 1.0 => snapped,
+                _ => available / modules,
+            },
+        };
+        if let Some(min_module_size) = self.min_module_size {
+            // Allow for the rounding of lengths that are converted back and forth.
+            if module_size < min_module_size.to_units(self.dpi) * (1.0 - 1e-9) {
+                return Err(FailedToGenerate::ModuleTooSmall {
+                    module_size: module_size / self.dpi * 25.4,
+                    min_module_size: min_module_size.to_millimeters(),
+                });
+            }
+        }
+
+        let side = module_size * modules;
+        Ok(match (self.module_size, self.snap_to_pixels) {
+            (Some(_), _) => Placement {
+                module_size,
+                origin: Point::default(),
+                size: Size::new(side, side),
+            },
+            (None, false) => Placement {
+                module_size,
+                origin: Point::default(),
+                size: self.size,
+            },
+            // Centering on whole pixels keeps the edges of the modules sharp.
+            (None, true) => Placement {
+                module_size,
+                origin: Point::new(
+                    math::floor((self.size.width - side) / 2.0).max(0.0),
+                    math::floor((self.size.height - side) / 2.0).max(0.0),
+                ),
+                size: self.size,
+            },
+        })
+    }
+
+    /// Builds the QR Code of the payload with the buffers of `scratch` and returns
+    /// the display list of its drawing operations and the size of its image.
+    pub(crate) fn build_with(
+        &self,
+        payload: Payload,
+        scratch: &mut DrawScratch,
+    ) -> Result<(DisplayList, Size)> {
+        let mut display_list = DisplayList::new();
+        let placement = self.build_into(payload, scratch, &mut display_list)?;
+        Ok((display_list, placement.size))
+    }
+
+    /// Builds the QR Code of the payload with the buffers of `scratch`, sending its drawing
+    /// operations to the receiver as they are produced, and returns where it is drawn.
+    fn build_into<R>(
+        &self,
+        payload: Payload,
+        scratch: &mut DrawScratch,
+        receiver: &mut R,
+    ) -> Result<Placement>
+    where
+        R: DisplayListOpReceiver + ?Sized,
+    {
+        let modules = self.build_modules(payload)?;
+        let placement = self.placement_for(modules.size())?;
+        // Produce draw calls for the image derived from the code.
+        draw_with(
+            scratch,
+            &modules,
+            placement,
+            self.quiet_zone_modules()?,
+            self.corner_radii,
+            self.shape,
+            self.invert,
+            receiver,
+        );
+        Ok(placement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::raster::render_mask;
+
+    /// The number of modules per side of a version 1 code.
+    const VERSION_1: i32 = 21;
+
+    fn snapped(side: f64) -> Options {
+        Options {
+            size: Size::new(side, side),
+            quiet_zone: 2,
+            snap_to_pixels: true,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn snaps_modules_to_whole_pixels() {
+        let placement = snapped(100.0).placement_for(VERSION_1).unwrap();
+        assert_eq!(placement.module_size, 4.0);
+        assert_eq!(placement.origin, Point::new(0.0, 0.0));
+        let placement = snapped(110.0).placement_for(VERSION_1).unwrap();
+        assert_eq!(placement.module_size, 4.0);
+        assert_eq!(placement.origin, Point::new(5.0, 5.0));
+    }
+
+    #[test]
+    fn keeps_fractional_modules_below_a_pixel_per_module() {
+        let placement = snapped(20.0).placement_for(VERSION_1).unwrap();
+        assert_eq!(placement.module_size, 20.0 / 25.0);
+        assert_eq!(placement.origin, Point::new(0.0, 0.0));
+        assert_eq!(placement.size, Size::new(20.0, 20.0));
+    }
+
+    #[test]
+    fn fits_the_widest_quiet_zone() {
+        let options = Options {
+            quiet_zone: Options::MAX_QUIET_ZONE,
+            ..Options::default()
+        };
+        let placement = options.placement_for(VERSION_1).unwrap();
+        assert_eq!(placement.module_size, 256.0 / 221.0);
+        let grid = options.build_module_grid("x").unwrap();
+        assert_eq!(grid.size(), 221);
+        let display_list = options.build("x").unwrap();
+        let mask = render_mask(&display_list, 256, 256);
+        // The code starts 100 modules, or 115.8 pixels, from the top left corner,
+        // and the center of its top left finder pattern is dark.
+        assert_eq!(mask.coverage(114, 119), 0);
+        assert_eq!(mask.coverage(119, 119), 255);
+    }
+
+    #[test]
+    fn rejects_quiet_zones_that_overflow() {
+        for quiet_zone in [Options::MAX_QUIET_ZONE + 1, 1 << 30, u32::MAX] {
+            let options = Options {
+                quiet_zone,
+                invert: true,
+                ..Options::default()
+            };
+            // Counting the modules does not overflow, whatever the quiet zone.
+            let placement = options.placement_for(VERSION_1).unwrap();
+            assert!(placement.module_size > 0.0 && placement.module_size.is_finite());
+            let error = FailedToGenerate::QuietZoneTooWide { quiet_zone };
+            assert_eq!(options.build("x").unwrap_err(), error);
+            assert_eq!(options.build_module_grid("x").unwrap_err(), error);
+        }
+    }
+}
@@ -0,0 +1,617 @@
+//! Named looks of QR Codes, which describe their shape, colors and decorations apart from
+//! their data and their size.
+//!
+//! With the `style-files` feature, presets are read from TOML or JSON files, so that looks
+//! can be changed without changing code. Every setting is optional and defaults to the
+//! [rounded](Preset::rounded) preset:
+//!
+//! ```toml
+//! shape = "dots"              # or "connected"
+//! quiet_zone = 4
+//!
+//! [radii]                     # in modules
+//! module = 0.5
+//! concave = 0.0
+//! finder_outer = 2.1
+//! finder_inner = 1.3
+//! finder_center = 1.5
+//!
+//! [colors]                    # as #rgb, #rrggbb or #rrggbbaa
+//! foreground = "#1a1a2e"
+//! finder = "#e94560"          # or "none" to use the foreground
+//! background = "#ffffff"      # or "none" for a transparent background
+//! background_radius = 12.0    # in the units of the image
+//!
+//! [logo_region]               # in modules
+//! width = 7
+//! height = 7
+//!
+//! [frame]                     # with the `text` feature
+//! template = "speech_bubble"  # or "rounded_border" or "bottom_banner"
+//! label = "SCAN ME"
+//! ```
+
+#[cfg(any(feature = "text", feature = "style-files"))]
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::{self, Display};
+
+use crate::builder::Builder;
+#[cfg(feature = "text")]
+use crate::frame::FrameTemplate;
+use crate::options::Options;
+use crate::rendering::style::{Background, Style};
+use crate::types::{Color, CornerRadii, LogoRegion, ModuleShape};
+
+/// The colors of a [`Preset`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Colors {
+    /// The color of the modules.
+    #[cfg_attr(feature = "serde", serde(with = "hex"))]
+    pub foreground: Color,
+    /// The color of the finder patterns, or `None` to use the foreground.
+    #[cfg_attr(feature = "serde", serde(with = "hex::optional"))]
+    pub finder: Option<Color>,
+    /// The color behind the modules, or `None` to leave it transparent.
+    #[cfg_attr(feature = "serde", serde(with = "hex::optional"))]
+    pub background: Option<Color>,
+    /// The radius of the corners of the background, in the units of the image.
+    pub background_radius: f64,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            foreground: Color::BLACK,
+            finder: None,
+            background: Some(Color::WHITE),
+            background_radius: 0.0,
+        }
+    }
+}
+
+/// The frame of a [`Preset`], drawn with a [`FrameBuilder`](crate::frame::FrameBuilder).
+#[cfg(feature = "text")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Frame {
+    pub template: FrameTemplate,
+    pub label: String,
+}
+
+#[cfg(feature = "text")]
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            template: FrameTemplate::default(),
+            label: "SCAN ME".into(),
+        }
+    }
+}
+
+/// The error type when a preset cannot be read or holds values out of their range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    /// The file is not valid TOML or JSON, or its settings have unknown names or wrong types.
+    #[cfg(feature = "style-files")]
+    Syntax(String),
+    /// A setting is outside of the range of values it may take.
+    OutOfRange {
+        /// The name of the setting, such as `radii.module`.
+        field: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "style-files")]
+            PresetError::Syntax(message) => f.write_str(message.trim_end()),
+            PresetError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } if max.is_infinite() => {
+                write!(f, "`{field}` is {value}, but must be at least {min}")
+            }
+            PresetError::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "`{field}` is {value}, but must be between {min} and {max}"
+            ),
+        }
+    }
+}
+
+impl Error for PresetError {}
+
+/// The look of a QR Code: the shape of its modules, its colors, its quiet zone,
+/// the region left empty for a logo and an optional frame.
+///
+/// Presets that are built in code or deserialized should be checked with
+/// [`Preset::validate`], which the functions that read files do.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Preset {
+    pub shape: ModuleShape,
+    pub radii: CornerRadii,
+    pub colors: Colors,
+    /// The width of the empty border around the QR Code, in modules.
+    pub quiet_zone: u32,
+    pub logo_region: LogoRegion,
+    #[cfg(feature = "text")]
+    pub frame: Option<Frame>,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self {
+            shape: ModuleShape::default(),
+            radii: CornerRadii::default(),
+            colors: Colors::default(),
+            quiet_zone: 4,
+            logo_region: LogoRegion::default(),
+            #[cfg(feature = "text")]
+            frame: None,
+        }
+    }
+}
+
+impl Preset {
+    /// The names of the built-in presets, as accepted by [`Preset::named`].
+    pub const NAMES: [&'static str; 5] = ["classic", "rounded", "dots", "liquid", "minimal"];
+
+    /// Returns the built-in preset with the given name, ignoring case.
+    pub fn named(name: &str) -> Option<Self> {
+        let preset = match name.to_ascii_lowercase().as_str() {
+            "classic" => Self::classic(),
+            "rounded" => Self::rounded(),
+            "dots" => Self::dots(),
+            "liquid" => Self::liquid(),
+            "minimal" => Self::minimal(),
+            _ => return None,
+        };
+        Some(preset)
+    }
+
+    /// Square modules, black on white, like most generated codes.
+    pub fn classic() -> Self {
+        Self {
+            radii: CornerRadii::SQUARE,
+            ..Self::default()
+        }
+    }
+
+    /// The default look of the crate, with rounded modules and finder patterns.
+    pub fn rounded() -> Self {
+        Self::default()
+    }
+
+    /// Separate round modules with round finder pattern centers.
+    pub fn dots() -> Self {
+        Self {
+            shape: ModuleShape::Dots,
+            radii: CornerRadii {
+                module: 0.5,
+                concave: 0.0,
+                finder_center: 1.5,
+                ..CornerRadii::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Modules that flow into each other, with circular finder patterns.
+    pub fn liquid() -> Self {
+        Self {
+            radii: CornerRadii {
+                module: 0.5,
+                concave: 0.5,
+                finder_outer: 3.5,
+                finder_inner: 2.5,
+                finder_center: 1.5,
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Slightly rounded modules in a soft black on a transparent background.
+    pub fn minimal() -> Self {
+        Self {
+            radii: CornerRadii {
+                module: 0.2,
+                concave: 0.0,
+                finder_outer: 1.0,
+                finder_inner: 0.6,
+                finder_center: 0.3,
+            },
+            colors: Colors {
+                foreground: Color::rgb(0x22, 0x22, 0x22),
+                background: None,
+                ..Colors::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Checks that the radii fit in the modules and finder patterns they round,
+    /// and that the radius of the background is not negative.
+    pub fn validate(&self) -> Result<(), PresetError> {
+        let radii = self.radii;
+        check("radii.module", radii.module, 0.0, 0.5)?;
+        check("radii.concave", radii.concave, 0.0, 0.5)?;
+        // The rings and the center of the finder patterns are 7, 5 and 3 modules wide.
+        check("radii.finder_outer", radii.finder_outer, 0.0, 3.5)?;
+        check("radii.finder_inner", radii.finder_inner, 0.0, 2.5)?;
+        check("radii.finder_center", radii.finder_center, 0.0, 1.5)?;
+        check(
+            "colors.background_radius",
+            self.colors.background_radius,
+            0.0,
+            f64::INFINITY,
+        )
+    }
+
+    /// Returns the default options with the shape, radii, quiet zone and logo region of the preset.
+    pub fn options(&self) -> Options {
+        Options {
+            corner_radii: self.radii,
+            shape: self.shape,
+            quiet_zone: self.quiet_zone,
+            logo_region: self.logo_region,
+            ..Options::default()
+        }
+    }
+
+    /// Returns the style that paints codes with the colors of the preset.
+    pub fn style(&self) -> Style {
+        let colors = self.colors;
+        Style {
+            foreground: colors.foreground.into(),
+            finder: colors.finder.map(Into::into),
+            background: colors.background.map(|color| Background {
+                paint: color.into(),
+                corner_radius: colors.background_radius,
+            }),
+        }
+    }
+
+    /// Reads and validates a preset from a TOML document.
+    #[cfg(feature = "style-files")]
+    pub fn from_toml(toml: &str) -> Result<Self, PresetError> {
+        let preset: Self = toml::from_str(toml).map_err(|e| PresetError::Syntax(e.to_string()))?;
+        preset.validate()?;
+        Ok(preset)
+    }
+
+    /// Reads and validates a preset from a JSON document.
+    #[cfg(feature = "style-files")]
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        let preset: Self =
+            serde_json::from_str(json).map_err(|e| PresetError::Syntax(e.to_string()))?;
+        preset.validate()?;
+        Ok(preset)
+    }
+
+    /// Returns the preset as a TOML document, as a starting point for a style file.
+    #[cfg(feature = "style-files")]
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("presets should serialize to TOML")
+    }
+}
+
+fn check(field: &'static str, value: f64, min: f64, max: f64) -> Result<(), PresetError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(PresetError::OutOfRange {
+            field,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+impl<'a> Builder<'a> {
+    /// Sets the shape, radii, quiet zone and logo region of the preset.
+    ///
+    /// The colors and the frame of the preset are applied when rendering, with
+    /// [`Preset::style`] and `Builder::frame`.
+    pub fn preset(self, preset: &Preset) -> Self {
+        self.module_shape(preset.shape)
+            .corner_radii(preset.radii)
+            .quiet_zone(preset.quiet_zone)
+            .logo_region(preset.logo_region)
+    }
+}
+
+/// Serializes colors as strings in the `#rrggbb` or `#rrggbbaa` notation.
+#[cfg(feature = "serde")]
+mod hex {
+    use alloc::format;
+    use alloc::string::String;
+
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::types::Color;
+
+    fn to_string(color: &Color) -> String {
+        match color.a {
+            255 => color.to_hex(),
+            a => format!("{}{a:02x}", color.to_hex()),
+        }
+    }
+
+    fn parse<E: Error>(s: &str) -> Result<Color, E> {
+        s.parse()
+            .map_err(|e| E::custom(format!("invalid color `{s}`: {e}")))
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        color: &Color,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(color))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Color, D::Error> {
+        parse(&String::deserialize(deserializer)?)
+    }
+
+    /// Serializes optional colors like colors, with `none` for a missing color.
+    pub(super) mod optional {
+        use super::*;
+
+        pub(crate) fn serialize<S: Serializer>(
+            color: &Option<Color>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match color {
+                Some(color) => super::serialize(color, serializer),
+                None => serializer.serialize_str("none"),
+            }
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Color>, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            if s.eq_ignore_ascii_case("none") || s.eq_ignore_ascii_case("transparent") {
+                Ok(None)
+            } else {
+                parse(&s).map(Some)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_presets_are_valid() {
+        for name in Preset::NAMES {
+            let preset = Preset::named(name).unwrap();
+            assert_eq!(preset.validate(), Ok(()), "{name}");
+            assert_eq!(Preset::named(&name.to_uppercase()), Some(preset));
+        }
+        assert_eq!(Preset::named("unknown"), None);
+    }
+
+    /// Returns the default preset with the setting of the given name set to `value`.
+    fn with(field: &str, value: f64) -> Preset {
+        let mut preset = Preset::default();
+        let setting = match field {
+            "radii.module" => &mut preset.radii.module,
+            "radii.concave" => &mut preset.radii.concave,
+            "radii.finder_outer" => &mut preset.radii.finder_outer,
+            "radii.finder_inner" => &mut preset.radii.finder_inner,
+            "radii.finder_center" => &mut preset.radii.finder_center,
+            "colors.background_radius" => &mut preset.colors.background_radius,
+            _ => unreachable!(),
+        };
+        *setting = value;
+        preset
+    }
+
+    #[test]
+    fn rejects_values_out_of_range() {
+        for (field, max) in [
+            ("radii.module", 0.5),
+            ("radii.concave", 0.5),
+            ("radii.finder_outer", 3.5),
+            ("radii.finder_inner", 2.5),
+            ("radii.finder_center", 1.5),
+            ("colors.background_radius", f64::INFINITY),
+        ] {
+            for value in [0.0, max.min(100.0)] {
+                assert_eq!(with(field, value).validate(), Ok(()), "{field} = {value}");
+            }
+            let above = max.is_finite().then_some(max + 0.1);
+            for value in [-0.1, f64::NAN].into_iter().chain(above) {
+                match with(field, value).validate() {
+                    Err(PresetError::OutOfRange {
+                        field: actual,
+                        min,
+                        max: actual_max,
+                        ..
+                    }) => {
+                        assert_eq!(actual, field);
+                        assert_eq!((min, actual_max), (0.0, max));
+                    }
+                    result => panic!("{field} = {value} gave {result:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn displays_ranges() {
+        let error = PresetError::OutOfRange {
+            field: "radii.module",
+            value: 0.75,
+            min: 0.0,
+            max: 0.5,
+        };
+        assert_eq!(
+            error.to_string(),
+            "`radii.module` is 0.75, but must be between 0 and 0.5"
+        );
+        let error = PresetError::OutOfRange {
+            field: "colors.background_radius",
+            value: -2.0,
+            min: 0.0,
+            max: f64::INFINITY,
+        };
+        assert_eq!(
+            error.to_string(),
+            "`colors.background_radius` is -2, but must be at least 0"
+        );
+    }
+
+    /// The preset described by the example of the module documentation.
+    #[cfg(all(feature = "style-files", feature = "text"))]
+    fn documented() -> Preset {
+        Preset {
+            shape: ModuleShape::Dots,
+            radii: CornerRadii {
+                module: 0.5,
+                concave: 0.0,
+                finder_outer: 2.1,
+                finder_inner: 1.3,
+                finder_center: 1.5,
+            },
+            colors: Colors {
+                foreground: Color::rgb(0x1a, 0x1a, 0x2e),
+                finder: Some(Color::rgb(0xe9, 0x45, 0x60)),
+                background: Some(Color::WHITE),
+                background_radius: 12.0,
+            },
+            quiet_zone: 4,
+            logo_region: LogoRegion::new(7, 7),
+            frame: Some(Frame {
+                template: FrameTemplate::SpeechBubble,
+                label: "SCAN ME".into(),
+            }),
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "style-files", feature = "text"))]
+    fn reads_every_documented_setting_from_toml() {
+        let toml = r##"
+            shape = "dots"
+            quiet_zone = 4
+
+            [radii]
+            module = 0.5
+            concave = 0.0
+            finder_outer = 2.1
+            finder_inner = 1.3
+            finder_center = 1.5
+
+            [colors]
+            foreground = "#1a1a2e"
+            finder = "#e94560"
+            background = "#ffffff"
+            background_radius = 12.0
+
+            [logo_region]
+            width = 7
+            height = 7
+
+            [frame]
+            template = "speech_bubble"
+            label = "SCAN ME"
+        "##;
+        assert_eq!(Preset::from_toml(toml), Ok(documented()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "style-files", feature = "text"))]
+    fn reads_every_documented_setting_from_json() {
+        let json = r##"{
+            "shape": "dots",
+            "quiet_zone": 4,
+            "radii": {
+                "module": 0.5,
+                "concave": 0.0,
+                "finder_outer": 2.1,
+                "finder_inner": 1.3,
+                "finder_center": 1.5
+            },
+            "colors": {
+                "foreground": "#1a1a2e",
+                "finder": "#e94560",
+                "background": "#ffffff",
+                "background_radius": 12.0
+            },
+            "logo_region": { "width": 7, "height": 7 },
+            "frame": { "template": "speech_bubble", "label": "SCAN ME" }
+        }"##;
+        assert_eq!(Preset::from_json(json), Ok(documented()));
+    }
+
+    #[test]
+    #[cfg(feature = "style-files")]
+    fn missing_settings_default_to_the_rounded_preset() {
+        assert_eq!(Preset::from_toml(""), Ok(Preset::rounded()));
+        assert_eq!(Preset::from_json("{}"), Ok(Preset::rounded()));
+        let preset = Preset::from_toml("[colors]\nfinder = \"none\"\nbackground = \"none\"");
+        assert_eq!(
+            preset.map(|p| (p.colors.finder, p.colors.background)),
+            Ok((None, None))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "style-files")]
+    fn rejects_unknown_settings() {
+        for toml in ["shapes = \"dots\"", "[colors]\nforground = \"#000000\""] {
+            assert!(
+                matches!(Preset::from_toml(toml), Err(PresetError::Syntax(_))),
+                "{toml}"
+            );
+        }
+        assert!(matches!(
+            Preset::from_json(r##"{"colors": {"foreground": "#000", "accent": "#f00"}}"##),
+            Err(PresetError::Syntax(_))
+        ));
+        #[cfg(feature = "text")]
+        assert!(matches!(
+            Preset::from_toml("[frame]\ntext = \"SCAN ME\""),
+            Err(PresetError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "style-files")]
+    fn files_are_validated() {
+        assert_eq!(
+            Preset::from_toml("[radii]\nmodule = 0.6"),
+            Err(PresetError::OutOfRange {
+                field: "radii.module",
+                value: 0.6,
+                min: 0.0,
+                max: 0.5,
+            })
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Python bindings, built with PyO3 and packaged with maturin as the `rounded_qr` module.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::builder::Builder;
+use crate::rendering::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use crate::rendering::style::{Color, Style};
+use crate::rendering::{raster, svg};
+use crate::types::{CornerRadii, ErrorCorrectionLevel, Size};
+
+/// The data to encode, either text or raw bytes.
+#[derive(Debug, Clone, FromPyObject)]
+enum Data {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// A path operation, as returned by `Builder.path_ops`.
+#[derive(Debug, Clone, IntoPyObject)]
+enum PathOp {
+    Point((&'static str, f64, f64)),
+    Arc((&'static str, f64, f64, f64, f64, f64, bool)),
+    Cubic((&'static str, f64, f64, f64, f64, f64, f64)),
+    Quad((&'static str, f64, f64, f64, f64)),
+    Close((&'static str,)),
+}
+
+/// A receiver that collects drawing operations as `PathOp` tuples.
+#[derive(Default)]
+struct PathOps(Vec<PathOp>);
+
+impl DisplayListOpReceiver for PathOps {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.0
+            .push(PathOp::Point(("move_to", op.point.x, op.point.y)));
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.0
+            .push(PathOp::Point(("line_to", op.point.x, op.point.y)));
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.0.push(PathOp::Arc((
+            "arc_to",
+            op.center.x,
+            op.center.y,
+            op.radius,
+            op.start_angle as f64,
+            op.end_angle as f64,
+            op.clockwise,
+        )));
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.0.push(PathOp::Cubic((
+            "cubic_to",
+            op.control1.x,
+            op.control1.y,
+            op.control2.x,
+            op.control2.y,
+            op.point.x,
+            op.point.y,
+        )));
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.0.push(PathOp::Quad((
+            "quad_to",
+            op.control.x,
+            op.control.y,
+            op.point.x,
+            op.point.y,
+        )));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.0.push(PathOp::Close(("close_path",)));
+    }
+}
+
+fn parse_color(value: &str, name: &str) -> PyResult<Color> {
+    value
+        .parse()
+        .map_err(|e| PyValueError::new_err(format!("invalid {name} color {value:?}: {e}")))
+}
+
+/// Builds rounded QR Codes.
+///
+/// Every option is a keyword argument of the constructor; the rendering methods
+/// can be called any number of times.
+#[pyclass(name = "Builder", module = "rounded_qr", frozen)]
+struct PyBuilder {
+    data: Data,
+    ecl: ErrorCorrectionLevel,
+    size: f64,
+    quiet_zone: u32,
+    radii: CornerRadii,
+    style: Style,
+}
+
+#[pymethods]
+impl PyBuilder {
+    #[new]
+    #[pyo3(signature = (
+        data,
+        *,
+        ecl = "medium",
+        size = 256.0,
+        quiet_zone = 0,
+        module_radius = None,
+        concave_radius = None,
+        finder_outer_radius = None,
+        finder_inner_radius = None,
+        finder_center_radius = None,
+        foreground = "#000000",
+        background = Some("#ffffff"),
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        data: Data,
+        ecl: &str,
+        size: f64,
+        quiet_zone: u32,
+        module_radius: Option<f64>,
+        concave_radius: Option<f64>,
+        finder_outer_radius: Option<f64>,
+        finder_inner_radius: Option<f64>,
+        finder_center_radius: Option<f64>,
+        foreground: &str,
+        background: Option<&str>,
+    ) -> PyResult<Self> {
+        let ecl = match ecl {
+            "low" | "L" => ErrorCorrectionLevel::Low,
+            "medium" | "M" => ErrorCorrectionLevel::Medium,
+            "quartile" | "Q" => ErrorCorrectionLevel::Quartile,
+            "high" | "H" => ErrorCorrectionLevel::High,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "invalid error correction level {ecl:?}, expected \"low\", \"medium\", \"quartile\" or \"high\""
+                )))
+            }
+        };
+        if !(size > 0.0 && size.is_finite()) {
+            return Err(PyValueError::new_err("size must be a positive number"));
+        }
+
+        let defaults = CornerRadii::default();
+        let radii = CornerRadii {
+            module: module_radius.unwrap_or(defaults.module),
+            concave: concave_radius.unwrap_or(defaults.concave),
+            finder_outer: finder_outer_radius.unwrap_or(defaults.finder_outer),
+            finder_inner: finder_inner_radius.unwrap_or(defaults.finder_inner),
+            finder_center: finder_center_radius.unwrap_or(defaults.finder_center),
+        };
+        radii
+            .validate()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let style = Style {
+            foreground: parse_color(foreground, "foreground")?.into(),
+            finder: None,
+            background: background
+                .map(|value| parse_color(value, "background"))
+                .transpose()?
+                .map(Into::into),
+        };
+        Ok(Self {
+            data,
+            ecl,
+            size,
+            quiet_zone,
+            radii,
+            style,
+        })
+    }
+
+    /// Returns a standalone SVG document of the code.
+    fn svg(&self) -> PyResult<String> {
+        let display_list = self.build()?;
+        Ok(svg::render(&display_list, self.image_size(), &self.style))
+    }
+
+    /// Returns a PNG image of the code, `size` pixels wide and high.
+    fn png<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let display_list = self.build()?;
+        let png = raster::render(&display_list, self.image_size(), &self.style)
+            .encode_png()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &png))
+    }
+
+    /// Returns the outline of the code as a list of tuples:
+    /// `("move_to", x, y)`, `("line_to", x, y)`,
+    /// `("arc_to", center_x, center_y, radius, start_angle, end_angle, clockwise)`
+    /// and `("close_path",)`.
+    fn path_ops(&self) -> PyResult<Vec<PathOp>> {
+        let display_list = self.build()?;
+        let mut ops = PathOps::default();
+        display_list.present(&mut ops);
+        Ok(ops.0)
+    }
+}
+
+impl PyBuilder {
+    fn image_size(&self) -> Size {
+        Size::new(self.size, self.size)
+    }
+
+    fn build(&self) -> PyResult<DisplayList> {
+        let builder = match &self.data {
+            Data::Text(text) => Builder::text(text),
+            Data::Bytes(bytes) => Builder::binary(bytes),
+        };
+        builder
+            .error_correction_level(self.ecl)
+            .size(self.image_size())
+            .quiet_zone(self.quiet_zone)
+            .corner_radii(self.radii)
+            .build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[pymodule]
+fn rounded_qr(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBuilder>()?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use crate::math;
+
+use super::display_list::ArcTo;
+use super::geometry::Point;
+
+impl ArcTo {
+    /// Returns the angle covered by the arc, in radians.
+    ///
+    /// The result is positive for clockwise arcs and negative otherwise.
+    pub fn sweep_angle(&self) -> f64 {
+        let mut sweep = (self.end_angle - self.start_angle) as f64;
+        if self.clockwise && sweep < 0.0 {
+            sweep += 2.0 * PI;
+        } else if !self.clockwise && sweep > 0.0 {
+            sweep -= 2.0 * PI;
+        }
+        sweep
+    }
+
+    /// Returns the point at the specified angle on the circle of the arc.
+    pub fn point_at(&self, angle: f64) -> Point {
+        Point::new(
+            self.center.x + self.radius * math::cos(angle),
+            self.center.y + self.radius * math::sin(angle),
+        )
+    }
+
+    /// Returns the point where the arc begins.
+    pub fn start_point(&self) -> Point {
+        self.point_at(self.start_angle as f64)
+    }
+
+    /// Returns the point where the arc ends.
+    pub fn end_point(&self) -> Point {
+        self.point_at(self.start_angle as f64 + self.sweep_angle())
+    }
+
+    /// Approximates the arc with cubic Bézier curves, each spanning at most a quarter turn.
+    ///
+    /// Every item holds the two control points and the end point of a curve,
+    /// the first curve starting at [`ArcTo::start_point`].
+    pub fn to_cubics(&self) -> Vec<[Point; 3]> {
+        let sweep = self.sweep_angle();
+        let count = math::ceil(sweep.abs() / FRAC_PI_2 - 1e-6).max(1.0) as usize;
+        let step = sweep / count as f64;
+        let k = 4.0 / 3.0 * math::tan(step / 4.0) * self.radius;
+
+        let mut angle = self.start_angle as f64;
+        (0..count)
+            .map(|_| {
+                let next = angle + step;
+                let start = self.point_at(angle);
+                let end = self.point_at(next);
+                let control1 = Point::new(
+                    start.x - k * math::sin(angle),
+                    start.y + k * math::cos(angle),
+                );
+                let control2 = Point::new(end.x + k * math::sin(next), end.y - k * math::cos(next));
+                angle = next;
+                [control1, control2, end]
+            })
+            .collect()
+    }
+
+    /// Approximates the arc with line segments that deviate from it by at most `tolerance`.
+    ///
+    /// The returned points exclude [`ArcTo::start_point`] and end with [`ArcTo::end_point`].
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        let sweep = self.sweep_angle();
+        let count = if self.radius <= tolerance {
+            1
+        } else {
+            let step = 2.0 * math::acos(1.0 - tolerance / self.radius);
+            math::ceil(sweep.abs() / step).max(1.0) as usize
+        };
+        let start = self.start_angle as f64;
+        (1..=count)
+            .map(|i| self.point_at(start + sweep * i as f64 / count as f64))
+            .collect()
+    }
+}
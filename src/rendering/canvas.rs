@@ -0,0 +1,311 @@
+use std::fmt::Write;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::format_number as num;
+use super::geometry::Size;
+use super::style::Style;
+use super::svg;
+
+/// A receiver that converts drawing operations to JavaScript calls on a
+/// `CanvasRenderingContext2D`, one statement per line.
+#[derive(Debug, Clone)]
+pub struct CanvasScriptBuilder {
+    context: String,
+    script: String,
+}
+
+impl CanvasScriptBuilder {
+    /// Creates a builder whose statements are called on the variable named `context`.
+    pub fn new(context: &str) -> Self {
+        Self {
+            context: context.to_owned(),
+            script: String::new(),
+        }
+    }
+
+    /// Returns the statements received so far.
+    pub fn finish(self) -> String {
+        self.script
+    }
+}
+
+impl DisplayListOpReceiver for CanvasScriptBuilder {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        _ = writeln!(
+            self.script,
+            "{}.moveTo({}, {});",
+            self.context,
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        _ = writeln!(
+            self.script,
+            "{}.lineTo({}, {});",
+            self.context,
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        // Canvas angles increase clockwise like the ones of the display list.
+        _ = writeln!(
+            self.script,
+            "{}.arc({}, {}, {}, {}, {}, {});",
+            self.context,
+            num(op.center.x),
+            num(op.center.y),
+            num(op.radius),
+            op.start_angle,
+            op.end_angle,
+            !op.clockwise
+        );
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        _ = writeln!(
+            self.script,
+            "{}.bezierCurveTo({}, {}, {}, {}, {}, {});",
+            self.context,
+            num(op.control1.x),
+            num(op.control1.y),
+            num(op.control2.x),
+            num(op.control2.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        _ = writeln!(
+            self.script,
+            "{}.quadraticCurveTo({}, {}, {}, {});",
+            self.context,
+            num(op.control.x),
+            num(op.control.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        _ = writeln!(self.script, "{}.closePath();", self.context);
+    }
+}
+
+/// Returns the outline of the display list as a string for the `Path2D` constructor,
+/// which accepts SVG path data.
+pub fn path2d_data(display_list: &DisplayList) -> String {
+    svg::path_data(display_list)
+}
+
+/// Returns JavaScript statements that paint the display list on the
+/// `CanvasRenderingContext2D` held by the variable named `context`.
+///
+/// `size` is the size the display list was built with, covered by the background.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style, context: &str) -> String {
+    let mut script = String::new();
+    if let Some(background) = &style.background {
+        _ = writeln!(
+            script,
+            "{context}.fillStyle = \"{}\";",
+            background.paint.approximate_color().to_css()
+        );
+        _ = writeln!(
+            script,
+            "{context}.fillRect(0, 0, {}, {});",
+            num(size.width),
+            num(size.height)
+        );
+    }
+    for (display_list, paint) in style.fills(display_list) {
+        _ = writeln!(
+            script,
+            "{context}.fillStyle = \"{}\";",
+            paint.approximate_color().to_css()
+        );
+        _ = writeln!(script, "{context}.beginPath();");
+        let mut builder = CanvasScriptBuilder::new(context);
+        display_list.present(&mut builder);
+        script.push_str(&builder.finish());
+        _ = writeln!(script, "{context}.fill();");
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::rendering::geometry::{Point, Rect};
+
+    /// The arguments of a `context.arc` call.
+    #[derive(Debug)]
+    struct CanvasArc {
+        center: Point,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        anticlockwise: bool,
+    }
+
+    /// An SVG `A` command, with the point it starts from.
+    #[derive(Debug)]
+    struct SvgArc {
+        from: Point,
+        radius: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    }
+
+    impl SvgArc {
+        /// Returns the center of the circle, following the conversion from endpoint
+        /// to center parameterization in the SVG specification.
+        fn center(&self) -> Point {
+            let (hx, hy) = (
+                (self.from.x - self.to.x) / 2.0,
+                (self.from.y - self.to.y) / 2.0,
+            );
+            let distance = hx * hx + hy * hy;
+            let factor = (self.radius * self.radius / distance - 1.0).max(0.0).sqrt();
+            let sign = if self.large_arc == self.sweep {
+                -1.0
+            } else {
+                1.0
+            };
+            Point::new(
+                sign * factor * hy + (self.from.x + self.to.x) / 2.0,
+                -sign * factor * hx + (self.from.y + self.to.y) / 2.0,
+            )
+        }
+    }
+
+    fn canvas_arcs(display_list: &DisplayList) -> Vec<CanvasArc> {
+        let mut builder = CanvasScriptBuilder::new("ctx");
+        display_list.present(&mut builder);
+        builder
+            .finish()
+            .lines()
+            .filter_map(|line| line.strip_prefix("ctx.arc("))
+            .map(|args| {
+                let args: Vec<_> = args.trim_end_matches(");").split(", ").collect();
+                let number = |i: usize| args[i].parse::<f64>().unwrap();
+                CanvasArc {
+                    center: Point::new(number(0), number(1)),
+                    radius: number(2),
+                    start_angle: number(3),
+                    end_angle: number(4),
+                    anticlockwise: args[5].parse().unwrap(),
+                }
+            })
+            .collect()
+    }
+
+    fn svg_arcs(display_list: &DisplayList) -> Vec<SvgArc> {
+        // Commands are written before their first number, so split them off.
+        let data: String = svg::path_data(display_list)
+            .chars()
+            .flat_map(|c| [Some(c), c.is_alphabetic().then_some(' ')])
+            .flatten()
+            .collect();
+        let mut tokens = data.split_whitespace();
+        let mut arcs = vec![];
+        let (mut start, mut current) = (Point::default(), Point::default());
+        while let Some(command) = tokens.next() {
+            let mut number = || tokens.next().unwrap().parse::<f64>().unwrap();
+            match command {
+                "M" => {
+                    current = Point::new(number(), number());
+                    start = current;
+                }
+                "L" => current = Point::new(number(), number()),
+                "A" => {
+                    let radius = number();
+                    _ = (number(), number());
+                    let (large_arc, sweep) = (number() == 1.0, number() == 1.0);
+                    let to = Point::new(number(), number());
+                    arcs.push(SvgArc {
+                        from: current,
+                        radius,
+                        large_arc,
+                        sweep,
+                        to,
+                    });
+                    current = to;
+                }
+                "Z" => current = start,
+                command => panic!("unexpected command {command}"),
+            }
+        }
+        arcs
+    }
+
+    fn assert_near(a: Point, b: Point) {
+        let distance = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+        assert!(distance < 0.01, "{a:?} is not near {b:?}");
+    }
+
+    /// Checks that every canvas arc traces the same circle in the same direction,
+    /// between the same points, as the SVG arc of the same display list.
+    fn assert_arcs_match_svg(display_list: &DisplayList) {
+        let (canvas, svg) = (canvas_arcs(display_list), svg_arcs(display_list));
+        assert!(!canvas.is_empty());
+        assert_eq!(canvas.len(), svg.len());
+        for (canvas, svg) in canvas.iter().zip(&svg) {
+            assert!((canvas.radius - svg.radius).abs() < 1e-3);
+            assert_near(canvas.center, svg.center());
+            assert_eq!(canvas.anticlockwise, !svg.sweep, "{canvas:?} {svg:?}");
+            let point_at = |angle: f64| {
+                Point::new(
+                    canvas.center.x + canvas.radius * angle.cos(),
+                    canvas.center.y + canvas.radius * angle.sin(),
+                )
+            };
+            assert_near(point_at(canvas.start_angle), svg.from);
+            assert_near(point_at(canvas.end_angle), svg.to);
+        }
+    }
+
+    #[test]
+    fn arcs_match_svg_for_a_rounded_code() {
+        let display_list = Builder::text("rounded-qr").quiet_zone(2).build().unwrap();
+        assert_arcs_match_svg(&display_list);
+    }
+
+    #[test]
+    fn arcs_match_svg_for_an_inverted_code() {
+        let display_list = Builder::text("rounded-qr")
+            .quiet_zone(2)
+            .invert(true)
+            .build()
+            .unwrap();
+        assert_arcs_match_svg(&display_list);
+    }
+
+    #[test]
+    fn arcs_match_svg_in_both_directions() {
+        let rect = DisplayList::rounded_rect(
+            Rect {
+                origin: Point::new(10.0, 20.0),
+                size: Size::new(30.0, 40.0),
+            },
+            5.0,
+        );
+        assert_arcs_match_svg(&rect);
+        let arcs = canvas_arcs(&rect.reversed());
+        assert!(arcs.iter().all(|arc| arc.anticlockwise));
+        assert_arcs_match_svg(&rect.reversed());
+    }
+
+    #[test]
+    fn path2d_data_is_svg_path_data() {
+        let display_list = Builder::text("rounded-qr").build().unwrap();
+        assert_eq!(path2d_data(&display_list), svg::path_data(&display_list));
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::Write;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::geometry::{Point, Size};
+use super::{format_number, format_number_with_precision};
+
+/// The UI frameworks that source code can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    /// A SwiftUI `Shape`.
+    SwiftUi,
+    /// A function returning a Flutter `Path`.
+    Flutter,
+    /// A function returning a Jetpack Compose `Path`.
+    Compose,
+}
+
+/// Formats a coordinate of the unit square.
+fn unit(value: f64) -> String {
+    format_number_with_precision(value, 5)
+}
+
+/// A receiver that writes the statements reproducing the drawing operations,
+/// with coordinates divided by `extent` so that they fit in the unit square.
+struct SourceWriter {
+    framework: Framework,
+    extent: f64,
+    body: String,
+}
+
+impl SourceWriter {
+    fn point(&self, point: Point) -> (String, String) {
+        (unit(point.x / self.extent), unit(point.y / self.extent))
+    }
+}
+
+impl DisplayListOpReceiver for SourceWriter {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        let (x, y) = self.point(op.point);
+        _ = match self.framework {
+            Framework::SwiftUi => writeln!(self.body, "        path.move(to: p({x}, {y}))"),
+            Framework::Flutter => writeln!(self.body, "    ..moveTo({x} * s, {y} * s)"),
+            Framework::Compose => writeln!(self.body, "        moveTo({x}f * s, {y}f * s)"),
+        };
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        let (x, y) = self.point(op.point);
+        _ = match self.framework {
+            Framework::SwiftUi => writeln!(self.body, "        path.addLine(to: p({x}, {y}))"),
+            Framework::Flutter => writeln!(self.body, "    ..lineTo({x} * s, {y} * s)"),
+            Framework::Compose => writeln!(self.body, "        lineTo({x}f * s, {y}f * s)"),
+        };
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        let radius = unit(op.radius / self.extent);
+        _ = match self.framework {
+            Framework::SwiftUi => {
+                let (x, y) = self.point(op.center);
+                let start = unit(op.start_angle as f64);
+                let end = unit(op.start_angle as f64 + op.sweep_angle());
+                // SwiftUI interprets the direction as if the y-axis pointed up.
+                writeln!(
+                    self.body,
+                    "        path.addArc(center: p({x}, {y}), radius: {radius} * s, \
+                     startAngle: .radians({start}), endAngle: .radians({end}), clockwise: {})",
+                    !op.clockwise
+                )
+            }
+            Framework::Flutter => {
+                let (x, y) = self.point(op.end_point());
+                writeln!(
+                    self.body,
+                    "    ..arcToPoint(Offset({x} * s, {y} * s), radius: Radius.circular({radius} * s), \
+                     clockwise: {}, largeArc: {})",
+                    op.clockwise,
+                    op.sweep_angle().abs() > std::f64::consts::PI
+                )
+            }
+            Framework::Compose => {
+                let corner = Point::new(op.center.x - op.radius, op.center.y - op.radius);
+                let (left, top) = self.point(corner);
+                let diameter = unit(op.radius * 2.0 / self.extent);
+                writeln!(
+                    self.body,
+                    "        arcTo(Rect(Offset({left}f * s, {top}f * s), Size({diameter}f * s, {diameter}f * s)), \
+                     {}f, {}f, false)",
+                    format_number((op.start_angle as f64).to_degrees()),
+                    format_number(op.sweep_angle().to_degrees())
+                )
+            }
+        };
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        let (x1, y1) = self.point(op.control1);
+        let (x2, y2) = self.point(op.control2);
+        let (x, y) = self.point(op.point);
+        _ = match self.framework {
+            Framework::SwiftUi => writeln!(
+                self.body,
+                "        path.addCurve(to: p({x}, {y}), control1: p({x1}, {y1}), control2: p({x2}, {y2}))"
+            ),
+            Framework::Flutter => writeln!(
+                self.body,
+                "    ..cubicTo({x1} * s, {y1} * s, {x2} * s, {y2} * s, {x} * s, {y} * s)"
+            ),
+            Framework::Compose => writeln!(
+                self.body,
+                "        cubicTo({x1}f * s, {y1}f * s, {x2}f * s, {y2}f * s, {x}f * s, {y}f * s)"
+            ),
+        };
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let (x1, y1) = self.point(op.control);
+        let (x, y) = self.point(op.point);
+        _ = match self.framework {
+            Framework::SwiftUi => writeln!(
+                self.body,
+                "        path.addQuadCurve(to: p({x}, {y}), control: p({x1}, {y1}))"
+            ),
+            Framework::Flutter => writeln!(
+                self.body,
+                "    ..quadraticBezierTo({x1} * s, {y1} * s, {x} * s, {y} * s)"
+            ),
+            Framework::Compose => writeln!(
+                self.body,
+                "        quadraticBezierTo({x1}f * s, {y1}f * s, {x}f * s, {y}f * s)"
+            ),
+        };
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        _ = match self.framework {
+            Framework::SwiftUi => writeln!(self.body, "        path.closeSubpath()"),
+            Framework::Flutter => writeln!(self.body, "    ..close()"),
+            Framework::Compose => writeln!(self.body, "        close()"),
+        };
+    }
+}
+
+/// Generates source code that reproduces the display list in the given framework.
+///
+/// The coordinates are normalized to a unit square, and the generated code scales them
+/// to the shorter side of the frame it is drawn in. `name` is the name of the generated
+/// type for SwiftUI, or of the generated function for Flutter and Compose.
+/// `size` is the size the display list was built with.
+pub fn render(display_list: &DisplayList, size: Size, framework: Framework, name: &str) -> String {
+    let mut writer = SourceWriter {
+        framework,
+        extent: size.width.min(size.height),
+        body: String::new(),
+    };
+    display_list.present(&mut writer);
+    let mut body = writer.body;
+    if framework == Framework::Flutter {
+        // Terminate the cascade of the path.
+        body.truncate(body.trim_end().len());
+        body.push_str(";\n");
+    }
+
+    match framework {
+        Framework::SwiftUi => format!(
+            "import SwiftUI\n\
+             \n\
+             struct {name}: Shape {{\n    \
+             func path(in rect: CGRect) -> Path {{\n        \
+             let s = min(rect.width, rect.height)\n        \
+             func p(_ x: CGFloat, _ y: CGFloat) -> CGPoint {{\n            \
+             CGPoint(x: rect.minX + x * s, y: rect.minY + y * s)\n        \
+             }}\n        \
+             var path = Path()\n\
+             {body}        \
+             return path\n    \
+             }}\n\
+             }}\n"
+        ),
+        Framework::Flutter => format!(
+            "import 'dart:math' as math;\n\
+             \n\
+             import 'package:flutter/painting.dart';\n\
+             \n\
+             Path {name}(Size size) {{\n  \
+             final s = math.min(size.width, size.height);\n  \
+             return Path()\n\
+             {body}\
+             }}\n"
+        ),
+        Framework::Compose => format!(
+            "import androidx.compose.ui.geometry.Offset\n\
+             import androidx.compose.ui.geometry.Rect\n\
+             import androidx.compose.ui.geometry.Size\n\
+             import androidx.compose.ui.graphics.Path\n\
+             \n\
+             fun {name}(size: Size): Path {{\n    \
+             val s = minOf(size.width, size.height)\n    \
+             return Path().apply {{\n\
+             {body}    \
+             }}\n\
+             }}\n"
+        ),
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+
+use crate::math;
+
+use super::display_list::{CubicTo, QuadTo};
+use super::geometry::Point;
+
+impl CubicTo {
+    /// Creates the cubic Bézier curve that traces the same path as the quadratic
+    /// Bézier curve starting at `start` with the given control point.
+    pub fn from_quadratic(start: Point, control: Point, point: Point) -> Self {
+        let towards_control = |p: Point| {
+            Point::new(
+                p.x + (control.x - p.x) * 2.0 / 3.0,
+                p.y + (control.y - p.y) * 2.0 / 3.0,
+            )
+        };
+        Self {
+            control1: towards_control(start),
+            control2: towards_control(point),
+            point,
+        }
+    }
+
+    /// Returns the point of the curve starting at `start` at the parameter `t`, between 0 and 1.
+    pub fn point_at(&self, start: Point, t: f64) -> Point {
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        Point::new(
+            a * start.x + b * self.control1.x + c * self.control2.x + d * self.point.x,
+            a * start.y + b * self.control1.y + c * self.control2.y + d * self.point.y,
+        )
+    }
+
+    /// Approximates the curve starting at `start` with line segments that deviate from it
+    /// by at most `tolerance`.
+    ///
+    /// The returned points exclude `start` and end with [`CubicTo::point`].
+    pub fn flatten(&self, start: Point, tolerance: f64) -> Vec<Point> {
+        // The distance between a curve and its chords is bounded by the second differences
+        // of its control points, divided by the square of the number of segments.
+        let second_difference = |a: Point, b: Point, c: Point| {
+            math::hypot(a.x - 2.0 * b.x + c.x, a.y - 2.0 * b.y + c.y)
+        };
+        let deviation = second_difference(start, self.control1, self.control2)
+            .max(second_difference(self.control1, self.control2, self.point));
+        let count = math::ceil(math::sqrt(0.75 * deviation / tolerance)).max(1.0) as usize;
+        (1..=count)
+            .map(|i| self.point_at(start, i as f64 / count as f64))
+            .collect()
+    }
+}
+
+impl QuadTo {
+    /// Returns the cubic Bézier curve that traces the same path as this curve
+    /// when it starts at `start`.
+    pub fn to_cubic(&self, start: Point) -> CubicTo {
+        CubicTo::from_quadratic(start, self.control, self.point)
+    }
+}
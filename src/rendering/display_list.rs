@@ -1,18 +1,249 @@
-use super::geometry::Point;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::geometry::{Point, Rect};
 use paste::paste;
 
+/// The part of the QR Code that the subsequent operations draw.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Layer {
+    /// The data, timing and alignment modules.
+    #[default]
+    Data,
+    /// The three finder patterns in the corners.
+    Finder,
+}
+
 /// An object that encapsulates a sequence of rendering operations
 /// that can be dispatched to the render backend later.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DisplayList {
     ops: Vec<DisplayListOp>,
 }
 
-pub(crate) struct DisplayListRecorder<'d> {
+/// An object that appends operations to a display list.
+pub struct DisplayListRecorder<'d> {
     display_list: &'d mut DisplayList,
 }
 
 impl DisplayList {
+    /// The version of the serialized representation of a display list.
+    ///
+    /// It is bumped whenever the set of operations or their fields change,
+    /// so that consumers can reject data they do not understand.
+    pub const SCHEMA_VERSION: u32 = 4;
+
+    /// Creates an empty display list.
+    pub fn new() -> Self {
+        Self { ops: vec![] }
+    }
+
+    /// Returns a recorder that appends operations to this display list.
+    pub fn begin_recording(&mut self) -> DisplayListRecorder<'_> {
+        DisplayListRecorder { display_list: self }
+    }
+
+    /// Appends an operation to the end of the display list.
+    pub fn add_op(&mut self, op: DisplayListOp) {
+        self.ops.push(op);
+    }
+
+    /// Returns the number of operations in the display list.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the display list contains no operations.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns a display list of the operations that draw the given layer.
+    ///
+    /// Operations that precede the first layer marker belong to [`Layer::Data`].
+    pub fn layer(&self, layer: Layer) -> DisplayList {
+        let mut current = Layer::default();
+        self.ops
+            .iter()
+            .filter(|op| {
+                if let DisplayListOp::BeginLayer(op) = op {
+                    current = op.layer;
+                    return false;
+                }
+                current == layer
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Creates a display list that traces a rectangle with rounded corners, clockwise.
+    ///
+    /// The radius is clamped to half of the shorter side of the rectangle.
+    pub fn rounded_rect(rect: Rect, radius: f64) -> DisplayList {
+        use core::f32::consts::{FRAC_PI_2, PI};
+
+        let Rect { origin, size } = rect;
+        let r = radius.min(size.width.min(size.height) / 2.0).max(0.0);
+        let (left, top) = (origin.x, origin.y);
+        let (right, bottom) = (left + size.width, top + size.height);
+
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        recorder.move_to(Point::new(left + r, top));
+        recorder.line_to(Point::new(right - r, top));
+        let corners = [
+            (Point::new(right - r, top + r), 3.0 * FRAC_PI_2),
+            (Point::new(right - r, bottom - r), 0.0),
+            (Point::new(left + r, bottom - r), FRAC_PI_2),
+            (Point::new(left + r, top + r), PI),
+        ];
+        let sides = [
+            Point::new(right, bottom - r),
+            Point::new(left + r, bottom),
+            Point::new(left, top + r),
+        ];
+        for (i, (center, start_angle)) in corners.into_iter().enumerate() {
+            if r > 0.0 {
+                recorder.arc_to(center, r, start_angle, start_angle + FRAC_PI_2, true);
+            }
+            if let Some(point) = sides.get(i) {
+                recorder.line_to(*point);
+            }
+        }
+        recorder.close_path();
+        display_list
+    }
+
+    /// Returns a copy of the display list moved by the given offset.
+    pub fn translated(&self, offset: Point) -> DisplayList {
+        let translate = |point: Point| Point::new(point.x + offset.x, point.y + offset.y);
+        self.ops
+            .iter()
+            .map(|op| match *op {
+                DisplayListOp::MoveTo(op) => DisplayListOp::MoveTo(MoveTo {
+                    point: translate(op.point),
+                }),
+                DisplayListOp::LineTo(op) => DisplayListOp::LineTo(LineTo {
+                    point: translate(op.point),
+                }),
+                DisplayListOp::ArcTo(op) => DisplayListOp::ArcTo(ArcTo {
+                    center: translate(op.center),
+                    ..op
+                }),
+                DisplayListOp::CubicTo(op) => DisplayListOp::CubicTo(CubicTo {
+                    control1: translate(op.control1),
+                    control2: translate(op.control2),
+                    point: translate(op.point),
+                }),
+                DisplayListOp::QuadTo(op) => DisplayListOp::QuadTo(QuadTo {
+                    control: translate(op.control),
+                    point: translate(op.point),
+                }),
+                op => op,
+            })
+            .collect()
+    }
+
+    /// Returns a display list that traces every subpath in the opposite direction,
+    /// which turns shapes into holes when combined with other shapes.
+    ///
+    /// Markers are kept between the subpaths they separate.
+    pub fn reversed(&self) -> DisplayList {
+        /// A piece of a subpath, with the point where it starts.
+        enum Segment {
+            Line(Point),
+            Arc(ArcTo),
+            Cubic(Point, CubicTo),
+            Quad(Point, QuadTo),
+        }
+
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        let mut segments = vec![];
+        let (mut start, mut current) = (Point::default(), Point::default());
+        let flush = |recorder: &mut DisplayListRecorder,
+                     segments: &mut Vec<Segment>,
+                     start: Point,
+                     current: Point,
+                     closed: bool| {
+            if segments.is_empty() && !closed {
+                return;
+            }
+            if closed {
+                // The implicit closing line becomes the first line of the reversed subpath.
+                recorder.move_to(start);
+                if !is_same_point(current, start) {
+                    recorder.line_to(current);
+                }
+            } else {
+                recorder.move_to(current);
+            }
+            for segment in segments.drain(..).rev() {
+                match segment {
+                    Segment::Line(from) => recorder.line_to(from),
+                    Segment::Arc(op) => recorder.arc_to(
+                        op.center,
+                        op.radius,
+                        op.end_angle,
+                        op.start_angle,
+                        !op.clockwise,
+                    ),
+                    Segment::Cubic(from, op) => recorder.cubic_to(op.control2, op.control1, from),
+                    Segment::Quad(from, op) => recorder.quad_to(op.control, from),
+                }
+            }
+            if closed {
+                recorder.close_path();
+            }
+        };
+
+        for op in &self.ops {
+            match *op {
+                DisplayListOp::MoveTo(op) => {
+                    flush(&mut recorder, &mut segments, start, current, false);
+                    (start, current) = (op.point, op.point);
+                }
+                DisplayListOp::LineTo(op) => {
+                    segments.push(Segment::Line(current));
+                    current = op.point;
+                }
+                DisplayListOp::ArcTo(op) => {
+                    // Arcs are joined to the current point with a line.
+                    if !is_same_point(op.start_point(), current) {
+                        segments.push(Segment::Line(current));
+                    }
+                    segments.push(Segment::Arc(op));
+                    current = op.end_point();
+                }
+                DisplayListOp::CubicTo(op) => {
+                    segments.push(Segment::Cubic(current, op));
+                    current = op.point;
+                }
+                DisplayListOp::QuadTo(op) => {
+                    segments.push(Segment::Quad(current, op));
+                    current = op.point;
+                }
+                DisplayListOp::ClosePath(_) => {
+                    flush(&mut recorder, &mut segments, start, current, true);
+                    current = start;
+                }
+                DisplayListOp::BeginLayer(op) => {
+                    flush(&mut recorder, &mut segments, start, current, false);
+                    recorder.begin_layer(op.layer);
+                }
+            }
+        }
+        flush(&mut recorder, &mut segments, start, current, false);
+        display_list
+    }
+
+    /// Returns an iterator over the operations in the display list.
+    pub fn iter(&self) -> core::slice::Iter<'_, DisplayListOp> {
+        self.ops.iter()
+    }
+
     pub fn present<R>(&self, receiver: &mut R)
     where
         R: DisplayListOpReceiver,
@@ -22,38 +253,142 @@ impl DisplayList {
                 DisplayListOp::MoveTo(op) => receiver.dispatch_move_to(*op),
                 DisplayListOp::LineTo(op) => receiver.dispatch_line_to(*op),
                 DisplayListOp::ArcTo(op) => receiver.dispatch_arc_to(*op),
+                DisplayListOp::CubicTo(op) => receiver.dispatch_cubic_to(*op),
+                DisplayListOp::QuadTo(op) => receiver.dispatch_quad_to(*op),
                 DisplayListOp::ClosePath(op) => receiver.dispatch_close_path(*op),
+                DisplayListOp::BeginLayer(op) => receiver.dispatch_begin_layer(*op),
             }
         }
     }
 }
 
-impl DisplayList {
-    pub(crate) fn new() -> Self {
-        Self { ops: vec![] }
+/// Returns `true` if the points only differ by floating point noise.
+fn is_same_point(a: Point, b: Point) -> bool {
+    (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DisplayListOp;
+    type IntoIter = core::slice::Iter<'a, DisplayListOp>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.iter()
     }
+}
 
-    pub(crate) fn begin_recording(&mut self) -> DisplayListRecorder<'_> {
-        DisplayListRecorder { display_list: self }
+impl IntoIterator for DisplayList {
+    type Item = DisplayListOp;
+    type IntoIter = alloc::vec::IntoIter<DisplayListOp>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.into_iter()
     }
+}
 
-    pub(crate) fn add_op(&mut self, op: DisplayListOp) {
-        self.ops.push(op);
+impl FromIterator<DisplayListOp> for DisplayList {
+    fn from_iter<I: IntoIterator<Item = DisplayListOp>>(iter: I) -> Self {
+        Self {
+            ops: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<DisplayListOp> for DisplayList {
+    fn extend<I: IntoIterator<Item = DisplayListOp>>(&mut self, iter: I) {
+        self.ops.extend(iter);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serialization {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{DisplayList, DisplayListOp};
+
+    #[derive(Serialize)]
+    struct VersionedRef<'a> {
+        version: u32,
+        ops: &'a [DisplayListOp],
+    }
+
+    #[derive(Deserialize)]
+    struct Versioned {
+        version: u32,
+        ops: Vec<DisplayListOp>,
+    }
+
+    impl Serialize for DisplayList {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            VersionedRef {
+                version: DisplayList::SCHEMA_VERSION,
+                ops: &self.ops,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DisplayList {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let versioned = Versioned::deserialize(deserializer)?;
+            // Every version so far only adds operations, so older data stays readable.
+            if versioned.version == 0 || versioned.version > DisplayList::SCHEMA_VERSION {
+                return Err(D::Error::custom(format!(
+                    "unsupported display list schema version {} (expected at most {})",
+                    versioned.version,
+                    DisplayList::SCHEMA_VERSION
+                )));
+            }
+            Ok(DisplayList { ops: versioned.ops })
+        }
     }
 }
 
 macro_rules! impl_ops {
-    ($($op_name:ident($op_fn_name:ident) { $($field:ident : $field_ty:ty),* }),*) => {
+    (@dispatch $fn_name:ident($op_name:ident)) => {
+        fn $fn_name(&mut self, op : $op_name);
+    };
+    (@dispatch $fn_name:ident($op_name:ident) => $fallback_fn_name:ident($fallback_name:ident)) => {
+        /// Receives a curve, which receivers written before curves were added draw
+        /// as a straight line to its end point. Override it to draw the curve.
+        fn $fn_name(&mut self, op : $op_name) {
+            self.$fallback_fn_name($fallback_name { point: op.point });
+        }
+    };
+    (
         $(
-            #[derive(Debug, Copy, Clone)]
+            $op_name:ident($op_fn_name:ident) { $($field:ident : $field_ty:ty),* }
+            $(=> $fallback_name:ident($fallback_fn_name:ident))?
+        ),*;
+        $($marker_name:ident($marker_fn_name:ident) { $($marker_field:ident : $marker_field_ty:ty),* }),*
+    ) => {
+        $(
+            #[derive(Debug, Copy, Clone, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub struct $op_name {
                 $(pub $field : $field_ty),*
             }
         )*
+        $(
+            #[derive(Debug, Copy, Clone, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            pub struct $marker_name {
+                $(pub $marker_field : $marker_field_ty),*
+            }
+        )*
 
-        #[derive(Debug, Copy, Clone)]
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
         pub enum DisplayListOp {
-            $($op_name($op_name)),*
+            $($op_name($op_name),)*
+            $($marker_name($marker_name),)*
         }
 
         impl<'d> DisplayListRecorder<'d> {
@@ -63,12 +398,55 @@ macro_rules! impl_ops {
                     self.display_list.add_op(op);
                 }
             )*
+            $(
+                pub fn $marker_fn_name(&mut self, $($marker_field : $marker_field_ty),*) {
+                    let op = DisplayListOp::$marker_name($marker_name { $($marker_field),* });
+                    self.display_list.add_op(op);
+                }
+            )*
         }
 
         paste! {
             pub trait DisplayListOpReceiver {
                 $(
-                    fn [<dispatch_ $op_fn_name>](&mut self, op : $op_name);
+                    impl_ops!(
+                        @dispatch [<dispatch_ $op_fn_name>]($op_name)
+                        $(=> [<dispatch_ $fallback_fn_name>]($fallback_name))?
+                    );
+                )*
+                $(
+                    /// Receives a marker that does not change the geometry,
+                    /// which receivers that only trace paths can ignore.
+                    fn [<dispatch_ $marker_fn_name>](&mut self, op : $marker_name) {
+                        _ = op;
+                    }
+                )*
+            }
+
+            /// Display lists record the operations they receive.
+            impl DisplayListOpReceiver for DisplayList {
+                $(
+                    fn [<dispatch_ $op_fn_name>](&mut self, op : $op_name) {
+                        self.add_op(DisplayListOp::$op_name(op));
+                    }
+                )*
+                $(
+                    fn [<dispatch_ $marker_fn_name>](&mut self, op : $marker_name) {
+                        self.add_op(DisplayListOp::$marker_name(op));
+                    }
+                )*
+            }
+
+            impl<'d> DisplayListOpReceiver for DisplayListRecorder<'d> {
+                $(
+                    fn [<dispatch_ $op_fn_name>](&mut self, op : $op_name) {
+                        self.display_list.[<dispatch_ $op_fn_name>](op);
+                    }
+                )*
+                $(
+                    fn [<dispatch_ $marker_fn_name>](&mut self, op : $marker_name) {
+                        self.display_list.[<dispatch_ $marker_fn_name>](op);
+                    }
                 )*
             }
         }
@@ -79,5 +457,106 @@ impl_ops!(
     MoveTo(move_to) { point: Point },
     LineTo(line_to) { point: Point },
     ArcTo(arc_to) { center: Point, radius: f64, start_angle: f32, end_angle: f32, clockwise: bool },
-    ClosePath(close_path) { }
+    CubicTo(cubic_to) { control1: Point, control2: Point, point: Point } => LineTo(line_to),
+    QuadTo(quad_to) { control: Point, point: Point } => LineTo(line_to),
+    ClosePath(close_path) { };
+    BeginLayer(begin_layer) { layer: Layer }
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::builder::Builder;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_json() {
+        let display_list = Builder::text("rounded-qr").build().unwrap();
+        let json = serde_json::to_string(&display_list).unwrap();
+        let decoded: DisplayList = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, display_list);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_the_schema_version() {
+        let json = serde_json::to_value(DisplayList::new()).unwrap();
+        assert_eq!(json["version"], DisplayList::SCHEMA_VERSION);
+        assert_eq!(json["ops"], serde_json::json!([]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rejects_unknown_schema_versions() {
+        for version in [0, DisplayList::SCHEMA_VERSION + 1] {
+            let json = format!(r#"{{"version":{version},"ops":[]}}"#);
+            let error = serde_json::from_str::<DisplayList>(&json).unwrap_err();
+            assert!(error.to_string().contains("schema version"), "{error}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reads_version_1_display_lists() {
+        let json = r#"{"version":1,"ops":[
+            {"move_to":{"point":{"x":0.0,"y":0.0}}},
+            {"line_to":{"point":{"x":10.0,"y":0.0}}},
+            {"arc_to":{"center":{"x":10.0,"y":5.0},"radius":5.0,
+                "start_angle":4.712389,"end_angle":7.853982,"clockwise":true}},
+            {"close_path":{}}
+        ]}"#;
+        let display_list: DisplayList = serde_json::from_str(json).unwrap();
+        let mut expected = DisplayList::new();
+        let mut recorder = expected.begin_recording();
+        recorder.move_to(Point::new(0.0, 0.0));
+        recorder.line_to(Point::new(10.0, 0.0));
+        recorder.arc_to(Point::new(10.0, 5.0), 5.0, 4.712389, 7.853982, true);
+        recorder.close_path();
+        assert_eq!(display_list, expected);
+    }
+
+    /// A receiver that only implements the operations that existed before curves.
+    #[derive(Default)]
+    struct LineReceiver {
+        points: Vec<Point>,
+    }
+
+    impl DisplayListOpReceiver for LineReceiver {
+        fn dispatch_move_to(&mut self, op: MoveTo) {
+            self.points.push(op.point);
+        }
+
+        fn dispatch_line_to(&mut self, op: LineTo) {
+            self.points.push(op.point);
+        }
+
+        fn dispatch_arc_to(&mut self, _op: ArcTo) {}
+
+        fn dispatch_close_path(&mut self, _op: ClosePath) {}
+    }
+
+    #[test]
+    fn curves_default_to_lines_to_their_end_points() {
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        recorder.move_to(Point::new(0.0, 0.0));
+        recorder.cubic_to(
+            Point::new(1.0, 2.0),
+            Point::new(3.0, 2.0),
+            Point::new(4.0, 0.0),
+        );
+        recorder.quad_to(Point::new(2.0, -2.0), Point::new(0.0, 0.0));
+        recorder.close_path();
+        let mut receiver = LineReceiver::default();
+        display_list.present(&mut receiver);
+        assert_eq!(
+            receiver.points,
+            [
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 0.0)
+            ]
+        );
+    }
+}
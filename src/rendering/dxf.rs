@@ -0,0 +1,172 @@
+use std::fmt::Write;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::geometry::{Point, Size};
+use super::{format_millimeters as mm, format_number_with_precision, CURVE_TOLERANCE};
+
+/// A vertex of a polyline with the bulge of the segment that starts at it.
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    point: Point,
+    /// The tangent of a quarter of the included angle of the arc,
+    /// positive for counterclockwise arcs and zero for straight segments.
+    bulge: f64,
+}
+
+/// A receiver that converts drawing operations to closed polylines whose arcs
+/// are stored as bulges, in the y-up coordinate system of CAD software.
+#[derive(Debug, Clone)]
+struct PolylineBuilder {
+    scale: f64,
+    height: f64,
+    polylines: Vec<Vec<Vertex>>,
+    current: Vec<Vertex>,
+}
+
+impl PolylineBuilder {
+    fn transform(&self, point: Point) -> Point {
+        Point::new(point.x * self.scale, (self.height - point.y) * self.scale)
+    }
+
+    fn push(&mut self, point: Point) {
+        let point = self.transform(point);
+        let is_same = |last: &Vertex| {
+            (last.point.x - point.x).abs() < 1e-4 && (last.point.y - point.y).abs() < 1e-4
+        };
+        if !self.current.last().is_some_and(is_same) {
+            self.current.push(Vertex { point, bulge: 0.0 });
+        }
+    }
+
+    /// Approximates a curve in drawing coordinates with straight segments,
+    /// since polylines have no curves.
+    fn curve_to(&mut self, curve: CubicTo) {
+        let Some(start) = self.current.last().map(|vertex| vertex.point) else {
+            return;
+        };
+        for point in curve.flatten(start, CURVE_TOLERANCE) {
+            self.current.push(Vertex { point, bulge: 0.0 });
+        }
+    }
+
+    fn end_polyline(&mut self) {
+        let mut polyline = std::mem::take(&mut self.current);
+        if polyline.len() > 1 {
+            let (first, last) = (polyline[0], polyline[polyline.len() - 1]);
+            if (first.point.x - last.point.x).abs() < 1e-4
+                && (first.point.y - last.point.y).abs() < 1e-4
+            {
+                polyline.pop();
+            }
+        }
+        if polyline.len() > 1 {
+            self.polylines.push(polyline);
+        }
+    }
+}
+
+impl DisplayListOpReceiver for PolylineBuilder {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.end_polyline();
+        self.push(op.point);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.push(op.point);
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.push(op.start_point());
+        // Flipping the y-axis turns clockwise arcs into arcs with negative bulges.
+        let bulge = -(op.sweep_angle() / 4.0).tan();
+        if let Some(last) = self.current.last_mut() {
+            last.bulge = bulge;
+        }
+        let end = self.transform(op.end_point());
+        self.current.push(Vertex {
+            point: end,
+            bulge: 0.0,
+        });
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.curve_to(CubicTo {
+            control1: self.transform(op.control1),
+            control2: self.transform(op.control2),
+            point: self.transform(op.point),
+        });
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let Some(start) = self.current.last().map(|vertex| vertex.point) else {
+            return;
+        };
+        let curve = QuadTo {
+            control: self.transform(op.control),
+            point: self.transform(op.point),
+        };
+        self.curve_to(curve.to_cubic(start));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.end_polyline();
+    }
+}
+
+/// Renders the display list to a DXF drawing in millimeters, made of one closed
+/// `LWPOLYLINE` per contour with bulges for the rounded corners.
+///
+/// `size` is the size the display list was built with, and `scale` is the number of
+/// millimeters per unit of the display list. Building the display list with a size of
+/// `modules × module_size`, where `modules` is the side of the
+/// [module grid](crate::builder::Builder::build_module_grid), makes `scale` equal to 1.
+pub fn render(display_list: &DisplayList, size: Size, scale: f64) -> String {
+    let mut builder = PolylineBuilder {
+        scale,
+        height: size.height,
+        polylines: vec![],
+        current: vec![],
+    };
+    display_list.present(&mut builder);
+    builder.end_polyline();
+
+    let mut dxf = String::new();
+    let mut group = |code: u32, value: &str| {
+        _ = write!(dxf, "{code}\n{value}\n");
+    };
+    group(0, "SECTION");
+    group(2, "HEADER");
+    group(9, "$ACADVER");
+    group(1, "AC1015");
+    // Millimeters.
+    group(9, "$INSUNITS");
+    group(70, "4");
+    group(9, "$EXTMIN");
+    group(10, "0");
+    group(20, "0");
+    group(9, "$EXTMAX");
+    group(10, &mm(size.width * scale));
+    group(20, &mm(size.height * scale));
+    group(0, "ENDSEC");
+    group(0, "SECTION");
+    group(2, "ENTITIES");
+    for polyline in &builder.polylines {
+        group(0, "LWPOLYLINE");
+        group(8, "0");
+        group(90, &polyline.len().to_string());
+        // Closed.
+        group(70, "1");
+        for vertex in polyline {
+            group(10, &mm(vertex.point.x));
+            group(20, &mm(vertex.point.y));
+            if vertex.bulge != 0.0 {
+                group(42, &format_number_with_precision(vertex.bulge, 6));
+            }
+        }
+    }
+    group(0, "ENDSEC");
+    group(0, "EOF");
+    dxf
+}
@@ -0,0 +1,326 @@
+//! Drawing into the frame buffers of displays with `embedded-graphics`, such as the
+//! monochrome e-paper displays driven by microcontrollers.
+
+use alloc::vec::Vec;
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point as PixelPoint, Size as PixelSize};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::transform::Transform;
+use embedded_graphics::Drawable;
+
+use crate::math;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::geometry::Point;
+
+/// The maximum distance, in pixels, between an arc or a curve and the segments approximating it.
+const TOLERANCE: f64 = 0.1;
+
+/// A display list drawn by filling its shapes with a single color, without anti-aliasing.
+///
+/// The pixels whose centers are inside the shapes, with the non-zero rule, are set to the color
+/// and the others are left untouched, so the target is usually cleared to the background first.
+/// The display list is drawn at one pixel per unit with its origin at the top-left corner
+/// of the target, which [`FilledShape::scale`] and [`Transform::translate`] change.
+#[derive(Debug, Clone, Copy)]
+pub struct FilledShape<'a, C> {
+    display_list: &'a DisplayList,
+    color: C,
+    scale: f64,
+    position: PixelPoint,
+}
+
+impl<'a, C: PixelColor> FilledShape<'a, C> {
+    /// Creates a drawable that fills the shapes of the display list with the color.
+    pub fn new(display_list: &'a DisplayList, color: C) -> Self {
+        Self {
+            display_list,
+            color,
+            scale: 1.0,
+            position: PixelPoint::zero(),
+        }
+    }
+
+    /// Sets the number of pixels per unit of the display list.
+    pub fn scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the position of the origin of the display list on the target.
+    pub fn get_position(&self) -> PixelPoint {
+        self.position
+    }
+
+    /// Returns the edges of the shapes, in pixels, sorted by the top of every edge.
+    fn edges(&self) -> Vec<Edge> {
+        let mut collector = EdgeCollector {
+            edges: Vec::new(),
+            scale: self.scale,
+            offset: Point::new(self.position.x as f64, self.position.y as f64),
+            start: Point::default(),
+            current: Point::default(),
+        };
+        self.display_list.present(&mut collector);
+        // Close the last contour in case it was left open.
+        collector.line_to(collector.start);
+
+        let mut edges = collector.edges;
+        edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+        edges
+    }
+}
+
+impl<C: PixelColor> Transform for FilledShape<'_, C> {
+    fn translate(&self, by: PixelPoint) -> Self {
+        Self {
+            position: self.position + by,
+            ..*self
+        }
+    }
+
+    fn translate_mut(&mut self, by: PixelPoint) -> &mut Self {
+        self.position += by;
+        self
+    }
+}
+
+impl<C: PixelColor> Drawable for FilledShape<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let edges = self.edges();
+        let area = target.bounding_box();
+        let (Some(first), Some(bottom_right)) = (edges.first(), area.bottom_right()) else {
+            return Ok(());
+        };
+        let bottom = edges
+            .iter()
+            .fold(first.bottom.y, |y, edge| y.max(edge.bottom.y));
+        let top_row = (math::floor(first.top.y) as i32).max(area.top_left.y);
+        let bottom_row = (math::ceil(bottom) as i32 - 1).min(bottom_right.y);
+
+        // Every row is sampled along the centers of its pixels, through the edges that cross it.
+        let mut next = 0;
+        let mut active = Vec::new();
+        let mut crossings = Vec::new();
+        for y in top_row..=bottom_row {
+            let center = y as f64 + 0.5;
+            while let Some(edge) = edges.get(next).filter(|edge| edge.top.y <= center) {
+                active.push(*edge);
+                next += 1;
+            }
+            active.retain(|edge| edge.bottom.y > center);
+
+            crossings.clear();
+            crossings.extend(active.iter().map(|edge| (edge.x_at(center), edge.winding)));
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding == 0 {
+                    continue;
+                }
+                // The pixels whose centers lie between the two crossings.
+                let start = (math::ceil(pair[0].0 - 0.5) as i32).max(area.top_left.x);
+                let end = (math::ceil(pair[1].0 - 0.5) as i32).min(bottom_right.x + 1);
+                if start < end {
+                    let span = Rectangle::new(
+                        PixelPoint::new(start, y),
+                        PixelSize::new((end - start) as u32, 1),
+                    );
+                    target.fill_solid(&span, self.color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A straight edge of a shape, in pixels.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: Point,
+    bottom: Point,
+    /// 1 if the edge is traced downwards and -1 otherwise.
+    winding: i32,
+}
+
+impl Edge {
+    /// Returns where the edge crosses the horizontal line at `y`.
+    fn x_at(&self, y: f64) -> f64 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+}
+
+/// A receiver that collects the edges of the contours it receives, in pixels.
+struct EdgeCollector {
+    edges: Vec<Edge>,
+    /// The number of pixels per unit of the received drawing operations.
+    scale: f64,
+    /// The position of the origin of the received drawing operations, in pixels.
+    offset: Point,
+    start: Point,
+    current: Point,
+}
+
+impl EdgeCollector {
+    /// Converts a point of the received drawing operations to pixels.
+    fn transform(&self, point: Point) -> Point {
+        Point::new(
+            point.x * self.scale + self.offset.x,
+            point.y * self.scale + self.offset.y,
+        )
+    }
+
+    /// Adds the edge from the current point to the given point, in pixels, unless it is horizontal.
+    fn line_to(&mut self, point: Point) {
+        let current = self.current;
+        if current.y < point.y {
+            self.edges.push(Edge {
+                top: current,
+                bottom: point,
+                winding: 1,
+            });
+        } else if current.y > point.y {
+            self.edges.push(Edge {
+                top: point,
+                bottom: current,
+                winding: -1,
+            });
+        }
+        self.current = point;
+    }
+
+    /// Adds the edges of a curve in pixels that starts at the current point.
+    fn curve_to(&mut self, curve: CubicTo) {
+        for point in curve.flatten(self.current, TOLERANCE) {
+            self.line_to(point);
+        }
+    }
+}
+
+impl DisplayListOpReceiver for EdgeCollector {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.line_to(self.start);
+        self.start = self.transform(op.point);
+        self.current = self.start;
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.line_to(self.transform(op.point));
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.line_to(self.transform(op.start_point()));
+        for point in op.flatten(TOLERANCE / self.scale) {
+            self.line_to(self.transform(point));
+        }
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.curve_to(CubicTo {
+            control1: self.transform(op.control1),
+            control2: self.transform(op.control2),
+            point: self.transform(op.point),
+        });
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let curve = QuadTo {
+            control: self.transform(op.control),
+            point: self.transform(op.point),
+        };
+        self.curve_to(curve.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.line_to(self.start);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::rendering::geometry::{Rect, Size};
+    use crate::rendering::raster::render_mask;
+    use crate::types::CornerRadii;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::BinaryColor;
+
+    /// Returns the code drawn into a 64 by 64 display, and its coverage of the pixels.
+    fn draw(builder: Builder) -> (MockDisplay<BinaryColor>, Vec<u8>) {
+        let display_list = builder.size(Size::new(64.0, 64.0)).build().unwrap();
+        let mut display = MockDisplay::new();
+        FilledShape::new(&display_list, BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        (display, render_mask(&display_list, 64, 64).data)
+    }
+
+    fn is_set(display: &MockDisplay<BinaryColor>, i: usize) -> bool {
+        let point = PixelPoint::new((i % 64) as i32, (i / 64) as i32);
+        display.get_pixel(point) == Some(BinaryColor::On)
+    }
+
+    #[test]
+    fn fills_the_pixels_of_square_modules() {
+        // 21 modules and a quiet zone of 1 module are 2 pixels each.
+        let builder = Builder::text("hello")
+            .corner_radii(CornerRadii::SQUARE)
+            .quiet_zone(1)
+            .snap_to_pixels(true);
+        let (display, coverage) = draw(builder);
+        assert!(coverage.iter().all(|&c| c == 0 || c == 255));
+        for (i, &c) in coverage.iter().enumerate() {
+            assert_eq!(is_set(&display, i), c == 255, "pixel {i}");
+        }
+    }
+
+    #[test]
+    fn fills_the_pixels_covered_by_rounded_modules() {
+        let (display, coverage) = draw(Builder::text("hello"));
+        let mut partial = 0;
+        for (i, &c) in coverage.iter().enumerate() {
+            match c {
+                0 => assert!(!is_set(&display, i), "pixel {i}"),
+                255 => assert!(is_set(&display, i), "pixel {i}"),
+                // Pixels on the edges are set when their centers are inside the shapes.
+                _ => partial += 1,
+            }
+        }
+        assert!(partial > 0);
+    }
+
+    #[test]
+    fn moves_the_shapes_with_the_position() {
+        let rect = Rect {
+            origin: Point::default(),
+            size: Size::new(3.0, 2.0),
+        };
+        let display_list = DisplayList::rounded_rect(rect, 0.0);
+        let mut display = MockDisplay::new();
+        FilledShape::new(&display_list, BinaryColor::On)
+            .scale(2.0)
+            .translate(PixelPoint::new(1, 2))
+            .draw(&mut display)
+            .unwrap();
+        display.assert_pattern(&[
+            "       ", //
+            "       ", " ######", " ######", " ######", " ######",
+        ]);
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt::Write;
+use std::io;
+
+use super::display_list::{
+    ArcTo, BeginLayer, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, Layer, LineTo,
+    MoveTo, QuadTo,
+};
+use super::format_number as num;
+use super::geometry::{Point, Size};
+use super::style::{Color, Style};
+
+/// A receiver that converts drawing operations to PostScript path construction operators.
+///
+/// The operators expect a coordinate system with the y-axis pointing down.
+#[derive(Debug, Clone, Default)]
+pub struct PostScriptPathBuilder {
+    data: String,
+    /// The first point of the current subpath, where closing it leads back to.
+    start: Point,
+    /// The end of the last operation, where quadratic curves start.
+    current: Point,
+}
+
+impl PostScriptPathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the PostScript program, one operator per line.
+    pub fn finish(self) -> String {
+        self.data
+    }
+}
+
+impl DisplayListOpReceiver for PostScriptPathBuilder {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        _ = writeln!(self.data, "{} {} moveto", num(op.point.x), num(op.point.y));
+        (self.start, self.current) = (op.point, op.point);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        _ = writeln!(self.data, "{} {} lineto", num(op.point.x), num(op.point.y));
+        self.current = op.point;
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        // With the y-axis pointing down, increasing angles run clockwise.
+        let start = (op.start_angle as f64).to_degrees();
+        let end = start + op.sweep_angle().to_degrees();
+        _ = writeln!(
+            self.data,
+            "{} {} {} {} {} {}",
+            num(op.center.x),
+            num(op.center.y),
+            num(op.radius),
+            num(start),
+            num(end),
+            if op.clockwise { "arc" } else { "arcn" }
+        );
+        self.current = op.end_point();
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        _ = writeln!(
+            self.data,
+            "{} {} {} {} {} {} curveto",
+            num(op.control1.x),
+            num(op.control1.y),
+            num(op.control2.x),
+            num(op.control2.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+        self.current = op.point;
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        // PostScript only has cubic curves, which represent quadratic ones exactly.
+        self.dispatch_cubic_to(op.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.data.push_str("closepath\n");
+        self.current = self.start;
+    }
+}
+
+fn set_color(out: &mut String, color: Color) {
+    _ = writeln!(
+        out,
+        "{} {} {} setrgbcolor",
+        num(color.r as f64 / 255.0),
+        num(color.g as f64 / 255.0),
+        num(color.b as f64 / 255.0)
+    );
+}
+
+/// Renders the display list to an Encapsulated PostScript document of the given size, in points.
+///
+/// The alpha components of the colors are ignored.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style) -> String {
+    let mut eps = header(size, style);
+    for (display_list, paint) in style.fills(display_list) {
+        if display_list.is_empty() {
+            continue;
+        }
+        set_color(&mut eps, paint.approximate_color());
+        eps.push_str("newpath\n");
+        let mut builder = PostScriptPathBuilder::new();
+        display_list.present(&mut builder);
+        eps.push_str(&builder.finish());
+        eps.push_str("fill\n");
+    }
+    eps.push_str(TRAILER);
+    eps
+}
+
+/// The end of a document, which restores the graphics state of the start.
+const TRAILER: &str = "grestore\n%%EOF\n";
+
+/// Returns the start of a document of the given size, which flips the y-axis
+/// and fills the background.
+fn header(size: Size, style: &Style) -> String {
+    let (width, height) = (num(size.width), num(size.height));
+    let mut eps = format!(
+        "%!PS-Adobe-3.0 EPSF-3.0\n\
+         %%BoundingBox: 0 0 {} {}\n\
+         %%HiResBoundingBox: 0 0 {width} {height}\n\
+         %%Creator: rounded-qr\n\
+         %%EndComments\n\
+         gsave\n\
+         0 {height} translate 1 -1 scale\n",
+        size.width.ceil(),
+        size.height.ceil(),
+    );
+    let background = style.background.as_ref();
+    if let Some(color) = background
+        .map(|b| b.paint.approximate_color())
+        .filter(|c| c.a > 0)
+    {
+        set_color(&mut eps, color);
+        _ = writeln!(eps, "0 0 {width} {height} rectfill");
+    }
+    eps
+}
+
+/// A receiver that streams an Encapsulated PostScript document to a writer as it receives
+/// drawing operations, without collecting them first.
+///
+/// The operations of every [layer](Layer) are filled with the approximate color of the paint
+/// of the style for that layer, and a new path starts whenever the color changes.
+/// Writing stops at the first error, which [`EpsWriter::finish`] returns.
+///
+/// The document of a code streamed from a builder is the same as the one [`render`]
+/// returns for its display list.
+#[derive(Debug)]
+pub struct EpsWriter<W: io::Write> {
+    writer: W,
+    /// The colors of the data layer and, if it is painted differently, of the finder layer.
+    colors: Vec<Color>,
+    /// The index of the color of the current layer.
+    color: usize,
+    /// The index of the color of the path being written, if one is open.
+    open: Option<usize>,
+    /// The operators of the operation being written.
+    path: PostScriptPathBuilder,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> EpsWriter<W> {
+    /// Writes the start of a document of the given size, in points, and its background
+    /// to the writer.
+    pub fn new(mut writer: W, size: Size, style: &Style) -> io::Result<Self> {
+        writer.write_all(header(size, style).as_bytes())?;
+        let mut colors = vec![style.foreground.approximate_color()];
+        if let Some(finder) = &style.finder {
+            colors.push(finder.approximate_color());
+        }
+        Ok(Self {
+            writer,
+            colors,
+            color: 0,
+            open: None,
+            path: PostScriptPathBuilder::new(),
+            error: None,
+        })
+    }
+
+    /// Writes the end of the document and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.open.is_some() {
+            self.writer.write_all(b"fill\n")?;
+        }
+        self.writer.write_all(TRAILER.as_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Writes the operators of an operation, starting a path if the color has changed.
+    fn write_op(&mut self, op: impl FnOnce(&mut PostScriptPathBuilder)) {
+        if self.error.is_some() {
+            return;
+        }
+        op(&mut self.path);
+        let mut write = || {
+            if self.open != Some(self.color) {
+                let mut start = String::new();
+                if self.open.is_some() {
+                    start.push_str("fill\n");
+                }
+                set_color(&mut start, self.colors[self.color]);
+                start.push_str("newpath\n");
+                self.writer.write_all(start.as_bytes())?;
+                self.open = Some(self.color);
+            }
+            self.writer.write_all(self.path.data.as_bytes())
+        };
+        if let Err(error) = write() {
+            self.error = Some(error);
+        }
+        self.path.data.clear();
+    }
+}
+
+impl<W: io::Write> DisplayListOpReceiver for EpsWriter<W> {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.write_op(|path| path.dispatch_move_to(op));
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.write_op(|path| path.dispatch_line_to(op));
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.write_op(|path| path.dispatch_arc_to(op));
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.write_op(|path| path.dispatch_cubic_to(op));
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.write_op(|path| path.dispatch_quad_to(op));
+    }
+
+    fn dispatch_close_path(&mut self, op: ClosePath) {
+        self.write_op(|path| path.dispatch_close_path(op));
+    }
+
+    fn dispatch_begin_layer(&mut self, op: BeginLayer) {
+        self.color = match op.layer {
+            Layer::Data => 0,
+            Layer::Finder => self.colors.len() - 1,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::types::ModuleShape;
+
+    #[test]
+    fn streams_the_same_document_as_it_renders() {
+        let size = Size::new(200.0, 200.0);
+        let builder = Builder::text("https://example.com").size(size);
+        let styles = [
+            Style::default(),
+            Style {
+                finder: Some(Color::rgb(0xe9, 0x45, 0x60).into()),
+                ..Style::default()
+            },
+            Style {
+                foreground: Color::rgba(0x12, 0x34, 0x56, 0x80).into(),
+                background: None,
+                ..Style::default()
+            },
+        ];
+        for builder in [
+            builder.clone(),
+            builder.clone().module_shape(ModuleShape::Dots),
+            builder.invert(true),
+        ] {
+            let display_list = builder.build().unwrap();
+            for style in &styles {
+                let mut writer = EpsWriter::new(vec![], size, style).unwrap();
+                builder.build_with_receiver(&mut writer).unwrap();
+                let streamed = String::from_utf8(writer.finish().unwrap()).unwrap();
+                assert_eq!(streamed, render(&display_list, size, style), "{style:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn streams_an_empty_document() {
+        let size = Size::new(10.0, 10.0);
+        let style = Style::default();
+        let writer = EpsWriter::new(vec![], size, &style).unwrap();
+        let streamed = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(streamed, render(&DisplayList::new(), size, &style));
+    }
+}
@@ -0,0 +1,106 @@
+use super::display_list::DisplayList;
+use super::geometry::Size;
+use super::raster::{render_bitmap, Bitmap};
+
+/// The maximum number of rows sent in one command, which keeps every band
+/// within the limits of printers with small buffers.
+const MAX_BAND_HEIGHT: usize = 255;
+
+/// Returns `GS v 0` commands that print the bitmap in normal density.
+///
+/// Tall bitmaps are split into bands that are printed one after another without gaps.
+pub fn raster_image(bitmap: &Bitmap) -> Vec<u8> {
+    let mut commands =
+        Vec::with_capacity(bitmap.data.len() + 8 * bitmap.height.div_ceil(MAX_BAND_HEIGHT));
+    let [x_low, x_high] = (bitmap.bytes_per_row as u16).to_le_bytes();
+    for band in (0..bitmap.height).step_by(MAX_BAND_HEIGHT) {
+        let rows = MAX_BAND_HEIGHT.min(bitmap.height - band);
+        let [y_low, y_high] = (rows as u16).to_le_bytes();
+        commands.extend_from_slice(&[0x1D, b'v', b'0', 0, x_low, x_high, y_low, y_high]);
+        let start = band * bitmap.bytes_per_row;
+        commands.extend_from_slice(&bitmap.data[start..start + rows * bitmap.bytes_per_row]);
+    }
+    commands
+}
+
+/// Renders the display list to an ESC/POS print job for thermal receipt printers,
+/// with the code centered on the paper.
+///
+/// `size` is the size the display list was built with and `scale` the number
+/// of millimeters per unit. `dots_per_mm` is the resolution of the printer,
+/// which is 8 for most 203 dpi receipt printers.
+pub fn render(display_list: &DisplayList, size: Size, scale: f64, dots_per_mm: f64) -> Vec<u8> {
+    let bitmap = render_bitmap(display_list, size, scale * dots_per_mm);
+    // Initialize the printer and center the image.
+    let mut job = vec![0x1B, b'@', 0x1B, b'a', 1];
+    job.extend(raster_image(&bitmap));
+    // Restore the left alignment and feed the paper past the image.
+    job.extend_from_slice(&[0x1B, b'a', 0, b'\n']);
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::geometry::{Point, Rect};
+
+    #[test]
+    fn writes_the_size_of_the_image_in_the_header() {
+        let bitmap = Bitmap {
+            width: 10,
+            height: 3,
+            bytes_per_row: 2,
+            data: vec![0x80, 0x40, 0x01, 0x80, 0xFF, 0xC0],
+        };
+        assert_eq!(
+            raster_image(&bitmap),
+            [0x1D, b'v', b'0', 0, 2, 0, 3, 0, 0x80, 0x40, 0x01, 0x80, 0xFF, 0xC0]
+        );
+    }
+
+    #[test]
+    fn splits_tall_images_into_bands() {
+        let (bytes_per_row, height) = (263, 600);
+        // Every row is filled with the low byte of its index.
+        let data = (0..height)
+            .flat_map(|y| vec![y as u8; bytes_per_row])
+            .collect::<Vec<_>>();
+        let bitmap = Bitmap {
+            width: bytes_per_row * 8,
+            height,
+            bytes_per_row,
+            data,
+        };
+        let commands = raster_image(&bitmap);
+        assert_eq!(commands.len(), 3 * 8 + bytes_per_row * height);
+
+        let mut rest = &commands[..];
+        for (first_row, rows) in [(0, 255), (255, 255), (510, 90)] {
+            let (header, band) = rest.split_at(8);
+            // 263 bytes per row are 0x107, written low byte first.
+            assert_eq!(header, [0x1D, b'v', b'0', 0, 0x07, 0x01, rows as u8, 0]);
+            let (band, next) = band.split_at(rows * bytes_per_row);
+            assert_eq!(band[0], first_row as u8);
+            assert_eq!(band[band.len() - 1], (first_row + rows - 1) as u8);
+            rest = next;
+        }
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn centers_the_image_and_feeds_the_paper() {
+        let rect = Rect {
+            origin: Point::default(),
+            size: Size::new(3.0, 2.0),
+        };
+        let display_list = DisplayList::rounded_rect(rect, 0.0);
+        assert_eq!(
+            render(&display_list, rect.size, 0.5, 2.0),
+            [
+                0x1B, b'@', 0x1B, b'a', 1, // Initialize and center.
+                0x1D, b'v', b'0', 0, 1, 0, 2, 0, 0xE0, 0xE0, // Print 3 by 2 pixels.
+                0x1B, b'a', 0, b'\n', // Align left and feed.
+            ]
+        );
+    }
+}
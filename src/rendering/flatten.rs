@@ -0,0 +1,93 @@
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::geometry::Point;
+
+/// A receiver that approximates the drawing operations with closed polygons.
+#[derive(Debug, Clone)]
+pub struct PathFlattener {
+    tolerance: f64,
+    contours: Vec<Vec<Point>>,
+    current: Vec<Point>,
+}
+
+impl PathFlattener {
+    /// Creates a flattener whose polygons deviate from arcs and curves by at most `tolerance`.
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            contours: vec![],
+            current: vec![],
+        }
+    }
+
+    /// Returns the polygons, one per subpath, without repeating their first point at the end.
+    pub fn finish(mut self) -> Vec<Vec<Point>> {
+        self.end_contour();
+        self.contours
+    }
+
+    fn end_contour(&mut self) {
+        let mut contour = std::mem::take(&mut self.current);
+        while contour.len() > 1 && is_same_point(contour[0], contour[contour.len() - 1]) {
+            contour.pop();
+        }
+        if contour.len() > 2 {
+            self.contours.push(contour);
+        }
+    }
+
+    fn push(&mut self, point: Point) {
+        match self.current.last() {
+            Some(last) if is_same_point(*last, point) => {}
+            _ => self.current.push(point),
+        }
+    }
+}
+
+/// Returns `true` if the points are closer than floating point noise.
+fn is_same_point(a: Point, b: Point) -> bool {
+    (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+}
+
+impl DisplayListOpReceiver for PathFlattener {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.end_contour();
+        self.push(op.point);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.push(op.point);
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.push(op.start_point());
+        for point in op.flatten(self.tolerance) {
+            self.push(point);
+        }
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        let start = self.current.last().copied().unwrap_or_default();
+        for point in op.flatten(start, self.tolerance) {
+            self.push(point);
+        }
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let start = self.current.last().copied().unwrap_or_default();
+        self.dispatch_cubic_to(op.to_cubic(start));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.end_contour();
+    }
+}
+
+/// Approximates the display list with closed polygons that deviate from arcs and curves
+/// by at most `tolerance`.
+pub fn flatten(display_list: &DisplayList, tolerance: f64) -> Vec<Vec<Point>> {
+    let mut flattener = PathFlattener::new(tolerance);
+    display_list.present(&mut flattener);
+    flattener.finish()
+}
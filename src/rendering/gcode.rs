@@ -0,0 +1,356 @@
+use std::fmt::Write;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::flatten::flatten;
+use super::geometry::{Point, Size};
+use super::{format_millimeters as mm, CURVE_TOLERANCE};
+
+/// The tool that engraves or cuts the material.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    /// A spindle that is lowered into the material.
+    Spindle {
+        /// The height of rapid moves above the material, in millimeters.
+        safe_z: f64,
+        /// The height of cutting moves, in millimeters, negative below the surface.
+        cut_z: f64,
+        /// The feed rate of plunges, in millimeters per minute.
+        plunge_rate: f64,
+    },
+    /// A laser that is switched on while cutting.
+    Laser {
+        /// The power passed as the `S` word of `M3`.
+        power: f64,
+    },
+}
+
+/// Options that control the generated G-code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeOptions {
+    /// The number of millimeters per unit of the display list.
+    pub scale: f64,
+    /// The feed rate of cutting moves, in millimeters per minute.
+    pub feed_rate: f64,
+    pub tool: Tool,
+    /// Traces the contours of the shapes, which cuts them out or engraves their outline.
+    pub outline: bool,
+    /// The distance between the horizontal passes that fill the shapes, in millimeters,
+    /// or `None` to leave them empty.
+    pub hatch_spacing: Option<f64>,
+}
+
+impl Default for GcodeOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            feed_rate: 600.0,
+            tool: Tool::Spindle {
+                safe_z: 5.0,
+                cut_z: -0.5,
+                plunge_rate: 200.0,
+            },
+            outline: true,
+            hatch_spacing: None,
+        }
+    }
+}
+
+/// Writes moves in millimeters, in the y-up coordinate system of machines.
+struct GcodeWriter<'a> {
+    options: &'a GcodeOptions,
+    height: f64,
+    program: String,
+    cutting: bool,
+    /// The position of the tool, in millimeters.
+    current: Point,
+    /// The start of the contour being traced, in millimeters.
+    contour_start: Point,
+}
+
+impl GcodeWriter<'_> {
+    fn transform(&self, point: Point) -> Point {
+        Point::new(
+            point.x * self.options.scale,
+            (self.height - point.y) * self.options.scale,
+        )
+    }
+
+    /// Moves to the point without cutting, then starts cutting.
+    fn start_cut(&mut self, point: Point) {
+        self.stop_cut();
+        _ = writeln!(self.program, "G0 X{} Y{}", mm(point.x), mm(point.y));
+        _ = match self.options.tool {
+            Tool::Spindle {
+                cut_z, plunge_rate, ..
+            } => writeln!(self.program, "G1 Z{} F{}", mm(cut_z), mm(plunge_rate)),
+            Tool::Laser { power } => writeln!(self.program, "M3 S{}", mm(power)),
+        };
+        self.cutting = true;
+        self.current = point;
+    }
+
+    fn stop_cut(&mut self) {
+        if !self.cutting {
+            return;
+        }
+        _ = match self.options.tool {
+            Tool::Spindle { safe_z, .. } => writeln!(self.program, "G0 Z{}", mm(safe_z)),
+            Tool::Laser { .. } => writeln!(self.program, "M5"),
+        };
+        self.cutting = false;
+    }
+
+    fn cut_to(&mut self, point: Point) {
+        _ = writeln!(
+            self.program,
+            "G1 X{} Y{} F{}",
+            mm(point.x),
+            mm(point.y),
+            mm(self.options.feed_rate)
+        );
+        self.current = point;
+    }
+
+    /// Cuts to the point unless the tool is already there.
+    fn join(&mut self, point: Point) {
+        if (self.current.x - point.x).abs() >= 1e-4 || (self.current.y - point.y).abs() >= 1e-4 {
+            self.cut_to(point);
+        }
+    }
+
+    /// Approximates a curve in machine coordinates with straight moves,
+    /// since G-code has no Bézier curves.
+    fn cut_curve(&mut self, curve: CubicTo) {
+        for point in curve.flatten(self.current, CURVE_TOLERANCE) {
+            self.cut_to(point);
+        }
+    }
+}
+
+impl DisplayListOpReceiver for GcodeWriter<'_> {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        let point = self.transform(op.point);
+        self.start_cut(point);
+        self.contour_start = point;
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        let point = self.transform(op.point);
+        self.cut_to(point);
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        let start = self.transform(op.start_point());
+        let end = self.transform(op.end_point());
+        let center = self.transform(op.center);
+        // Arcs start wherever their angle says, which is not always where the last move ended.
+        self.join(start);
+        // Flipping the y-axis keeps the visual direction, so clockwise arcs stay clockwise.
+        _ = writeln!(
+            self.program,
+            "{} X{} Y{} I{} J{} F{}",
+            if op.clockwise { "G2" } else { "G3" },
+            mm(end.x),
+            mm(end.y),
+            mm(center.x - start.x),
+            mm(center.y - start.y),
+            mm(self.options.feed_rate)
+        );
+        self.current = end;
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.cut_curve(CubicTo {
+            control1: self.transform(op.control1),
+            control2: self.transform(op.control2),
+            point: self.transform(op.point),
+        });
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let curve = QuadTo {
+            control: self.transform(op.control),
+            point: self.transform(op.point),
+        };
+        self.cut_curve(curve.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.join(self.contour_start);
+    }
+}
+
+/// Returns the horizontal segments that fill the polygons with the even-odd rule.
+fn hatch_lines(contours: &[Vec<Point>], spacing: f64) -> Vec<(Point, Point)> {
+    let points = contours.iter().flatten();
+    let min_y = points.clone().map(|p| p.y).fold(f64::INFINITY, f64::min);
+    let max_y = points.map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut lines = vec![];
+    let mut y = min_y + spacing / 2.0;
+    let mut reversed = false;
+    while y < max_y {
+        let mut crossings = vec![];
+        for contour in contours {
+            for (i, a) in contour.iter().enumerate() {
+                let b = contour[(i + 1) % contour.len()];
+                if (a.y <= y) != (b.y <= y) {
+                    crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+        let mut segments = crossings
+            .chunks_exact(2)
+            .map(|pair| (Point::new(pair[0], y), Point::new(pair[1], y)))
+            .collect::<Vec<_>>();
+        // Alternate the direction of the passes to shorten the travel between them.
+        if reversed {
+            segments.reverse();
+            segments.iter_mut().for_each(|(a, b)| std::mem::swap(a, b));
+        }
+        lines.extend(segments);
+        reversed = !reversed;
+        y += spacing;
+    }
+    lines
+}
+
+/// Renders the display list to a G-code program in millimeters, with the origin
+/// at the bottom-left corner of the image.
+///
+/// `size` is the size the display list was built with. Contours are traced with
+/// `G2`/`G3` arcs, and the optional hatch passes fill the shapes for engraving.
+pub fn render(display_list: &DisplayList, size: Size, options: &GcodeOptions) -> String {
+    let mut writer = GcodeWriter {
+        options,
+        height: size.height,
+        program: String::new(),
+        cutting: false,
+        current: Point::default(),
+        contour_start: Point::default(),
+    };
+    writer.program.push_str("G21\nG90\nG17\n");
+    if let Tool::Spindle { safe_z, .. } = options.tool {
+        _ = writeln!(writer.program, "G0 Z{}", mm(safe_z));
+    }
+
+    if let Some(spacing) = options.hatch_spacing.filter(|s| *s > 0.0) {
+        // Flatten arcs to a tenth of the spacing, which is invisible on the passes.
+        let tolerance = spacing / 10.0 / options.scale;
+        let contours = flatten(display_list, tolerance)
+            .into_iter()
+            .map(|contour| {
+                contour
+                    .into_iter()
+                    .map(|point| writer.transform(point))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for (start, end) in hatch_lines(&contours, spacing) {
+            writer.start_cut(start);
+            writer.cut_to(end);
+        }
+    }
+    if options.outline {
+        display_list.present(&mut writer);
+    }
+    writer.stop_cut();
+
+    writer.program.push_str("M2\n");
+    writer.program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::{FRAC_PI_2, PI};
+
+    fn program(display_list: &DisplayList) -> Vec<String> {
+        let options = GcodeOptions {
+            tool: Tool::Laser { power: 1000.0 },
+            ..GcodeOptions::default()
+        };
+        render(display_list, Size::new(10.0, 10.0), &options)
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn joins_the_arc_start_with_a_cut() {
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        recorder.move_to(Point::new(0.0, 0.0));
+        // The arc starts at (4, 2), away from the start of the contour.
+        recorder.arc_to(Point::new(2.0, 2.0), 2.0, 0.0, FRAC_PI_2, true);
+        let program = program(&display_list);
+        let arc = program
+            .iter()
+            .position(|line| line.starts_with("G2 "))
+            .unwrap();
+        assert_eq!(program[arc - 1], "G1 X4 Y8 F600");
+        assert!(program[arc].starts_with("G2 X2 Y6 I-2 J0 "));
+    }
+
+    #[test]
+    fn does_not_join_an_arc_that_starts_at_the_tool() {
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        recorder.move_to(Point::new(4.0, 2.0));
+        recorder.arc_to(Point::new(2.0, 2.0), 2.0, 0.0, PI, true);
+        let program = program(&display_list);
+        let arc = program
+            .iter()
+            .position(|line| line.starts_with("G2 "))
+            .unwrap();
+        assert_eq!(program[arc - 1], "M3 S1000");
+    }
+
+    #[test]
+    fn closing_a_path_cuts_back_to_its_start() {
+        let mut display_list = DisplayList::new();
+        let mut recorder = display_list.begin_recording();
+        recorder.move_to(Point::new(1.0, 1.0));
+        recorder.line_to(Point::new(3.0, 1.0));
+        recorder.line_to(Point::new(3.0, 3.0));
+        recorder.close_path();
+        let program = program(&display_list);
+        let cuts: Vec<_> = program
+            .iter()
+            .filter(|line| line.starts_with("G1 "))
+            .collect();
+        assert_eq!(cuts, ["G1 X3 Y9 F600", "G1 X3 Y7 F600", "G1 X1 Y9 F600"]);
+    }
+
+    #[test]
+    fn contours_of_a_code_end_where_they_start() {
+        let display_list = crate::builder::Builder::text("rounded-qr")
+            .size(Size::new(10.0, 10.0))
+            .build()
+            .unwrap();
+        let program = program(&display_list);
+        let point = |line: &str| {
+            let mut words = line.split(' ').skip(1);
+            (
+                words.next().unwrap().to_owned(),
+                words.next().unwrap().to_owned(),
+            )
+        };
+        let mut contour_start = None;
+        let mut last = None;
+        for line in &program {
+            if line.starts_with("G0 X") {
+                contour_start = Some(point(line));
+            } else if line.starts_with("G1 X") || line.starts_with("G2 ") || line.starts_with("G3 ")
+            {
+                last = Some(point(line));
+            } else if line == "M5" {
+                assert_eq!(last, contour_start);
+            }
+        }
+        assert!(contour_start.is_some());
+    }
+}
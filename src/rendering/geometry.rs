@@ -1,7 +1,8 @@
-use std::ops::Mul;
+use core::ops::Mul;
 
 /// A structure that contains a point in a two-dimensional coordinate system.
 #[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -37,6 +38,7 @@ where
 
 /// A structure that contains width and height values.
 #[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     pub width: f64,
     pub height: f64,
@@ -58,6 +60,7 @@ impl Size {
 
 /// A structure that contains the location and dimensions of a rectangle.
 #[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub origin: Point,
     pub size: Size,
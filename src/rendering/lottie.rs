@@ -0,0 +1,350 @@
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::format_number as num;
+use super::geometry::{Point, Size};
+use super::style::{Color, Paint, Style};
+
+/// A contour of a Lottie path, made of cubic Bézier segments between its vertices.
+///
+/// The tangents are relative to their vertices, and straight segments have empty tangents.
+#[derive(Debug, Clone, Default)]
+struct Contour {
+    vertices: Vec<Point>,
+    in_tangents: Vec<Point>,
+    out_tangents: Vec<Point>,
+    closed: bool,
+}
+
+/// A receiver that converts drawing operations to the contours of Lottie paths.
+#[derive(Debug, Clone, Default)]
+struct ContourBuilder {
+    contours: Vec<Contour>,
+    current: Point,
+}
+
+impl ContourBuilder {
+    fn contour(&mut self) -> &mut Contour {
+        if self.contours.last().is_none_or(|contour| contour.closed) {
+            self.start(self.current);
+        }
+        self.contours
+            .last_mut()
+            .expect("a contour should have been started")
+    }
+
+    fn start(&mut self, point: Point) {
+        self.contours.push(Contour {
+            vertices: vec![point],
+            in_tangents: vec![Point::default()],
+            out_tangents: vec![Point::default()],
+            closed: false,
+        });
+        self.current = point;
+    }
+
+    fn push(&mut self, point: Point, in_tangent: Point) {
+        let contour = self.contour();
+        contour.vertices.push(point);
+        contour.in_tangents.push(in_tangent);
+        contour.out_tangents.push(Point::default());
+        self.current = point;
+    }
+}
+
+impl DisplayListOpReceiver for ContourBuilder {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.start(op.point);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.push(op.point, Point::default());
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        let start = op.start_point();
+        if (start.x - self.current.x).hypot(start.y - self.current.y) > 1e-3 {
+            self.push(start, Point::default());
+        }
+        for [control1, control2, end] in op.to_cubics() {
+            self.dispatch_cubic_to(CubicTo {
+                control1,
+                control2,
+                point: end,
+            });
+        }
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        let current = self.current;
+        let contour = self.contour();
+        if let Some(out_tangent) = contour.out_tangents.last_mut() {
+            *out_tangent = Point::new(op.control1.x - current.x, op.control1.y - current.y);
+        }
+        let in_tangent = Point::new(op.control2.x - op.point.x, op.control2.y - op.point.y);
+        self.push(op.point, in_tangent);
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.dispatch_cubic_to(op.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        let Some(contour) = self.contours.last_mut().filter(|contour| !contour.closed) else {
+            return;
+        };
+        // Closed paths join their last vertex to the first one, which must not be repeated.
+        let (first, last) = (
+            contour.vertices[0],
+            contour.vertices[contour.vertices.len() - 1],
+        );
+        if contour.vertices.len() > 1 && (first.x - last.x).hypot(first.y - last.y) < 1e-3 {
+            contour.vertices.pop();
+            contour.out_tangents.pop();
+            contour.in_tangents[0] = contour.in_tangents.pop().unwrap_or_default();
+        }
+        contour.closed = true;
+        self.current = first;
+    }
+}
+
+/// Returns the array of the points as Lottie values.
+fn points(points: &[Point]) -> String {
+    let points = points
+        .iter()
+        .map(|p| format!("[{},{}]", num(p.x), num(p.y)))
+        .collect::<Vec<_>>();
+    format!("[{}]", points.join(","))
+}
+
+/// Returns a static Lottie property.
+fn value(value: &str) -> String {
+    format!("{{\"a\":0,\"k\":{value}}}")
+}
+
+/// Returns the path shapes of the display list.
+fn shapes(display_list: &DisplayList) -> Vec<String> {
+    let mut builder = ContourBuilder::default();
+    display_list.present(&mut builder);
+    builder
+        .contours
+        .iter()
+        .filter(|contour| contour.vertices.len() > 1)
+        .map(|contour| {
+            let path = format!(
+                "{{\"i\":{},\"o\":{},\"v\":{},\"c\":{}}}",
+                points(&contour.in_tangents),
+                points(&contour.out_tangents),
+                points(&contour.vertices),
+                contour.closed
+            );
+            format!("{{\"ty\":\"sh\",\"ks\":{}}}", value(&path))
+        })
+        .collect()
+}
+
+/// Returns the components of the color between 0 and 1, without its alpha.
+fn rgb(color: Color) -> String {
+    format!(
+        "{},{},{}",
+        num(color.r as f64 / 255.0),
+        num(color.g as f64 / 255.0),
+        num(color.b as f64 / 255.0)
+    )
+}
+
+/// Returns the shape that fills the paths before it in a group with the paint,
+/// whose gradients span an image of the given size.
+fn fill(paint: &Paint, size: Size) -> String {
+    let position = |p: Point| {
+        value(&format!(
+            "[{},{}]",
+            num(p.x * size.width),
+            num(p.y * size.height)
+        ))
+    };
+    let (kind, start, end) = match paint {
+        Paint::Solid(color) => {
+            return format!(
+                "{{\"ty\":\"fl\",\"c\":{},\"o\":{},\"r\":1}}",
+                value(&format!("[{},1]", rgb(*color))),
+                value(&num(color.opacity() * 100.0))
+            );
+        }
+        Paint::LinearGradient(gradient) => (1, position(gradient.start), position(gradient.end)),
+        Paint::RadialGradient(gradient) => {
+            let radius = gradient.radius * size.width.min(size.height);
+            let center = gradient.center;
+            let edge = value(&format!(
+                "[{},{}]",
+                num(center.x * size.width + radius),
+                num(center.y * size.height)
+            ));
+            (2, position(center), edge)
+        }
+    };
+
+    // The color stops are followed by opacity stops if any color is translucent.
+    let stops = paint.stops();
+    let mut colors = stops
+        .iter()
+        .map(|stop| format!("{},{}", num(stop.offset.clamp(0.0, 1.0)), rgb(stop.color)))
+        .collect::<Vec<_>>();
+    if stops.iter().any(|stop| stop.color.a != 255) {
+        colors.extend(stops.iter().map(|stop| {
+            format!(
+                "{},{}",
+                num(stop.offset.clamp(0.0, 1.0)),
+                num(stop.color.opacity())
+            )
+        }));
+    }
+    format!(
+        "{{\"ty\":\"gf\",\"t\":{kind},\"s\":{start},\"e\":{end},\"g\":{{\"p\":{},\"k\":{}}},\"o\":{},\"r\":1}}",
+        stops.len(),
+        value(&format!("[{}]", colors.join(","))),
+        value("100")
+    )
+}
+
+/// Returns a group of shapes, which ends with the transform that every group needs.
+fn group(mut items: Vec<String>) -> String {
+    items.push(format!(
+        "{{\"ty\":\"tr\",\"p\":{},\"a\":{},\"s\":{},\"r\":{},\"o\":{}}}",
+        value("[0,0]"),
+        value("[0,0]"),
+        value("[100,100]"),
+        value("0"),
+        value("100")
+    ));
+    format!("{{\"ty\":\"gr\",\"it\":[{}]}}", items.join(","))
+}
+
+/// Returns a shape layer that is shown from the frame `in_point` until before `out_point`.
+fn layer(index: usize, in_point: usize, out_point: usize, shapes: &[String]) -> String {
+    let transform = format!(
+        "{{\"o\":{},\"r\":{},\"p\":{},\"a\":{},\"s\":{}}}",
+        value("100"),
+        value("0"),
+        value("[0,0,0]"),
+        value("[0,0,0]"),
+        value("[100,100,100]")
+    );
+    format!(
+        "{{\"ddd\":0,\"ind\":{index},\"ty\":4,\"sr\":1,\"ks\":{transform},\"ao\":0,\"shapes\":[{}],\"ip\":{in_point},\"op\":{out_point},\"st\":0,\"bm\":0}}",
+        shapes.join(",")
+    )
+}
+
+/// Renders the frames of an animation to a Lottie animation of the given size,
+/// which shows one frame after the other at `frame_rate` frames per second.
+///
+/// Every frame is a shape layer that is only shown during its frame.
+pub fn render(frames: &[DisplayList], size: Size, style: &Style, frame_rate: f64) -> String {
+    let mut layers = vec![];
+    for (i, frame) in frames.iter().enumerate() {
+        let groups = style
+            .fills(frame)
+            .iter()
+            .rev()
+            .filter(|(display_list, _)| !display_list.is_empty())
+            .map(|(display_list, paint)| {
+                let mut items = shapes(display_list);
+                items.push(fill(paint, size));
+                group(items)
+            })
+            .collect::<Vec<_>>();
+        layers.push(layer(layers.len() + 1, i, i + 1, &groups));
+    }
+    if let Some(background) = &style.background {
+        let radius = background
+            .corner_radius
+            .min(size.width.min(size.height) / 2.0)
+            .max(0.0);
+        let rect = format!(
+            "{{\"ty\":\"rc\",\"p\":{},\"s\":{},\"r\":{}}}",
+            value(&format!(
+                "[{},{}]",
+                num(size.width / 2.0),
+                num(size.height / 2.0)
+            )),
+            value(&format!("[{},{}]", num(size.width), num(size.height))),
+            value(&num(radius))
+        );
+        let shape = group(vec![rect, fill(&background.paint, size)]);
+        layers.push(layer(layers.len() + 1, 0, frames.len(), &[shape]));
+    }
+
+    // Layers are listed from front to back.
+    format!(
+        "{{\"v\":\"5.7.0\",\"fr\":{},\"ip\":0,\"op\":{},\"w\":{},\"h\":{},\"ddd\":0,\"assets\":[],\"layers\":[{}]}}",
+        num(frame_rate),
+        frames.len(),
+        size.width.ceil(),
+        size.height.ceil(),
+        layers.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::Animation;
+    use crate::builder::Builder;
+    use crate::rendering::geometry::Rect;
+    use serde_json::Value;
+
+    #[test]
+    fn shows_every_frame_for_one_frame() {
+        let builder = Builder::text("hello").size(Size::new(200.0, 200.0));
+        let frames = Animation::reveal(builder).frames(30).unwrap();
+        let size = Size::new(200.0, 200.0);
+        let lottie: Value = serde_json::from_str(&render(&frames, size, &Style::default(), 60.0))
+            .expect("the animation should be valid JSON");
+
+        assert_eq!(lottie["fr"], 60.0);
+        assert_eq!((&lottie["ip"], &lottie["op"]), (&0.into(), &30.into()));
+        // 30 frames at 60 frames per second last half a second.
+        let duration = lottie["op"].as_f64().unwrap() / lottie["fr"].as_f64().unwrap();
+        assert_eq!(duration, 0.5);
+        assert_eq!((&lottie["w"], &lottie["h"]), (&200.into(), &200.into()));
+
+        // Every frame has its layer, in front of the layer of the background.
+        let layers = lottie["layers"].as_array().unwrap();
+        assert_eq!(layers.len(), 31);
+        for (i, layer) in layers[..30].iter().enumerate() {
+            assert_eq!((&layer["ip"], &layer["op"]), (&i.into(), &(i + 1).into()));
+        }
+        let background = &layers[30];
+        assert_eq!(
+            (&background["ip"], &background["op"]),
+            (&0.into(), &30.into())
+        );
+        // The first frame is empty, and the last one is the whole code.
+        assert_eq!(layers[0]["shapes"], Value::Array(vec![]));
+        assert!(!layers[29]["shapes"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn converts_contours_to_closed_paths() {
+        let rect = Rect {
+            origin: Point::new(1.0, 2.0),
+            size: Size::new(3.0, 4.0),
+        };
+        let frames = [DisplayList::rounded_rect(rect, 0.0)];
+        let style = Style {
+            background: None,
+            ..Style::default()
+        };
+        let lottie: Value =
+            serde_json::from_str(&render(&frames, Size::new(5.0, 6.0), &style, 30.0)).unwrap();
+        let layers = lottie["layers"].as_array().unwrap();
+        assert_eq!(layers.len(), 1);
+        let items = &layers[0]["shapes"][0]["it"];
+        assert_eq!(items[0]["ty"], "sh");
+        let path = &items[0]["ks"]["k"];
+        assert_eq!(path["c"], true);
+        assert_eq!(path["v"].as_array().unwrap().len(), 4);
+        assert_eq!(items[1]["ty"], "fl");
+    }
+}
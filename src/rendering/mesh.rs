@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::display_list::DisplayList;
+use super::flatten::flatten;
+use super::format_millimeters as mm;
+use super::geometry::{Point, Size};
+use super::triangulate::{contains, signed_area, triangulate};
+use super::zip::ZipWriter;
+
+/// Options that control the extrusion of a QR Code into a solid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshOptions {
+    /// The number of millimeters per unit of the display list.
+    pub scale: f64,
+    /// The height of the raised modules, in millimeters.
+    pub height: f64,
+    /// The thickness of the plate below the modules, in millimeters, or zero to leave it out.
+    pub base_thickness: f64,
+    /// The distance by which the plate extends beyond the image, in millimeters.
+    pub base_margin: f64,
+    /// The maximum distance between the arcs and their approximating edges, in millimeters.
+    pub tolerance: f64,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            height: 1.0,
+            base_thickness: 2.0,
+            base_margin: 2.0,
+            tolerance: 0.05,
+        }
+    }
+}
+
+/// A triangle mesh in millimeters, whose triangles run counterclockwise
+/// when seen from outside the solid.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl Mesh {
+    /// Returns `true` if every edge is shared by exactly two triangles that traverse it
+    /// in opposite directions, which means the mesh encloses a volume without gaps.
+    pub fn is_watertight(&self) -> bool {
+        let mut edges = HashMap::new();
+        for triangle in &self.triangles {
+            for i in 0..3 {
+                let edge = (triangle[i], triangle[(i + 1) % 3]);
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        edges
+            .iter()
+            .all(|(&(a, b), &count)| count == 1 && edges.get(&(b, a)) == Some(&1))
+    }
+
+    /// Encodes the mesh as a binary STL file.
+    pub fn to_stl(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(84 + self.triangles.len() * 50);
+        let mut header = [0u8; 80];
+        let title = b"rounded-qr";
+        header[..title.len()].copy_from_slice(title);
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&(self.triangles.len() as u32).to_le_bytes());
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|i| self.vertices[i as usize]);
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let n = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            let normal = if length > 0.0 {
+                n.map(|x| x / length)
+            } else {
+                [0.0; 3]
+            };
+            for value in [normal, a, b, c].iter().flatten() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Encodes the mesh as a Wavefront OBJ file.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::from("# rounded-qr\n");
+        for [x, y, z] in self.vertices.iter().map(|vertex| vertex.map(f64::from)) {
+            _ = writeln!(obj, "v {} {} {}", mm(x), mm(y), mm(z));
+        }
+        for [a, b, c] in &self.triangles {
+            _ = writeln!(obj, "f {} {} {}", a + 1, b + 1, c + 1);
+        }
+        obj
+    }
+
+    /// Encodes the mesh as a 3D Manufacturing Format package.
+    pub fn to_3mf(&self) -> Vec<u8> {
+        let mut model = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<model unit="millimeter" xml:lang="en-US" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">"#,
+            "\n<resources>\n<object id=\"1\" type=\"model\">\n<mesh>\n<vertices>\n",
+        ));
+        for [x, y, z] in self.vertices.iter().map(|vertex| vertex.map(f64::from)) {
+            _ = writeln!(
+                model,
+                r#"<vertex x="{}" y="{}" z="{}"/>"#,
+                mm(x),
+                mm(y),
+                mm(z)
+            );
+        }
+        model.push_str("</vertices>\n<triangles>\n");
+        for [a, b, c] in &self.triangles {
+            _ = writeln!(model, r#"<triangle v1="{a}" v2="{b}" v3="{c}"/>"#);
+        }
+        model.push_str(concat!(
+            "</triangles>\n</mesh>\n</object>\n</resources>\n",
+            "<build>\n<item objectid=\"1\"/>\n</build>\n</model>\n",
+        ));
+
+        let mut zip = ZipWriter::new();
+        zip.add_file(
+            "[Content_Types].xml",
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+                r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+                r#"<Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>"#,
+                r#"</Types>"#,
+            )
+            .as_bytes(),
+        );
+        zip.add_file(
+            "_rels/.rels",
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+                r#"<Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>"#,
+                r#"</Relationships>"#,
+            )
+            .as_bytes(),
+        );
+        zip.add_file("3D/3dmodel.model", model.as_bytes());
+        zip.finish()
+    }
+}
+
+/// Rounds the coordinate to a tenth of a micrometer, so that shapes that touch
+/// meet at exactly the same point despite floating point noise.
+fn snap(value: f64) -> f64 {
+    (value * 1e4).round() / 1e4
+}
+
+/// Removes the vertices that lie on the straight line through their neighbors,
+/// which includes repeated points and the tips of spikes that run out and back
+/// along the line, as the corners of finder patterns rounded by more than a module do.
+fn remove_collinear(points: &mut Vec<Point>) {
+    let mut i = 0;
+    while points.len() > 2 && i < points.len() {
+        let len = points.len();
+        let (a, b, c) = (
+            points[(i + len - 1) % len],
+            points[i],
+            points[(i + 1) % len],
+        );
+        let (u, v) = ((b.x - a.x, b.y - a.y), (c.x - b.x, c.y - b.y));
+        let cross = u.0 * v.1 - u.1 * v.0;
+        if cross.abs() <= 1e-9 * u.0.hypot(u.1) * v.0.hypot(v.1) {
+            points.remove(i);
+            // Removing a tip can turn the previous vertex into one.
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Collects the triangles of the mesh, sharing the vertices at each height.
+struct MeshBuilder<'a> {
+    points: &'a [Point],
+    vertices: HashMap<(usize, usize), u32>,
+    heights: Vec<f64>,
+    /// The level of the top of the plate, whose points at the same position share
+    /// a vertex, so that shapes touching at a corner are joined there.
+    plate_level: Option<usize>,
+    /// The first point at the same position as each point.
+    first_at: Vec<usize>,
+    mesh: Mesh,
+}
+
+impl MeshBuilder<'_> {
+    fn vertex(&mut self, point: usize, level: usize) -> u32 {
+        let point = self.point_at(point, level);
+        *self.vertices.entry((point, level)).or_insert_with(|| {
+            let p = self.points[point];
+            self.mesh
+                .vertices
+                .push([p.x as f32, p.y as f32, self.heights[level] as f32]);
+            self.mesh.vertices.len() as u32 - 1
+        })
+    }
+
+    fn point_at(&self, point: usize, level: usize) -> usize {
+        if self.plate_level == Some(level) {
+            self.first_at[point]
+        } else {
+            point
+        }
+    }
+
+    /// Adds the triangles of the face, facing up or down.
+    fn face(&mut self, outer: &[usize], holes: &[Vec<usize>], level: usize, up: bool) {
+        let ring = |ring: &[usize]| -> Vec<usize> {
+            ring.iter().map(|&i| self.point_at(i, level)).collect()
+        };
+        let outer = ring(outer);
+        let holes = holes.iter().map(|hole| ring(hole)).collect::<Vec<_>>();
+        for [a, b, c] in triangulate(self.points, &outer, &holes) {
+            let [a, b, c] = [a, b, c].map(|i| self.vertex(i, level));
+            self.mesh
+                .triangles
+                .push(if up { [a, b, c] } else { [a, c, b] });
+        }
+    }
+
+    /// Adds the vertical walls on the right side of the edges of the contour.
+    fn walls(&mut self, contour: &[usize], bottom: usize, top: usize) {
+        for (i, &a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            let (a0, b0) = (self.vertex(a, bottom), self.vertex(b, bottom));
+            let (a1, b1) = (self.vertex(a, top), self.vertex(b, top));
+            self.mesh.triangles.push([a0, b0, b1]);
+            self.mesh.triangles.push([a0, b1, a1]);
+        }
+    }
+}
+
+/// Extrudes the filled shapes of the display list into a solid for 3D printing.
+///
+/// `size` is the size the display list was built with. The shapes are raised
+/// by `height` on top of the base plate, with the bottom-left corner of the image
+/// at the origin and arcs approximated within `tolerance`. The resulting mesh is closed,
+/// so it can be sliced without repairs.
+pub fn extrude(display_list: &DisplayList, size: Size, options: &MeshOptions) -> Mesh {
+    let scale = options.scale;
+    let mut points = vec![];
+    let mut contours = vec![];
+    for contour in flatten(display_list, options.tolerance / scale) {
+        let mut contour = contour
+            .into_iter()
+            .map(|p| Point::new(snap(p.x * scale), snap((size.height - p.y) * scale)))
+            .collect::<Vec<_>>();
+        remove_collinear(&mut contour);
+        if contour.len() > 2 {
+            contours.push((points.len()..points.len() + contour.len()).collect::<Vec<_>>());
+            points.extend(contour);
+        }
+    }
+
+    // The largest contour is always an outer boundary, which tells the direction
+    // of the others. Outer boundaries are made counterclockwise and holes clockwise.
+    let areas = contours
+        .iter()
+        .map(|contour| signed_area(&points, contour))
+        .collect::<Vec<_>>();
+    let outer_sign = areas
+        .iter()
+        .copied()
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(1.0)
+        .signum();
+    let mut outers = vec![];
+    let mut holes = vec![];
+    for (mut contour, area) in contours.into_iter().zip(areas) {
+        if area.signum() == outer_sign {
+            if area < 0.0 {
+                contour.reverse();
+            }
+            outers.push((contour, area.abs()));
+        } else {
+            if area > 0.0 {
+                contour.reverse();
+            }
+            holes.push((contour, area.abs()));
+        }
+    }
+
+    // Each hole belongs to the smallest shape around it, and each shape
+    // that is nested in a hole is surrounded by the exposed plate inside it.
+    let parent = |contour: &[usize], candidates: &[(Vec<usize>, f64)]| {
+        let (a, b) = (points[contour[0]], points[contour[1]]);
+        let probe = Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (candidate, _))| contains(&points, candidate, probe))
+            .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+            .map(|(i, _)| i)
+    };
+    let mut outer_holes = vec![vec![]; outers.len()];
+    for (hole, _) in &holes {
+        if let Some(i) = parent(hole, &outers) {
+            outer_holes[i].push(hole.clone());
+        }
+    }
+    let mut islands = vec![vec![]; holes.len()];
+    let mut covered = vec![];
+    for (outer, _) in &outers {
+        let reversed = outer.iter().rev().copied().collect::<Vec<_>>();
+        match parent(outer, &holes) {
+            Some(i) => islands[i].push(reversed),
+            None => covered.push(reversed),
+        }
+    }
+
+    let has_base = options.base_thickness > 0.0;
+    let mut heights = vec![0.0];
+    if has_base {
+        heights.push(options.base_thickness);
+    }
+    heights.push(heights[heights.len() - 1] + options.height);
+    let (base, bottom, top) = (0, heights.len() - 2, heights.len() - 1);
+
+    let plate = if has_base {
+        let margin = options.base_margin;
+        let (width, height) = (size.width * scale, size.height * scale);
+        let start = points.len();
+        points.extend([
+            Point::new(-margin, -margin),
+            Point::new(width + margin, -margin),
+            Point::new(width + margin, height + margin),
+            Point::new(-margin, height + margin),
+        ]);
+        Some((start..start + 4).collect::<Vec<_>>())
+    } else {
+        None
+    };
+
+    let mut first_at = HashMap::new();
+    let first_at = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| *first_at.entry((p.x.to_bits(), p.y.to_bits())).or_insert(i))
+        .collect();
+    let mut builder = MeshBuilder {
+        points: &points,
+        vertices: HashMap::new(),
+        heights,
+        plate_level: has_base.then_some(bottom),
+        first_at,
+        mesh: Mesh::default(),
+    };
+    for ((outer, _), holes) in outers.iter().zip(&outer_holes) {
+        builder.face(outer, holes, top, true);
+        if !has_base {
+            builder.face(outer, holes, bottom, false);
+        }
+        builder.walls(outer, bottom, top);
+        for hole in holes {
+            builder.walls(hole, bottom, top);
+        }
+    }
+    if let Some(plate) = plate {
+        // The top of the plate is exposed around the shapes and inside their holes.
+        builder.face(&plate, &covered, bottom, true);
+        for ((hole, _), islands) in holes.iter().zip(&islands) {
+            let exposed = hole.iter().rev().copied().collect::<Vec<_>>();
+            builder.face(&exposed, islands, bottom, true);
+        }
+        builder.face(&plate, &[], base, false);
+        builder.walls(&plate, base, bottom);
+    }
+    builder.mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::types::{CornerRadii, LogoRegion};
+
+    const SIZE: Size = Size {
+        width: 50.0,
+        height: 50.0,
+    };
+
+    /// Extrudes the code with and without a base plate, checking that both meshes
+    /// are closed and that their horizontal faces point out of the solid.
+    fn assert_solid(builder: Builder) {
+        let display_list = builder.size(SIZE).build().unwrap();
+        for base_thickness in [0.0, 2.0] {
+            let options = MeshOptions {
+                base_thickness,
+                ..MeshOptions::default()
+            };
+            let mesh = extrude(&display_list, SIZE, &options);
+            assert!(!mesh.triangles.is_empty());
+            assert!(mesh.is_watertight(), "base thickness {base_thickness}");
+            for triangle in &mesh.triangles {
+                let [a, b, c] = triangle.map(|i| mesh.vertices[i as usize]);
+                if a[2] != b[2] || b[2] != c[2] {
+                    continue;
+                }
+                // Twice the area seen from above, positive for faces pointing up.
+                let area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+                let area = if a[2] == 0.0 { -area } else { area };
+                assert!(area > -1e-4, "{triangle:?} is flipped");
+            }
+        }
+    }
+
+    #[test]
+    fn extrudes_a_rounded_code() {
+        assert_solid(Builder::text("rounded-qr"));
+    }
+
+    #[test]
+    fn extrudes_a_square_code() {
+        assert_solid(Builder::text("rounded-qr").corner_radii(CornerRadii::SQUARE));
+    }
+
+    #[test]
+    fn extrudes_an_inverted_code() {
+        assert_solid(Builder::text("rounded-qr").quiet_zone(2).invert(true));
+        assert_solid(
+            Builder::text("rounded-qr")
+                .quiet_zone(2)
+                .invert(true)
+                .corner_radii(CornerRadii::SQUARE),
+        );
+    }
+
+    #[test]
+    fn extrudes_a_code_with_a_logo_hole() {
+        let logo = LogoRegion::new(7, 5);
+        assert_solid(Builder::text("https://github.com/ktiays/rounded-qr").logo_region(logo));
+        assert_solid(
+            Builder::text("https://github.com/ktiays/rounded-qr")
+                .quiet_zone(2)
+                .invert(true)
+                .logo_region(logo),
+        );
+    }
+
+    #[test]
+    fn detects_open_meshes() {
+        let display_list = Builder::text("rounded-qr").build().unwrap();
+        let mut mesh = extrude(&display_list, SIZE, &MeshOptions::default());
+        mesh.triangles.pop();
+        assert!(!mesh.is_watertight());
+    }
+}
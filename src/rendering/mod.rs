@@ -1,2 +1,75 @@
+use alloc::format;
+use alloc::string::String;
+
+mod arc;
+mod cubic;
 pub mod display_list;
 pub mod geometry;
+pub mod style;
+
+#[cfg(feature = "std")]
+pub mod canvas;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod dxf;
+#[cfg(feature = "std")]
+pub mod eps;
+#[cfg(feature = "std")]
+pub mod escpos;
+#[cfg(feature = "std")]
+pub mod flatten;
+#[cfg(feature = "std")]
+pub mod gcode;
+#[cfg(feature = "std")]
+pub mod lottie;
+#[cfg(feature = "std")]
+pub mod mesh;
+#[cfg(feature = "std")]
+pub mod pdf;
+#[cfg(feature = "std")]
+pub mod raster;
+#[cfg(feature = "std")]
+pub mod svg;
+#[cfg(feature = "std")]
+pub mod terminal;
+#[cfg(feature = "std")]
+mod triangulate;
+#[cfg(feature = "std")]
+pub mod vector_drawable;
+#[cfg(feature = "std")]
+mod zip;
+#[cfg(feature = "std")]
+pub mod zpl;
+
+#[cfg(feature = "embedded-graphics")]
+pub mod embedded;
+
+/// Formats a coordinate with at most three fractional digits and no trailing zeros.
+pub(crate) fn format_number(value: f64) -> String {
+    format_number_with_precision(value, 3)
+}
+
+/// Formats a length in millimeters, precise to a tenth of a micrometer.
+#[cfg(feature = "std")]
+pub(crate) fn format_millimeters(value: f64) -> String {
+    format_number_with_precision(value, 4)
+}
+
+/// The maximum distance, in millimeters, between a curve and the straight segments
+/// approximating it, in formats for machines that have no Bézier curves.
+#[cfg(feature = "std")]
+pub(crate) const CURVE_TOLERANCE: f64 = 0.01;
+
+/// Formats a number with at most `digits` fractional digits and no trailing zeros.
+pub(crate) fn format_number_with_precision(value: f64, digits: usize) -> String {
+    let mut s = format!("{:.*}", digits, value);
+    if s.contains('.') {
+        let trimmed = s.trim_end_matches('0').trim_end_matches('.').len();
+        s.truncate(trimmed);
+    }
+    if s == "-0" {
+        s.remove(0);
+    }
+    s
+}
@@ -0,0 +1,362 @@
+use std::fmt::Write;
+
+use crate::sheet::SheetPage;
+
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::format_number as num;
+use super::geometry::{Point, Rect, Size};
+use super::style::{Color, GradientStop, Paint, Style};
+
+/// A PDF content stream.
+///
+/// The coordinate system of the stream is flipped to match the one of display lists,
+/// with the origin at the top-left corner of the page and the y-axis pointing down.
+#[derive(Debug, Clone)]
+pub struct PdfContent {
+    data: String,
+    /// The dictionaries of the shadings painted by the stream, named `/Sh0`, `/Sh1`, and so on.
+    shadings: Vec<String>,
+    /// The first point of the current subpath, where closing it leads back to.
+    start: Point,
+    /// The end of the last path operation, where quadratic curves start.
+    current: Point,
+}
+
+impl PdfContent {
+    /// Creates an empty content stream for a page of the given size.
+    pub fn new(page_size: Size) -> Self {
+        Self {
+            data: format!("1 0 0 -1 0 {} cm\n", num(page_size.height)),
+            shadings: vec![],
+            start: Point::default(),
+            current: Point::default(),
+        }
+    }
+
+    /// Sets the color used by subsequent fill operations.
+    ///
+    /// The alpha component of the color is ignored.
+    pub fn set_fill_color(&mut self, color: Color) {
+        _ = writeln!(self.data, "{} rg", rgb(color));
+    }
+
+    /// Fills the rectangle with the current fill color.
+    pub fn fill_rect(&mut self, rect: Rect) {
+        _ = writeln!(
+            self.data,
+            "{} {} {} {} re f",
+            num(rect.origin.x),
+            num(rect.origin.y),
+            num(rect.size.width),
+            num(rect.size.height)
+        );
+    }
+
+    /// Fills the path built by the operations received so far with the current fill color.
+    pub fn fill(&mut self) {
+        self.data.push_str("f\n");
+    }
+
+    /// Fills the display list with the current fill color.
+    pub fn fill_display_list(&mut self, display_list: &DisplayList) {
+        if display_list.is_empty() {
+            return;
+        }
+        display_list.present(self);
+        self.fill();
+    }
+
+    /// Fills the display list with the paint, whose gradients span the given bounds.
+    ///
+    /// The alpha components of the colors are ignored.
+    pub fn fill_display_list_with_paint(
+        &mut self,
+        display_list: &DisplayList,
+        paint: &Paint,
+        bounds: Rect,
+    ) {
+        if display_list.is_empty() {
+            return;
+        }
+        let Rect { origin, size } = bounds;
+        let position = |p: Point| {
+            format!(
+                "{} {}",
+                num(origin.x + p.x * size.width),
+                num(origin.y + p.y * size.height)
+            )
+        };
+        let (shading_type, coords) = match paint {
+            Paint::Solid(color) => {
+                self.set_fill_color(*color);
+                return self.fill_display_list(display_list);
+            }
+            Paint::LinearGradient(gradient) => (
+                2,
+                format!("{} {}", position(gradient.start), position(gradient.end)),
+            ),
+            Paint::RadialGradient(gradient) => {
+                let center = position(gradient.center);
+                let radius = num(gradient.radius * size.width.min(size.height));
+                (3, format!("{center} 0 {center} {radius}"))
+            }
+        };
+        let name = format!("Sh{}", self.shadings.len());
+        self.shadings.push(format!(
+            "<< /ShadingType {shading_type} /ColorSpace /DeviceRGB /Coords [{coords}] /Function {} /Extend [true true] >>",
+            gradient_function(&paint.stops())
+        ));
+
+        // Paint the shading through the display list used as a clipping path.
+        self.data.push_str("q\n");
+        display_list.present(self);
+        _ = writeln!(self.data, "W n\n/{name} sh\nQ");
+    }
+
+    /// Returns the resource dictionary of the stream.
+    fn resources(&self) -> String {
+        if self.shadings.is_empty() {
+            return "<< >>".to_owned();
+        }
+        let shadings = self
+            .shadings
+            .iter()
+            .enumerate()
+            .map(|(i, shading)| format!("/Sh{i} {shading}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("<< /Shading << {shadings} >> >>")
+    }
+}
+
+/// Returns the components of the color as PDF numbers.
+fn rgb(color: Color) -> String {
+    format!(
+        "{} {} {}",
+        num(color.r as f64 / 255.0),
+        num(color.g as f64 / 255.0),
+        num(color.b as f64 / 255.0)
+    )
+}
+
+/// Returns a function that interpolates between the gradient stops over the domain [0 1].
+fn gradient_function(stops: &[GradientStop]) -> String {
+    let mut stops = stops
+        .iter()
+        .map(|stop| GradientStop::new(stop.offset.clamp(0.0, 1.0), stop.color))
+        .collect::<Vec<_>>();
+    let (Some(first), Some(last)) = (stops.first().copied(), stops.last().copied()) else {
+        return gradient_function(&[GradientStop::new(0.0, Color::BLACK)]);
+    };
+    if first.offset > 0.0 || stops.len() == 1 {
+        stops.insert(0, GradientStop::new(0.0, first.color));
+    }
+    if last.offset < 1.0 {
+        stops.push(GradientStop::new(1.0, last.color));
+    }
+
+    let segments = stops
+        .windows(2)
+        .map(|pair| {
+            format!(
+                "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+                rgb(pair[0].color),
+                rgb(pair[1].color)
+            )
+        })
+        .collect::<Vec<_>>();
+    if segments.len() == 1 {
+        return segments[0].clone();
+    }
+    let bounds = stops[1..stops.len() - 1]
+        .iter()
+        .map(|stop| num(stop.offset))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let encode = vec!["0 1"; segments.len()].join(" ");
+    format!(
+        "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{bounds}] /Encode [{encode}] >>",
+        segments.join(" ")
+    )
+}
+
+impl DisplayListOpReceiver for PdfContent {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        _ = writeln!(self.data, "{} {} m", num(op.point.x), num(op.point.y));
+        (self.start, self.current) = (op.point, op.point);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        _ = writeln!(self.data, "{} {} l", num(op.point.x), num(op.point.y));
+        self.current = op.point;
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        for [control1, control2, end] in op.to_cubics() {
+            _ = writeln!(
+                self.data,
+                "{} {} {} {} {} {} c",
+                num(control1.x),
+                num(control1.y),
+                num(control2.x),
+                num(control2.y),
+                num(end.x),
+                num(end.y)
+            );
+        }
+        self.current = op.end_point();
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        _ = writeln!(
+            self.data,
+            "{} {} {} {} {} {} c",
+            num(op.control1.x),
+            num(op.control1.y),
+            num(op.control2.x),
+            num(op.control2.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+        self.current = op.point;
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        // PDF only has cubic curves, which represent quadratic ones exactly.
+        self.dispatch_cubic_to(op.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.data.push_str("h\n");
+        self.current = self.start;
+    }
+}
+
+/// A PDF document made of pages with vector content.
+#[derive(Debug, Clone, Default)]
+pub struct PdfDocument {
+    pages: Vec<(Size, PdfContent)>,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a page of the given size, in points, to the document.
+    pub fn add_page(&mut self, size: Size, content: PdfContent) {
+        self.pages.push((size, content));
+    }
+
+    /// Serializes the document.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = vec![];
+        let mut object = |out: &mut Vec<u8>, body: String| {
+            offsets.push(out.len());
+            out.extend_from_slice(
+                format!("{} 0 obj\n{}\nendobj\n", offsets.len(), body).as_bytes(),
+            );
+        };
+
+        // Objects 1 and 2 are the catalog and the page tree, followed by
+        // a page object and its content stream for every page.
+        let kids = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", 3 + i * 2))
+            .collect::<Vec<_>>()
+            .join(" ");
+        object(&mut out, "<< /Type /Catalog /Pages 2 0 R >>".to_owned());
+        object(
+            &mut out,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids,
+                self.pages.len()
+            ),
+        );
+        for (i, (size, content)) in self.pages.iter().enumerate() {
+            object(
+                &mut out,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R /Resources {} >>",
+                    num(size.width),
+                    num(size.height),
+                    4 + i * 2,
+                    content.resources()
+                ),
+            );
+            object(
+                &mut out,
+                format!(
+                    "<< /Length {} >>\nstream\n{}endstream",
+                    content.data.len(),
+                    content.data
+                ),
+            );
+        }
+
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in &offsets {
+            _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref
+        );
+        out.extend_from_slice(trailer.as_bytes());
+        out
+    }
+}
+
+/// Paints the display list with the style, whose background fills the bounds
+/// and whose gradients span them.
+fn paint(content: &mut PdfContent, display_list: &DisplayList, bounds: Rect, style: &Style) {
+    if let Some(background) = &style.background {
+        if background.paint.stops().iter().any(|stop| stop.color.a > 0) {
+            let shape = DisplayList::rounded_rect(bounds, background.corner_radius);
+            content.fill_display_list_with_paint(&shape, &background.paint, bounds);
+        }
+    }
+    for (display_list, paint) in style.fills(display_list) {
+        content.fill_display_list_with_paint(&display_list, paint, bounds);
+    }
+}
+
+/// Renders the display list to a single-page PDF document of the given size, in points.
+///
+/// The alpha components of the colors are ignored.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style) -> Vec<u8> {
+    let bounds = Rect {
+        origin: Point::default(),
+        size,
+    };
+    let mut content = PdfContent::new(size);
+    paint(&mut content, display_list, bounds, style);
+
+    let mut document = PdfDocument::new();
+    document.add_page(size, content);
+    document.to_bytes()
+}
+
+/// Renders the pages of a sheet to a PDF document with a page for each of them.
+///
+/// Every code is painted with the style over its own bounds.
+/// The alpha components of the colors are ignored.
+pub fn render_sheet(pages: &[SheetPage], style: &Style) -> Vec<u8> {
+    let mut document = PdfDocument::new();
+    for page in pages {
+        let mut content = PdfContent::new(page.size);
+        for code in &page.codes {
+            paint(&mut content, &code.display_list, code.bounds, style);
+        }
+        content.set_fill_color(Color::BLACK);
+        content.fill_display_list(&page.crop_marks);
+        document.add_page(page.size, content);
+    }
+    document.to_bytes()
+}
@@ -0,0 +1,360 @@
+use super::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use super::geometry::{Point, Rect, Size};
+use super::style::{Color, Paint, Style};
+
+/// The maximum distance, in pixels, between an arc or a curve and the segments approximating it.
+const ARC_TOLERANCE: f64 = 0.05;
+
+/// A single-channel image that stores how much of every pixel is covered by a shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    /// The coverage of the pixels in row-major order, from 0 (empty) to 255 (covered).
+    pub data: Vec<u8>,
+}
+
+impl Mask {
+    /// Returns the coverage of the pixel at the given position.
+    pub fn coverage(&self, x: usize, y: usize) -> u8 {
+        self.data[y * self.width + x]
+    }
+
+    /// Converts the mask to a bitmap whose pixels are set where they are at least half covered.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let bytes_per_row = self.width.div_ceil(8);
+        let mut data = vec![0; bytes_per_row * self.height];
+        for (y, row) in self.data.chunks_exact(self.width.max(1)).enumerate() {
+            for (x, &coverage) in row.iter().enumerate() {
+                if coverage >= 128 {
+                    data[y * bytes_per_row + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        Bitmap {
+            width: self.width,
+            height: self.height,
+            bytes_per_row,
+            data,
+        }
+    }
+}
+
+/// A black and white image with one bit per pixel, as used by printers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    /// The number of bytes that store each row, padded to whole bytes.
+    pub bytes_per_row: usize,
+    /// The rows from top to bottom, with the leftmost pixel in the most significant bit
+    /// of each byte and set bits for covered pixels.
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Returns `true` if the pixel at the given position is covered.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.data[y * self.bytes_per_row + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Returns the bytes of the row at the given position.
+    pub fn row(&self, y: usize) -> &[u8] {
+        &self.data[y * self.bytes_per_row..(y + 1) * self.bytes_per_row]
+    }
+}
+
+/// An RGBA image with 8 bits per channel and non-premultiplied alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pixmap {
+    pub width: usize,
+    pub height: usize,
+    /// The pixels in row-major order, four bytes per pixel.
+    pub data: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Creates an image filled with the given color.
+    pub fn new(width: usize, height: usize, color: Color) -> Self {
+        let data = [color.r, color.g, color.b, color.a].repeat(width * height);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Blends the color over the image, weighted by the coverage of the mask.
+    ///
+    /// The mask must have the same dimensions as the image.
+    pub fn fill_mask(&mut self, mask: &Mask, color: Color) {
+        debug_assert_eq!((mask.width, mask.height), (self.width, self.height));
+        for (pixel, coverage) in self.data.chunks_exact_mut(4).zip(&mask.data) {
+            blend(pixel, color, *coverage);
+        }
+    }
+
+    /// Blends the paint over the image, weighted by the coverage of the mask.
+    ///
+    /// Gradients span the given bounds, in pixels. The mask must have the same
+    /// dimensions as the image.
+    pub fn fill_mask_with_paint(&mut self, mask: &Mask, paint: &Paint, bounds: Rect) {
+        if let Paint::Solid(color) = paint {
+            return self.fill_mask(mask, *color);
+        }
+        debug_assert_eq!((mask.width, mask.height), (self.width, self.height));
+        let width = self.width.max(1);
+        for (i, (pixel, coverage)) in self.data.chunks_exact_mut(4).zip(&mask.data).enumerate() {
+            if *coverage == 0 {
+                continue;
+            }
+            let center = Point::new((i % width) as f64 + 0.5, (i / width) as f64 + 0.5);
+            blend(pixel, paint.color_at(center, bounds), *coverage);
+        }
+    }
+
+    /// Encodes the image in the PNG format.
+    ///
+    /// Fails if the image is empty or too large for a PNG image.
+    #[cfg(feature = "png")]
+    pub fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let width = u32::try_from(self.width).map_err(|_| png::EncodingError::LimitsExceeded)?;
+        let height = u32::try_from(self.height).map_err(|_| png::EncodingError::LimitsExceeded)?;
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.data)?;
+        writer.finish()?;
+        Ok(out)
+    }
+}
+
+/// Blends the color over an RGBA pixel, weighted by the coverage.
+fn blend(pixel: &mut [u8], color: Color, coverage: u8) {
+    let alpha = coverage as f64 / 255.0 * color.opacity();
+    if alpha <= 0.0 {
+        return;
+    }
+    let dst_alpha = pixel[3] as f64 / 255.0;
+    let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+    let mix = |src: u8, dst: u8| {
+        let value = (src as f64 * alpha + dst as f64 * dst_alpha * (1.0 - alpha)) / out_alpha;
+        value.round() as u8
+    };
+    pixel[0] = mix(color.r, pixel[0]);
+    pixel[1] = mix(color.g, pixel[1]);
+    pixel[2] = mix(color.b, pixel[2]);
+    pixel[3] = (out_alpha * 255.0).round() as u8;
+}
+
+/// A receiver that computes the anti-aliased coverage of the filled drawing operations.
+///
+/// Coverage is accumulated from the signed area under every edge, which fills
+/// the contours traced by [`Builder`](crate::builder::Builder) with the non-zero rule.
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    /// The signed area contributions, with two extra columns per row that absorb
+    /// the contributions of edges touching the right border.
+    accumulator: Vec<f32>,
+    /// The number of pixels per unit of the received drawing operations.
+    scale: f64,
+    start: Point,
+    current: Point,
+}
+
+impl Rasterizer {
+    /// Creates a rasterizer for an image with the given dimensions, in pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_scale(width, height, 1.0)
+    }
+
+    /// Creates a rasterizer for an image with the given dimensions, in pixels,
+    /// that scales the received drawing operations by `scale` pixels per unit.
+    pub fn with_scale(width: usize, height: usize, scale: f64) -> Self {
+        Self {
+            width,
+            height,
+            accumulator: vec![0.0; (width + 2) * height],
+            scale,
+            start: Point::default(),
+            current: Point::default(),
+        }
+    }
+
+    /// Adds a straight edge between the two points.
+    pub fn line(&mut self, from: Point, to: Point) {
+        let clamp = |p: Point| Point::new(p.x.clamp(0.0, self.width as f64), p.y);
+        let (from, to) = (clamp(from), clamp(to));
+        if (from.y - to.y).abs() <= f64::EPSILON {
+            return;
+        }
+        let (direction, p0, p1) = if from.y < to.y {
+            (1.0, from, to)
+        } else {
+            (-1.0, to, from)
+        };
+        let stride = self.width + 2;
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let y_start = p0.y.max(0.0);
+        let y_end = p1.y.min(self.height as f64);
+        let mut x = p0.x + (y_start - p0.y) * dxdy;
+        let mut y = y_start.floor() as usize;
+        while (y as f64) < y_end {
+            let dy = ((y + 1) as f64).min(p1.y) - (y as f64).max(p0.y);
+            let x_next = x + dxdy * dy;
+            let d = (dy * direction) as f32;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let row = y * stride;
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+            if x1i <= x0i + 1 {
+                let xmf = (0.5 * (x + x_next) - x0_floor) as f32;
+                self.accumulator[row + x0i] += d - d * xmf;
+                self.accumulator[row + x0i + 1] += d * xmf;
+            } else {
+                let s = (1.0 / (x1 - x0)) as f32;
+                let x0f = (x0 - x0_floor) as f32;
+                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+                let x1f = (x1 - x1_ceil + 1.0) as f32;
+                let am = 0.5 * s * x1f * x1f;
+                self.accumulator[row + x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    self.accumulator[row + x0i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    self.accumulator[row + x0i + 1] += d * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.accumulator[row + xi] += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.accumulator[row + x1i - 1] += d * (1.0 - a2 - am);
+                }
+                self.accumulator[row + x1i] += d * am;
+            }
+            x = x_next;
+            y += 1;
+        }
+    }
+
+    /// Adds the edges of a curve in pixel coordinates that starts at the current point.
+    fn curve_to(&mut self, curve: CubicTo) {
+        let mut current = self.current;
+        for point in curve.flatten(current, ARC_TOLERANCE) {
+            self.line(current, point);
+            current = point;
+        }
+        self.current = current;
+    }
+
+    /// Returns the coverage mask of the edges added so far.
+    pub fn into_mask(mut self) -> Mask {
+        // Close the last contour in case it was left open.
+        self.line(self.current, self.start);
+
+        let stride = self.width + 2;
+        let mut data = Vec::with_capacity(self.width * self.height);
+        for row in self.accumulator.chunks_exact(stride) {
+            let mut sum = 0_f32;
+            for value in &row[..self.width] {
+                sum += value;
+                data.push((sum.abs().min(1.0) * 255.0).round() as u8);
+            }
+        }
+        Mask {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+impl DisplayListOpReceiver for Rasterizer {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.line(self.current, self.start);
+        self.start = op.point * self.scale;
+        self.current = self.start;
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        let point = op.point * self.scale;
+        self.line(self.current, point);
+        self.current = point;
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        let mut current = op.start_point() * self.scale;
+        self.line(self.current, current);
+        for point in op.flatten(ARC_TOLERANCE / self.scale) {
+            let point = point * self.scale;
+            self.line(current, point);
+            current = point;
+        }
+        self.current = current;
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.curve_to(CubicTo {
+            control1: op.control1 * self.scale,
+            control2: op.control2 * self.scale,
+            point: op.point * self.scale,
+        });
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        let curve = QuadTo {
+            control: op.control * self.scale,
+            point: op.point * self.scale,
+        };
+        self.curve_to(curve.to_cubic(self.current));
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.line(self.current, self.start);
+        self.current = self.start;
+    }
+}
+
+/// Rasterizes the display list to a coverage mask of the given dimensions, in pixels.
+pub fn render_mask(display_list: &DisplayList, width: usize, height: usize) -> Mask {
+    let mut rasterizer = Rasterizer::new(width, height);
+    display_list.present(&mut rasterizer);
+    rasterizer.into_mask()
+}
+
+/// Rasterizes the display list at `scale` pixels per unit to a bitmap whose dimensions
+/// are the scaled size rounded up.
+pub fn render_bitmap(display_list: &DisplayList, size: Size, scale: f64) -> Bitmap {
+    let width = (size.width * scale).ceil() as usize;
+    let height = (size.height * scale).ceil() as usize;
+    let mut rasterizer = Rasterizer::with_scale(width, height, scale);
+    display_list.present(&mut rasterizer);
+    rasterizer.into_mask().to_bitmap()
+}
+
+/// Renders the display list to an image whose dimensions are the given size rounded up, in pixels.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style) -> Pixmap {
+    let (width, height) = (size.width.ceil() as usize, size.height.ceil() as usize);
+    let bounds = Rect {
+        origin: Point::default(),
+        size,
+    };
+    let mut pixmap = Pixmap::new(width, height, Color::TRANSPARENT);
+    if let Some(background) = &style.background {
+        let shape = DisplayList::rounded_rect(bounds, background.corner_radius);
+        let mask = render_mask(&shape, width, height);
+        pixmap.fill_mask_with_paint(&mask, &background.paint, bounds);
+    }
+    for (display_list, paint) in style.fills(display_list) {
+        let mask = render_mask(&display_list, width, height);
+        pixmap.fill_mask_with_paint(&mask, paint, bounds);
+    }
+    pixmap
+}
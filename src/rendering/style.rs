@@ -0,0 +1,483 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::math;
+
+use super::display_list::{DisplayList, Layer};
+use super::geometry::{Point, Rect};
+
+/// A color in the sRGB color space with 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    /// Creates an opaque color with the given components.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Creates a color with the given components.
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Returns the alpha component as a value between 0 and 1.
+    pub fn opacity(&self) -> f64 {
+        self.a as f64 / 255.0
+    }
+
+    /// Returns the relative luminance of the color as defined by WCAG,
+    /// from 0 for black to 1 for white, ignoring its alpha component.
+    pub fn relative_luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = channel as f64 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                math::powf((c + 0.055) / 1.055, 2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Returns the WCAG contrast ratio between the colors, from 1 to 21.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Returns the color that results from drawing this color over an opaque one.
+    pub fn over(&self, backdrop: Color) -> Color {
+        let alpha = self.opacity();
+        let mix = |a: u8, b: u8| math::round(a as f64 * alpha + b as f64 * (1.0 - alpha)) as u8;
+        Color::rgb(
+            mix(self.r, backdrop.r),
+            mix(self.g, backdrop.g),
+            mix(self.b, backdrop.b),
+        )
+    }
+
+    /// Returns the color in the `#rrggbb` notation, ignoring its alpha component.
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Returns the color in a CSS notation, `#rrggbb` if opaque and `rgba()` otherwise.
+    pub fn to_css(&self) -> String {
+        if self.a == 255 {
+            self.to_hex()
+        } else {
+            format!(
+                "rgba({}, {}, {}, {})",
+                self.r,
+                self.g,
+                self.b,
+                super::format_number(self.opacity())
+            )
+        }
+    }
+}
+
+/// The error type returned when a string cannot be parsed as a [`Color`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected a color in the #rgb, #rrggbb or #rrggbbaa notation")
+    }
+}
+
+impl Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a color in the `#rgb`, `#rrggbb` or `#rrggbbaa` notation, with an optional `#`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if !hex.is_ascii() {
+            return Err(ParseColorError);
+        }
+        let channel = |range: core::ops::Range<usize>| {
+            u8::from_str_radix(&hex[range], 16).map_err(|_| ParseColorError)
+        };
+        match hex.len() {
+            3 => Ok(Self::rgb(
+                channel(0..1)? * 0x11,
+                channel(1..2)? * 0x11,
+                channel(2..3)? * 0x11,
+            )),
+            6 => Ok(Self::rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+            8 => Ok(Self::rgba(
+                channel(0..2)?,
+                channel(2..4)?,
+                channel(4..6)?,
+                channel(6..8)?,
+            )),
+            _ => Err(ParseColorError),
+        }
+    }
+}
+
+/// A color at a position along a gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// The position of the stop, from 0 at the start of the gradient to 1 at its end.
+    pub offset: f64,
+    pub color: Color,
+}
+
+impl GradientStop {
+    pub fn new(offset: f64, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+/// A gradient that varies the color along the line from `start` to `end`.
+///
+/// Positions are fractions of the width and height of the image, so the
+/// gradient spans the whole QR Code whatever its size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearGradient {
+    pub start: Point,
+    pub end: Point,
+    pub stops: Vec<GradientStop>,
+}
+
+impl LinearGradient {
+    pub fn new(start: Point, end: Point, stops: Vec<GradientStop>) -> Self {
+        Self { start, end, stops }
+    }
+}
+
+/// A gradient that varies the color from `center` outwards to a circle of `radius`.
+///
+/// The center is in fractions of the width and height of the image and the
+/// radius in fractions of its shorter side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadialGradient {
+    pub center: Point,
+    pub radius: f64,
+    pub stops: Vec<GradientStop>,
+}
+
+impl RadialGradient {
+    pub fn new(center: Point, radius: f64, stops: Vec<GradientStop>) -> Self {
+        Self {
+            center,
+            radius,
+            stops,
+        }
+    }
+}
+
+/// Describes how a shape is filled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Self::Solid(color)
+    }
+}
+
+impl Paint {
+    /// Returns the stops of the gradient, sorted by offset, or a single stop for a solid color.
+    pub fn stops(&self) -> Vec<GradientStop> {
+        let mut stops = match self {
+            Paint::Solid(color) => vec![GradientStop::new(0.0, *color)],
+            Paint::LinearGradient(gradient) => gradient.stops.clone(),
+            Paint::RadialGradient(gradient) => gradient.stops.clone(),
+        };
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        stops
+    }
+
+    /// Returns the color of the paint at the given point of an image with the given bounds.
+    pub fn color_at(&self, point: Point, bounds: Rect) -> Color {
+        let Rect { origin, size } = bounds;
+        let offset = match self {
+            Paint::Solid(color) => return *color,
+            Paint::LinearGradient(gradient) => {
+                let start = Point::new(
+                    origin.x + gradient.start.x * size.width,
+                    origin.y + gradient.start.y * size.height,
+                );
+                let (dx, dy) = (
+                    (gradient.end.x - gradient.start.x) * size.width,
+                    (gradient.end.y - gradient.start.y) * size.height,
+                );
+                let length = dx * dx + dy * dy;
+                if length > 0.0 {
+                    ((point.x - start.x) * dx + (point.y - start.y) * dy) / length
+                } else {
+                    0.0
+                }
+            }
+            Paint::RadialGradient(gradient) => {
+                let center = Point::new(
+                    origin.x + gradient.center.x * size.width,
+                    origin.y + gradient.center.y * size.height,
+                );
+                let radius = gradient.radius * size.width.min(size.height);
+                let distance = math::hypot(point.x - center.x, point.y - center.y);
+                if radius > 0.0 {
+                    distance / radius
+                } else {
+                    1.0
+                }
+            }
+        };
+        self.color_at_offset(offset)
+    }
+
+    /// Returns the color at the given offset along the gradient, interpolated between its stops.
+    pub fn color_at_offset(&self, offset: f64) -> Color {
+        let stops = self.stops();
+        let Some(first) = stops.first() else {
+            return Color::TRANSPARENT;
+        };
+        let Some(end) = stops.iter().position(|stop| stop.offset > offset) else {
+            return stops[stops.len() - 1].color;
+        };
+        if end == 0 {
+            return first.color;
+        }
+        let (a, b) = (stops[end - 1], stops[end]);
+        let t = (offset - a.offset) / (b.offset - a.offset);
+        let mix = |a: u8, b: u8| math::round(a as f64 + (b as f64 - a as f64) * t) as u8;
+        Color::rgba(
+            mix(a.color.r, b.color.r),
+            mix(a.color.g, b.color.g),
+            mix(a.color.b, b.color.b),
+            mix(a.color.a, b.color.a),
+        )
+    }
+
+    /// Returns a solid color that approximates the paint, for backends that cannot draw gradients.
+    pub fn approximate_color(&self) -> Color {
+        match self {
+            Paint::Solid(color) => *color,
+            _ => self.color_at_offset(0.5),
+        }
+    }
+}
+
+/// The fill behind the QR Code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Background {
+    pub paint: Paint,
+    /// The radius of the corners of the background, in the units of the image.
+    pub corner_radius: f64,
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Self {
+            paint: color.into(),
+            corner_radius: 0.0,
+        }
+    }
+}
+
+/// Describes how the built-in backends paint a display list.
+///
+/// The SVG and raster backends honor every part of the style, and the PDF backend
+/// every part but the alpha components of the colors, which it ignores. The other
+/// backends draw gradients with their [approximate colors](Paint::approximate_color)
+/// and backgrounds without rounded corners.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// The paint that fills the modules of the QR Code.
+    pub foreground: Paint,
+    /// The paint that fills the finder patterns, or `None` to use the foreground.
+    pub finder: Option<Paint>,
+    /// The fill behind the QR Code, or `None` to leave it transparent.
+    pub background: Option<Background>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            foreground: Color::BLACK.into(),
+            finder: None,
+            background: Some(Color::WHITE.into()),
+        }
+    }
+}
+
+/// The lowest contrast ratio between the modules and the background that scanners
+/// read reliably.
+pub const MIN_CONTRAST_RATIO: f64 = 3.0;
+
+/// A problem with the colors of a [`Style`] that may prevent scanning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContrastWarning {
+    /// The contrast ratio between the modules and the background is below [`MIN_CONTRAST_RATIO`].
+    LowContrast { ratio: f64 },
+    /// The modules are lighter than the background, which many scanners cannot read.
+    Inverted,
+}
+
+impl Display for ContrastWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContrastWarning::LowContrast { ratio } => write!(
+                f,
+                "the contrast ratio between the modules and the background is {ratio:.2}:1, below the recommended {MIN_CONTRAST_RATIO}:1"
+            ),
+            ContrastWarning::Inverted => {
+                f.write_str("the modules are lighter than the background, which many scanners cannot read")
+            }
+        }
+    }
+}
+
+impl Style {
+    /// Splits the display list into the parts that are filled with the same paint,
+    /// in the order they are drawn.
+    pub fn fills(&self, display_list: &DisplayList) -> Vec<(DisplayList, &Paint)> {
+        match &self.finder {
+            None => vec![(display_list.clone(), &self.foreground)],
+            Some(finder) => vec![
+                (display_list.layer(Layer::Data), &self.foreground),
+                (display_list.layer(Layer::Finder), finder),
+            ],
+        }
+    }
+
+    /// Returns the lowest contrast ratio between any color of the modules and
+    /// any color of the background.
+    ///
+    /// Translucent colors are composited over the background, and a transparent
+    /// background is assumed to be printed on white.
+    pub fn contrast_ratio(&self) -> f64 {
+        self.color_pairs()
+            .into_iter()
+            .map(|(module, background)| module.contrast_ratio(&background))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Checks that the modules stand out from the background enough to be scanned.
+    pub fn check_contrast(&self) -> Option<ContrastWarning> {
+        let ratio = self.contrast_ratio();
+        if ratio < MIN_CONTRAST_RATIO {
+            return Some(ContrastWarning::LowContrast { ratio });
+        }
+        let inverted = self.color_pairs().into_iter().any(|(module, background)| {
+            module.relative_luminance() > background.relative_luminance()
+        });
+        inverted.then_some(ContrastWarning::Inverted)
+    }
+
+    /// Returns every pair of opaque module and background colors.
+    fn color_pairs(&self) -> Vec<(Color, Color)> {
+        let backgrounds = match &self.background {
+            Some(background) => background
+                .paint
+                .stops()
+                .iter()
+                .map(|stop| stop.color.over(Color::WHITE))
+                .collect(),
+            None => vec![Color::WHITE],
+        };
+        let modules = self
+            .foreground
+            .stops()
+            .into_iter()
+            .chain(self.finder.iter().flat_map(Paint::stops))
+            .map(|stop| stop.color)
+            .collect::<Vec<_>>();
+        backgrounds
+            .into_iter()
+            .flat_map(|background| {
+                modules
+                    .iter()
+                    .map(move |module| (module.over(background), background))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(foreground: impl Into<Paint>, background: Option<Color>) -> Style {
+        Style {
+            foreground: foreground.into(),
+            finder: None,
+            background: background.map(Background::from),
+        }
+    }
+
+    fn low_contrast_ratio(style: &Style) -> f64 {
+        match style.check_contrast() {
+            Some(ContrastWarning::LowContrast { ratio }) => ratio,
+            warning => panic!("expected low contrast, got {warning:?}"),
+        }
+    }
+
+    #[test]
+    fn black_on_white_has_the_highest_contrast() {
+        let style = Style::default();
+        assert!((style.contrast_ratio() - 21.0).abs() < 1e-9);
+        assert_eq!(style.check_contrast(), None);
+    }
+
+    #[test]
+    fn warns_below_the_minimum_contrast_ratio() {
+        // On white, grays from #959595 up are below 3:1, while #949494 still meets it.
+        let ratio = low_contrast_ratio(&style(Color::rgb(0x95, 0x95, 0x95), Some(Color::WHITE)));
+        assert!(ratio < MIN_CONTRAST_RATIO);
+        let darker = style(Color::rgb(0x94, 0x94, 0x94), Some(Color::WHITE));
+        assert!(darker.contrast_ratio() >= MIN_CONTRAST_RATIO);
+        assert_eq!(darker.check_contrast(), None);
+    }
+
+    #[test]
+    fn checks_the_lightest_stop_of_a_gradient() {
+        let gradient = LinearGradient::new(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            vec![
+                GradientStop::new(0.0, Color::BLACK),
+                GradientStop::new(1.0, Color::rgb(0xcc, 0xcc, 0xcc)),
+            ],
+        );
+        let style = style(Paint::LinearGradient(gradient), Some(Color::WHITE));
+        assert!(low_contrast_ratio(&style) < MIN_CONTRAST_RATIO);
+    }
+
+    #[test]
+    fn composites_translucent_modules_over_the_background() {
+        let style = style(Color::rgba(0, 0, 0, 0x33), None);
+        assert!(low_contrast_ratio(&style) < MIN_CONTRAST_RATIO);
+    }
+
+    #[test]
+    fn warns_about_modules_lighter_than_the_background() {
+        let style = style(Color::WHITE, Some(Color::BLACK));
+        assert_eq!(style.check_contrast(), Some(ContrastWarning::Inverted));
+    }
+}
@@ -0,0 +1,526 @@
+use std::fmt::Write;
+use std::io;
+
+use crate::sheet::SheetPage;
+
+use super::display_list::{
+    ArcTo, BeginLayer, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, Layer, LineTo,
+    MoveTo, QuadTo,
+};
+use super::format_number as num;
+use super::geometry::{Point, Rect, Size};
+use super::style::{Paint, Style};
+
+/// A receiver that converts drawing operations to SVG path data.
+#[derive(Debug, Clone, Default)]
+pub struct SvgPathBuilder {
+    data: String,
+}
+
+impl SvgPathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the path data, suitable for the `d` attribute of a `<path>` element.
+    pub fn finish(self) -> String {
+        self.data
+    }
+
+    fn command(&mut self, command: char) {
+        if !self.data.is_empty() {
+            self.data.push(' ');
+        }
+        self.data.push(command);
+    }
+}
+
+impl DisplayListOpReceiver for SvgPathBuilder {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.command('M');
+        _ = write!(self.data, "{} {}", num(op.point.x), num(op.point.y));
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.command('L');
+        _ = write!(self.data, "{} {}", num(op.point.x), num(op.point.y));
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        let sweep = op.sweep_angle();
+        let end = op.end_point();
+        self.command('A');
+        _ = write!(
+            self.data,
+            "{r} {r} 0 {} {} {} {}",
+            (sweep.abs() > std::f64::consts::PI) as u8,
+            op.clockwise as u8,
+            num(end.x),
+            num(end.y),
+            r = num(op.radius),
+        );
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.command('C');
+        _ = write!(
+            self.data,
+            "{} {} {} {} {} {}",
+            num(op.control1.x),
+            num(op.control1.y),
+            num(op.control2.x),
+            num(op.control2.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.command('Q');
+        _ = write!(
+            self.data,
+            "{} {} {} {}",
+            num(op.control.x),
+            num(op.control.y),
+            num(op.point.x),
+            num(op.point.y)
+        );
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.command('Z');
+    }
+}
+
+/// Returns the SVG path data of the display list.
+pub fn path_data(display_list: &DisplayList) -> String {
+    let mut builder = SvgPathBuilder::new();
+    display_list.present(&mut builder);
+    builder.finish()
+}
+
+/// Returns the attributes that fill a shape with the paint, whose gradients span the bounds,
+/// adding the gradient to `defs` under the given id if there is one.
+fn fill_attributes(paint: &Paint, id: &str, bounds: Rect, defs: &mut String) -> String {
+    let Rect { origin, size } = bounds;
+    let stops = |defs: &mut String| {
+        for stop in paint.stops() {
+            _ = write!(
+                defs,
+                "<stop offset=\"{}\" stop-color=\"{}\"",
+                num(stop.offset),
+                stop.color.to_hex()
+            );
+            if stop.color.a != 255 {
+                _ = write!(defs, " stop-opacity=\"{}\"", num(stop.color.opacity()));
+            }
+            defs.push_str("/>\n");
+        }
+    };
+    match paint {
+        Paint::Solid(color) if color.a == 255 => format!("fill=\"{}\"", color.to_hex()),
+        Paint::Solid(color) => format!(
+            "fill=\"{}\" fill-opacity=\"{}\"",
+            color.to_hex(),
+            num(color.opacity())
+        ),
+        Paint::LinearGradient(gradient) => {
+            _ = writeln!(
+                defs,
+                "<linearGradient id=\"{id}\" gradientUnits=\"userSpaceOnUse\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\">",
+                num(origin.x + gradient.start.x * size.width),
+                num(origin.y + gradient.start.y * size.height),
+                num(origin.x + gradient.end.x * size.width),
+                num(origin.y + gradient.end.y * size.height)
+            );
+            stops(defs);
+            defs.push_str("</linearGradient>\n");
+            format!("fill=\"url(#{id})\"")
+        }
+        Paint::RadialGradient(gradient) => {
+            _ = writeln!(
+                defs,
+                "<radialGradient id=\"{id}\" gradientUnits=\"userSpaceOnUse\" cx=\"{}\" cy=\"{}\" r=\"{}\">",
+                num(origin.x + gradient.center.x * size.width),
+                num(origin.y + gradient.center.y * size.height),
+                num(gradient.radius * size.width.min(size.height))
+            );
+            stops(defs);
+            defs.push_str("</radialGradient>\n");
+            format!("fill=\"url(#{id})\"")
+        }
+    }
+}
+
+/// Returns the elements that paint the display list with the style, whose background
+/// fills the bounds and whose gradients span them, adding the gradients to `defs`
+/// with ids that start with `id`.
+fn paint(
+    display_list: &DisplayList,
+    bounds: Rect,
+    style: &Style,
+    id: &str,
+    defs: &mut String,
+) -> String {
+    let mut body = background(style, bounds, id, defs);
+    for (i, (display_list, paint)) in style.fills(display_list).iter().enumerate() {
+        if display_list.is_empty() {
+            continue;
+        }
+        let fill = fill_attributes(paint, &format!("{id}-fill-{i}"), bounds, defs);
+        _ = writeln!(body, "<path d=\"{}\" {fill}/>", path_data(display_list));
+    }
+    body
+}
+
+/// Renders the display list to a standalone SVG document of the given size.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style) -> String {
+    let mut defs = String::new();
+    let bounds = Rect {
+        origin: Point::default(),
+        size,
+    };
+    let body = paint(display_list, bounds, style, "rqr", &mut defs);
+    document(size, "", &defs, &body)
+}
+
+/// Renders the frames of an animation to a standalone SVG document of the given size,
+/// which shows them one after the other over `duration` seconds with SMIL animations
+/// and keeps showing the last one.
+pub fn render_animated(frames: &[DisplayList], size: Size, style: &Style, duration: f64) -> String {
+    let mut defs = String::new();
+    let bounds = Rect {
+        origin: Point::default(),
+        size,
+    };
+    let mut body = background(style, bounds, "rqr", &mut defs);
+    let mut values = vec![];
+    for frame in frames {
+        for (i, (display_list, _)) in style.fills(frame).iter().enumerate() {
+            if values.len() <= i {
+                values.push(vec![]);
+            }
+            values[i].push(path_data(display_list));
+        }
+    }
+    let paints = style.fills(&DisplayList::new());
+    for (i, (values, (_, paint))) in values.iter().zip(paints).enumerate() {
+        let fill = fill_attributes(paint, &format!("rqr-fill-{i}"), bounds, &mut defs);
+        // Frames have different sets of subpaths, so they are shown as they are, without tweening.
+        _ = writeln!(
+            body,
+            "<path d=\"{}\" {fill}>\n<animate attributeName=\"d\" values=\"{}\" dur=\"{}s\" calcMode=\"discrete\" fill=\"freeze\"/>\n</path>",
+            values[0],
+            values.join(";"),
+            num(duration)
+        );
+    }
+    document(size, "", &defs, &body)
+}
+
+/// Renders the pages of a sheet to a standalone SVG document, in points, in which the pages
+/// are stacked from top to bottom with a gap between them.
+///
+/// Every code is painted with the style over its own bounds. The pages are also listed
+/// as Inkscape pages, so that Inkscape exports them as separate pages.
+pub fn render_sheet(pages: &[SheetPage], style: &Style) -> String {
+    const PAGE_GAP: f64 = 36.0;
+    let mut defs = String::new();
+    let mut body = String::new();
+    let mut namedview = String::new();
+    let mut size = Size::default();
+    for (i, page) in pages.iter().enumerate() {
+        if i > 0 {
+            size.height += PAGE_GAP;
+        }
+        _ = writeln!(
+            namedview,
+            "<inkscape:page x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+            num(size.height),
+            num(page.size.width),
+            num(page.size.height)
+        );
+        _ = writeln!(
+            body,
+            "<g id=\"page-{}\" transform=\"translate(0 {})\">",
+            i + 1,
+            num(size.height)
+        );
+        for (j, code) in page.codes.iter().enumerate() {
+            let id = format!("rqr-{}-{}", i + 1, j + 1);
+            body.push_str(&paint(
+                &code.display_list,
+                code.bounds,
+                style,
+                &id,
+                &mut defs,
+            ));
+        }
+        if !page.crop_marks.is_empty() {
+            _ = writeln!(body, "<path d=\"{}\"/>", path_data(&page.crop_marks));
+        }
+        body.push_str("</g>\n");
+        size.width = size.width.max(page.size.width);
+        size.height += page.size.height;
+    }
+    if !namedview.is_empty() {
+        body.insert_str(
+            0,
+            &format!(
+                "<sodipodi:namedview xmlns:sodipodi=\"http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd\" xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\">\n{namedview}</sodipodi:namedview>\n"
+            ),
+        );
+    }
+    document(size, "pt", &defs, &body)
+}
+
+/// A receiver that streams a standalone SVG document to a writer as it receives
+/// drawing operations, without collecting them first.
+///
+/// The operations of every [layer](Layer) are written to a `<path>` element filled with
+/// the paint of the style for that layer, and a new element starts whenever the paint changes.
+/// Writing stops at the first error, which [`SvgWriter::finish`] returns.
+///
+/// The document of a code streamed from a builder is the same as the one [`render`]
+/// returns for its display list.
+#[derive(Debug)]
+pub struct SvgWriter<W: io::Write> {
+    writer: W,
+    /// The fill attributes of the data layer and, if it is painted differently,
+    /// of the finder layer.
+    fills: Vec<String>,
+    /// The index of the fill of the current layer.
+    fill: usize,
+    /// The index of the fill of the `<path>` element being written, if one is open.
+    open: Option<usize>,
+    /// The path data of the operation being written.
+    path: SvgPathBuilder,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> SvgWriter<W> {
+    /// Writes the start of a document of the given size and its background to the writer.
+    pub fn new(mut writer: W, size: Size, style: &Style) -> io::Result<Self> {
+        let bounds = Rect {
+            origin: Point::default(),
+            size,
+        };
+        let mut defs = String::new();
+        let background = background(style, bounds, "rqr", &mut defs);
+        let foreground = fill_attributes(&style.foreground, "rqr-fill-0", bounds, &mut defs);
+        let mut fills = vec![foreground];
+        if let Some(finder) = &style.finder {
+            fills.push(fill_attributes(finder, "rqr-fill-1", bounds, &mut defs));
+        }
+        writer.write_all(header(size, "", &defs).as_bytes())?;
+        writer.write_all(background.as_bytes())?;
+        Ok(Self {
+            writer,
+            fills,
+            fill: 0,
+            open: None,
+            path: SvgPathBuilder::new(),
+            error: None,
+        })
+    }
+
+    /// Writes the end of the document and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if let Some(open) = self.open {
+            writeln!(self.writer, "\" {}/>", self.fills[open])?;
+        }
+        self.writer.write_all(b"</svg>\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Writes the path data of an operation, starting a `<path>` element
+    /// if the paint has changed.
+    fn write_op(&mut self, op: impl FnOnce(&mut SvgPathBuilder)) {
+        if self.error.is_some() {
+            return;
+        }
+        op(&mut self.path);
+        let mut write = || {
+            if self.open == Some(self.fill) {
+                self.writer.write_all(b" ")?;
+            } else {
+                if let Some(open) = self.open {
+                    writeln!(self.writer, "\" {}/>", self.fills[open])?;
+                }
+                self.writer.write_all(b"<path d=\"")?;
+                self.open = Some(self.fill);
+            }
+            self.writer.write_all(self.path.data.as_bytes())
+        };
+        if let Err(error) = write() {
+            self.error = Some(error);
+        }
+        self.path.data.clear();
+    }
+}
+
+impl<W: io::Write> DisplayListOpReceiver for SvgWriter<W> {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.write_op(|path| path.dispatch_move_to(op));
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.write_op(|path| path.dispatch_line_to(op));
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.write_op(|path| path.dispatch_arc_to(op));
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.write_op(|path| path.dispatch_cubic_to(op));
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.write_op(|path| path.dispatch_quad_to(op));
+    }
+
+    fn dispatch_close_path(&mut self, op: ClosePath) {
+        self.write_op(|path| path.dispatch_close_path(op));
+    }
+
+    fn dispatch_begin_layer(&mut self, op: BeginLayer) {
+        self.fill = match op.layer {
+            Layer::Data => 0,
+            Layer::Finder => self.fills.len() - 1,
+        };
+    }
+}
+
+/// Returns the element that draws the background of the style over the bounds,
+/// adding its gradient to `defs` with an id that starts with `id`.
+fn background(style: &Style, bounds: Rect, id: &str, defs: &mut String) -> String {
+    let Rect { origin, size } = bounds;
+    let mut body = String::new();
+    if let Some(background) = &style.background {
+        let fill = fill_attributes(&background.paint, &format!("{id}-background"), bounds, defs);
+        let mut attributes = String::new();
+        if origin != Point::default() {
+            _ = write!(
+                attributes,
+                "x=\"{}\" y=\"{}\" ",
+                num(origin.x),
+                num(origin.y)
+            );
+        }
+        _ = write!(
+            attributes,
+            "width=\"{}\" height=\"{}\"",
+            num(size.width),
+            num(size.height)
+        );
+        let radius = background
+            .corner_radius
+            .min(size.width.min(size.height) / 2.0)
+            .max(0.0);
+        if radius > 0.0 {
+            _ = write!(attributes, " rx=\"{}\"", num(radius));
+        }
+        _ = writeln!(body, "<rect {attributes} {fill}/>");
+    }
+    body
+}
+
+/// Wraps the definitions and the elements in an SVG document of the given size,
+/// whose width and height are given in `unit`, or in pixels if it is empty.
+fn document(size: Size, unit: &str, defs: &str, body: &str) -> String {
+    let mut svg = header(size, unit, defs);
+    svg.push_str(body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Returns the start of an SVG document of the given size, up to its definitions.
+fn header(size: Size, unit: &str, defs: &str) -> String {
+    let (width, height) = (num(size.width), num(size.height));
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}{unit}\" height=\"{height}{unit}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+    if !defs.is_empty() {
+        _ = write!(svg, "<defs>\n{defs}</defs>\n");
+    }
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::rendering::style::{Background, Color, GradientStop, LinearGradient};
+    use crate::types::ModuleShape;
+
+    /// Returns styles with a single paint, a finder paint, a gradient and
+    /// a rounded or missing background.
+    fn styles() -> Vec<Style> {
+        let gradient = LinearGradient::new(
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            vec![
+                GradientStop::new(0.0, Color::rgb(0x12, 0x34, 0x56)),
+                GradientStop::new(1.0, Color::rgb(0xab, 0xcd, 0xef)),
+            ],
+        );
+        vec![
+            Style::default(),
+            Style {
+                finder: Some(Color::rgb(0xe9, 0x45, 0x60).into()),
+                ..Style::default()
+            },
+            Style {
+                foreground: Paint::LinearGradient(gradient),
+                background: Some(Background {
+                    paint: Color::rgba(0xff, 0xee, 0xdd, 0x80).into(),
+                    corner_radius: 12.0,
+                }),
+                ..Style::default()
+            },
+            Style {
+                background: None,
+                ..Style::default()
+            },
+        ]
+    }
+
+    /// Returns builders of codes whose layers are drawn with connected modules, dots
+    /// and inverted.
+    fn builders() -> Vec<Builder<'static>> {
+        let builder = Builder::text("https://example.com").size(Size::new(200.0, 200.0));
+        vec![
+            builder.clone(),
+            builder.clone().module_shape(ModuleShape::Dots),
+            builder.invert(true),
+        ]
+    }
+
+    #[test]
+    fn streams_the_same_document_as_it_renders() {
+        let size = Size::new(200.0, 200.0);
+        for builder in builders() {
+            let display_list = builder.build().unwrap();
+            for style in styles() {
+                let mut writer = SvgWriter::new(vec![], size, &style).unwrap();
+                builder.build_with_receiver(&mut writer).unwrap();
+                let streamed = String::from_utf8(writer.finish().unwrap()).unwrap();
+                assert_eq!(streamed, render(&display_list, size, &style), "{style:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn streams_an_empty_document() {
+        let size = Size::new(10.0, 10.0);
+        let style = Style::default();
+        let writer = SvgWriter::new(vec![], size, &style).unwrap();
+        let streamed = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(streamed, render(&DisplayList::new(), size, &style));
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use crate::grid::ModuleGrid;
+
+use super::style::Color;
+
+/// The set of characters used to draw modules in a terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerminalCharset {
+    /// Unicode half blocks, drawing two rows of modules per line.
+    #[default]
+    HalfBlocks,
+    /// Unicode half circles and triangles, drawing every module with two columns
+    /// so that the convex corners of the shapes are cut off.
+    ///
+    /// The ends of shapes that are one module high are drawn as half circles, and
+    /// other convex corners are beveled, since Unicode has no filled quarter circles.
+    Beveled,
+    /// Plain ASCII, drawing every module with two columns.
+    Ascii,
+}
+
+/// Options that control how a module grid is drawn as text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalOptions {
+    pub charset: TerminalCharset,
+    /// The dark and light colors, applied with 24-bit ANSI escape sequences.
+    ///
+    /// When `None`, no escape sequences are emitted and glyphs are drawn with
+    /// the foreground color of the terminal.
+    pub colors: Option<(Color, Color)>,
+    /// Draws glyphs for the light modules instead of the dark ones, which keeps
+    /// the code scannable on terminals with a dark background when `colors` is `None`.
+    pub invert: bool,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        Self {
+            charset: TerminalCharset::default(),
+            colors: Some((Color::BLACK, Color::WHITE)),
+            invert: false,
+        }
+    }
+}
+
+/// Draws the module grid as lines of text, each terminated with a line break.
+pub fn render(grid: &ModuleGrid, options: &TerminalOptions) -> String {
+    let ink = |x: i32, y: i32| {
+        let inside = (0..grid.size()).contains(&x) && (0..grid.size()).contains(&y);
+        inside && grid.get(x, y) != options.invert
+    };
+
+    let mut out = String::new();
+    let rows_per_line = match options.charset {
+        TerminalCharset::HalfBlocks => 2,
+        TerminalCharset::Beveled | TerminalCharset::Ascii => 1,
+    };
+    for y in (0..grid.size()).step_by(rows_per_line) {
+        if let Some((dark, light)) = options.colors {
+            let (fg, bg) = if options.invert {
+                (light, dark)
+            } else {
+                (dark, light)
+            };
+            _ = write!(
+                out,
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                fg.r, fg.g, fg.b, bg.r, bg.g, bg.b
+            );
+        }
+        for x in 0..grid.size() {
+            match options.charset {
+                TerminalCharset::HalfBlocks => {
+                    out.push(match (ink(x, y), ink(x, y + 1)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    });
+                }
+                TerminalCharset::Beveled => {
+                    if !ink(x, y) {
+                        out.push_str("  ");
+                        continue;
+                    }
+                    let (top, bottom) = (ink(x, y - 1), ink(x, y + 1));
+                    // A corner is convex when neither of the modules sharing it is inked.
+                    let left = match (ink(x - 1, y) || top, ink(x - 1, y) || bottom) {
+                        (false, false) => '◖',
+                        (false, true) => '◢',
+                        (true, false) => '◥',
+                        (true, true) => '█',
+                    };
+                    let right = match (ink(x + 1, y) || top, ink(x + 1, y) || bottom) {
+                        (false, false) => '◗',
+                        (false, true) => '◣',
+                        (true, false) => '◤',
+                        (true, true) => '█',
+                    };
+                    out.push(left);
+                    out.push(right);
+                }
+                TerminalCharset::Ascii => {
+                    out.push_str(if ink(x, y) { "##" } else { "  " });
+                }
+            }
+        }
+        if options.colors.is_some() {
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a grid from rows of `#` for dark modules and `.` for light ones.
+    fn grid(rows: &[&str]) -> ModuleGrid {
+        let mut grid = ModuleGrid::new(rows.len() as i32);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, module) in row.chars().enumerate() {
+                grid.set(x as i32, y as i32, module == '#');
+            }
+        }
+        grid
+    }
+
+    fn plain(charset: TerminalCharset) -> TerminalOptions {
+        TerminalOptions {
+            charset,
+            colors: None,
+            invert: false,
+        }
+    }
+
+    #[test]
+    fn half_blocks_draw_two_rows_per_line() {
+        let grid = grid(&["#.#", ".##", "#.."]);
+        let text = render(&grid, &plain(TerminalCharset::HalfBlocks));
+        assert_eq!(text, "▀▄█\n▀  \n");
+    }
+
+    #[test]
+    fn ascii_draws_two_columns_per_module() {
+        let grid = grid(&["#.", ".#"]);
+        let text = render(&grid, &plain(TerminalCharset::Ascii));
+        assert_eq!(text, "##  \n  ##\n");
+    }
+
+    #[test]
+    fn beveled_draws_lone_modules_as_circles() {
+        let grid = grid(&["#..", "...", "..."]);
+        let text = render(&grid, &plain(TerminalCharset::Beveled));
+        assert_eq!(text, "◖◗    \n      \n      \n");
+    }
+
+    #[test]
+    fn beveled_cuts_only_convex_corners() {
+        let grid = grid(&["##", "#."]);
+        let text = render(&grid, &plain(TerminalCharset::Beveled));
+        assert_eq!(text, "◢██◗\n◥◤  \n");
+    }
+
+    #[test]
+    fn invert_draws_the_light_modules() {
+        let grid = grid(&["#.", ".#"]);
+        let options = TerminalOptions {
+            invert: true,
+            ..plain(TerminalCharset::Ascii)
+        };
+        assert_eq!(render(&grid, &options), "  ##\n##  \n");
+    }
+
+    #[test]
+    fn colors_wrap_every_line_in_escape_sequences() {
+        let grid = grid(&["#"]);
+        let options = TerminalOptions {
+            charset: TerminalCharset::Ascii,
+            ..TerminalOptions::default()
+        };
+        assert_eq!(
+            render(&grid, &options),
+            "\x1b[38;2;0;0;0m\x1b[48;2;255;255;255m##\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use super::geometry::Point;
+
+/// Returns twice the signed area of the triangle, positive if it turns counterclockwise
+/// in a y-up coordinate system.
+fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Returns the signed area of the polygon, positive if it runs counterclockwise
+/// in a y-up coordinate system.
+pub(crate) fn signed_area(points: &[Point], polygon: &[usize]) -> f64 {
+    let mut area = 0.0;
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        area += points[a].x * points[b].y - points[b].x * points[a].y;
+    }
+    area / 2.0
+}
+
+/// Returns `true` if the point is inside the polygon, using the even-odd rule.
+pub(crate) fn contains(points: &[Point], polygon: &[usize], point: Point) -> bool {
+    let mut inside = false;
+    for (i, &a) in polygon.iter().enumerate() {
+        let (a, b) = (points[a], points[polygon[(i + 1) % polygon.len()]]);
+        if (a.y <= point.y) != (b.y <= point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Returns `true` if the segments share any point, including their endpoints.
+fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let on_segment = |p: Point, q: Point, r: Point| {
+        r.x >= p.x.min(q.x) && r.x <= p.x.max(q.x) && r.y >= p.y.min(q.y) && r.y <= p.y.max(q.y)
+    };
+    let d1 = cross(a, b, c);
+    let d2 = cross(a, b, d);
+    let d3 = cross(c, d, a);
+    let d4 = cross(c, d, b);
+    (d1 * d2 < 0.0 && d3 * d4 < 0.0)
+        || (d1 == 0.0 && on_segment(a, b, c))
+        || (d2 == 0.0 && on_segment(a, b, d))
+        || (d3 == 0.0 && on_segment(c, d, a))
+        || (d4 == 0.0 && on_segment(c, d, b))
+}
+
+/// Returns `true` if the direction from `p` towards `m` points into the interior
+/// of a counterclockwise polygon whose corner at `p` is formed by `prev` and `next`.
+fn is_locally_inside(prev: Point, p: Point, next: Point, m: Point) -> bool {
+    if cross(prev, p, next) >= 0.0 {
+        cross(p, next, m) >= 0.0 && cross(p, m, prev) >= 0.0
+    } else {
+        cross(p, prev, m) < 0.0 || cross(p, m, next) < 0.0
+    }
+}
+
+/// Merges the hole into the polygon with a pair of coincident edges to the
+/// nearest vertex of the polygon that can be seen from the rightmost vertex of the hole.
+fn bridge_hole(points: &[Point], polygon: &mut Vec<usize>, hole: &[usize], others: &[&[usize]]) {
+    // A hole that touches the polygon at a shared vertex is joined there without a bridge,
+    // since a bridge to any other vertex could run along the edges next to it.
+    let len = polygon.len();
+    let touching = hole.iter().enumerate().find_map(|(k, &v)| {
+        let next = points[hole[(k + 1) % hole.len()]];
+        (0..len)
+            .find(|&i| {
+                polygon[i] == v
+                    && is_locally_inside(
+                        points[polygon[(i + len - 1) % len]],
+                        points[v],
+                        points[polygon[(i + 1) % len]],
+                        next,
+                    )
+            })
+            .map(|i| (i, k))
+    });
+    if let Some((i, k)) = touching {
+        let mut merged = Vec::with_capacity(polygon.len() + hole.len());
+        merged.extend_from_slice(&polygon[..i]);
+        merged.extend_from_slice(&hole[k..]);
+        merged.extend_from_slice(&hole[..k]);
+        merged.extend_from_slice(&polygon[i..]);
+        *polygon = merged;
+        return;
+    }
+
+    let m_index = (0..hole.len())
+        .max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x))
+        .unwrap_or(0);
+    let m = points[hole[m_index]];
+
+    let edges = |polygon: &[usize]| -> Vec<(Point, Point)> {
+        (0..polygon.len())
+            .map(|i| (points[polygon[i]], points[polygon[(i + 1) % polygon.len()]]))
+            .collect()
+    };
+    let mut obstacles = edges(polygon);
+    for other in others.iter().chain([&hole]) {
+        obstacles.extend(edges(other));
+    }
+
+    let mut candidates = (0..polygon.len()).collect::<Vec<_>>();
+    let distance = |i: usize| {
+        let p = points[polygon[i]];
+        (p.x - m.x).powi(2) + (p.y - m.y).powi(2)
+    };
+    candidates.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+    let visible = candidates.iter().copied().find(|&i| {
+        let len = polygon.len();
+        let p = points[polygon[i]];
+        let prev = points[polygon[(i + len - 1) % len]];
+        let next = points[polygon[(i + 1) % len]];
+        is_locally_inside(prev, p, next, m)
+            && !obstacles.iter().any(|&(a, b)| {
+                // Edges that end at the bridge touch it without blocking it.
+                ![a, b].iter().any(|&q| q == p || q == m) && segments_intersect(p, m, a, b)
+            })
+    });
+    let p_index = visible.unwrap_or(candidates[0]);
+
+    let mut merged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    merged.extend_from_slice(&polygon[..=p_index]);
+    merged.extend_from_slice(&hole[m_index..]);
+    merged.extend_from_slice(&hole[..=m_index]);
+    merged.extend_from_slice(&polygon[p_index..]);
+    *polygon = merged;
+}
+
+/// Returns `true` if the point is inside the counterclockwise triangle or on its edges.
+fn is_in_triangle(a: Point, b: Point, c: Point, p: Point) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+/// Splits a polygon with holes into triangles by ear clipping.
+///
+/// The outer boundary runs counterclockwise and the holes run clockwise, in a y-up
+/// coordinate system. The returned triangles are counterclockwise and index `points`.
+pub(crate) fn triangulate(
+    points: &[Point],
+    outer: &[usize],
+    holes: &[Vec<usize>],
+) -> Vec<[usize; 3]> {
+    let mut polygon = outer.to_vec();
+    let mut holes = holes.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    // Bridging holes from right to left keeps the bridges from crossing each other.
+    holes.sort_by(|a, b| {
+        let max_x = |hole: &[usize]| {
+            hole.iter()
+                .map(|&i| points[i].x)
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        max_x(b).total_cmp(&max_x(a))
+    });
+    for (i, hole) in holes.iter().enumerate() {
+        bridge_hole(points, &mut polygon, hole, &holes[i + 1..]);
+    }
+
+    let n = polygon.len();
+    let mut triangles = Vec::with_capacity(n.saturating_sub(2));
+    if n < 3 {
+        return triangles;
+    }
+    let mut prev = (0..n).map(|i| (i + n - 1) % n).collect::<Vec<_>>();
+    let mut next = (0..n).map(|i| (i + 1) % n).collect::<Vec<_>>();
+    untangle(points, &polygon, &mut prev, &mut next);
+    let point = |i: usize| points[polygon[i]];
+
+    let is_ear = |prev: &[usize], next: &[usize], b: usize| {
+        let a = prev[b];
+        let c = next[b];
+        let (pa, pb, pc) = (point(a), point(b), point(c));
+        if cross(pa, pb, pc) <= 0.0 {
+            return false;
+        }
+        // The diagonal must leave both ends into the polygon, which only fails at
+        // vertices that the polygon visits more than once.
+        if !is_locally_inside(point(prev[a]), pa, pb, pc)
+            || !is_locally_inside(pb, pc, point(next[c]), pa)
+        {
+            return false;
+        }
+        let sides = |p: Point| [cross(pa, pb, p), cross(pb, pc, p), cross(pc, pa, p)];
+        let mut v = next[c];
+        while v != a {
+            let pv = point(v);
+            // Rings that touch leave vertices on the corners, which only block the ear
+            // if one of their edges runs into it, inside both sides that meet there.
+            let blocks = if pv == pa || pv == pb || pv == pc {
+                let on = sides(pv);
+                [prev[v], next[v]].iter().any(|&w| {
+                    let towards = sides(point(w));
+                    (0..3).all(|i| on[i] != 0.0 || towards[i] > 0.0)
+                })
+            } else {
+                is_in_triangle(pa, pb, pc, pv)
+            };
+            if blocks {
+                return false;
+            }
+            v = next[v];
+        }
+        true
+    };
+
+    // Untangling may have split the polygon into several cycles, which are clipped in turn.
+    let mut done = vec![false; n];
+    for start in 0..n {
+        if done[start] {
+            continue;
+        }
+        let mut remaining = 1;
+        let mut v = next[start];
+        while v != start {
+            remaining += 1;
+            v = next[v];
+        }
+        let mut current = start;
+        let mut failures = 0;
+        while remaining > 3 {
+            let (a, c) = (prev[current], next[current]);
+            if polygon[a] == polygon[c] {
+                // A spike out to the vertex and back along the same edge encloses nothing,
+                // so it is dropped with the repeated vertex and its edges pair up
+                // without a triangle.
+                next[a] = next[c];
+                prev[next[c]] = a;
+                done[current] = true;
+                done[c] = true;
+                remaining -= 2;
+                failures = 0;
+                current = a;
+                continue;
+            }
+            // Clip the vertex anyway when no ear is left, which only happens
+            // with degenerate input, so that the loop always terminates.
+            if is_ear(&prev, &next, current) || failures > remaining {
+                triangles.push([polygon[a], polygon[current], polygon[c]]);
+                next[a] = c;
+                prev[c] = a;
+                done[current] = true;
+                remaining -= 1;
+                failures = 0;
+                current = c;
+            } else {
+                failures += 1;
+                current = next[current];
+            }
+        }
+        if remaining == 3 {
+            triangles.push([
+                polygon[prev[current]],
+                polygon[current],
+                polygon[next[current]],
+            ]);
+        }
+        let mut v = current;
+        for _ in 0..remaining {
+            done[v] = true;
+            v = next[v];
+        }
+    }
+    triangles
+}
+
+/// Relinks the visits of the polygon to the vertices that it passes more than once,
+/// which happens where holes touch the boundary or each other, so that each visit
+/// turns into the next edge counterclockwise around the vertex and keeps to its own
+/// wedge of the interior. The polygon may fall apart into several cycles, one for
+/// each region that the touching rings enclose.
+fn untangle(points: &[Point], polygon: &[usize], prev: &mut [usize], next: &mut [usize]) {
+    let mut visits = (0..polygon.len()).collect::<Vec<_>>();
+    visits.sort_by_key(|&i| polygon[i]);
+    for visits in visits.chunk_by(|&a, &b| polygon[a] == polygon[b]) {
+        if visits.len() < 2 {
+            continue;
+        }
+        let v = points[polygon[visits[0]]];
+        let angle = |i: usize| {
+            let p = points[polygon[i]];
+            (p.y - v.y).atan2(p.x - v.x)
+        };
+        // The edges around the vertex in counterclockwise order, where an edge that
+        // leaves along an arriving one comes after it.
+        let mut edges = visits
+            .iter()
+            .flat_map(|&i| [(angle(prev[i]), false, i), (angle(next[i]), true, i)])
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        // Each leaving edge is followed by the arriving edge that bounds its wedge.
+        let len = edges.len();
+        let pairs = (0..len)
+            .filter(|&k| edges[k].1)
+            .map(|k| {
+                let arriving = edges[(k + 1) % len];
+                (!arriving.1).then_some((arriving.2, next[edges[k].2]))
+            })
+            .collect::<Option<Vec<_>>>();
+        for (visit, to) in pairs.into_iter().flatten() {
+            next[visit] = to;
+            prev[to] = visit;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Checks that the triangles all turn counterclockwise and cover the area.
+    fn assert_covers(points: &[Point], triangles: &[[usize; 3]], area: f64) {
+        let mut sum = 0.0;
+        for &[a, b, c] in triangles {
+            let doubled = cross(points[a], points[b], points[c]);
+            assert!(doubled >= 0.0, "{:?} is clockwise", [a, b, c]);
+            sum += doubled / 2.0;
+        }
+        assert!((sum - area).abs() < 1e-9, "{sum} is not {area}");
+    }
+
+    #[test]
+    fn triangulates_holes_that_touch_at_a_corner() {
+        let points = [
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (4.0, 4.0),
+            (4.0, 5.0),
+            (5.0, 5.0),
+            (5.0, 4.0),
+            (5.0, 6.0),
+            (6.0, 6.0),
+            (6.0, 5.0),
+        ]
+        .map(|(x, y)| Point::new(x, y));
+        let holes = [vec![4, 5, 6, 7], vec![6, 8, 9, 10]];
+        let triangles = triangulate(&points, &[0, 1, 2, 3], &holes);
+        assert_covers(&points, &triangles, 98.0);
+    }
+
+    #[test]
+    fn triangulates_a_checkerboard_of_holes() {
+        let mut points = vec![
+            Point::new(-1.0, -1.0),
+            Point::new(6.0, -1.0),
+            Point::new(6.0, 6.0),
+            Point::new(-1.0, 6.0),
+        ];
+        let mut indices = HashMap::new();
+        let mut at = |x: i32, y: i32| {
+            *indices.entry((x, y)).or_insert_with(|| {
+                points.push(Point::new(f64::from(x), f64::from(y)));
+                points.len() - 1
+            })
+        };
+        // Holes on the dark squares, which touch each other at their corners.
+        let holes = (0..5)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .filter(|(x, y)| (x + y) % 2 == 0)
+            .map(|(x, y)| vec![at(x, y), at(x, y + 1), at(x + 1, y + 1), at(x + 1, y)])
+            .collect::<Vec<_>>();
+        let triangles = triangulate(&points, &[0, 1, 2, 3], &holes);
+        assert_covers(&points, &triangles, 49.0 - holes.len() as f64);
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt::Write;
+
+use super::display_list::DisplayList;
+use super::format_number as num;
+use super::geometry::Size;
+use super::style::{Color, Style};
+use super::svg;
+
+/// Returns the color in the `#AARRGGBB` notation used by Android resources.
+fn argb(color: Color) -> String {
+    format!(
+        "#{:02X}{:02X}{:02X}{:02X}",
+        color.a, color.r, color.g, color.b
+    )
+}
+
+/// Renders the display list to an Android VectorDrawable resource.
+///
+/// The viewport has the given size, which is also used as the intrinsic size in `dp`.
+/// Arcs are written as elliptical arc commands of the path data.
+pub fn render(display_list: &DisplayList, size: Size, style: &Style) -> String {
+    let (width, height) = (num(size.width), num(size.height));
+    let mut xml = format!(
+        "<vector xmlns:android=\"http://schemas.android.com/apk/res/android\"\n    \
+         android:width=\"{width}dp\"\n    \
+         android:height=\"{height}dp\"\n    \
+         android:viewportWidth=\"{width}\"\n    \
+         android:viewportHeight=\"{height}\">\n"
+    );
+    if let Some(background) = &style.background {
+        _ = writeln!(
+            xml,
+            "    <path\n        android:fillColor=\"{}\"\n        android:pathData=\"M0 0 H{width} V{height} H0 Z\" />",
+            argb(background.paint.approximate_color())
+        );
+    }
+    for (display_list, paint) in style.fills(display_list) {
+        _ = writeln!(
+            xml,
+            "    <path\n        android:fillColor=\"{}\"\n        android:pathData=\"{}\" />",
+            argb(paint.approximate_color()),
+            svg::path_data(&display_list)
+        );
+    }
+    xml.push_str("</vector>\n");
+    xml
+}
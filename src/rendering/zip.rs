@@ -0,0 +1,109 @@
+/// Computes the CRC-32 checksum used by ZIP archives.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// A file that has been written to the archive.
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writes a ZIP archive whose files are stored without compression.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ZipWriter {
+    buffer: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+/// The DOS date of 1980-01-01, the earliest that ZIP archives can record.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl ZipWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn add_file(&mut self, name: &str, data: &[u8]) {
+        let entry = Entry {
+            name: name.to_owned(),
+            crc: crc32(data),
+            size: data.len() as u32,
+            offset: self.buffer.len() as u32,
+        };
+
+        self.write_u32(0x0403_4b50);
+        self.write_u16(20);
+        self.write_u16(0);
+        self.write_u16(0);
+        self.write_u16(0);
+        self.write_u16(DOS_DATE);
+        self.write_u32(entry.crc);
+        self.write_u32(entry.size);
+        self.write_u32(entry.size);
+        self.write_u16(name.len() as u16);
+        self.write_u16(0);
+        self.buffer.extend_from_slice(name.as_bytes());
+        self.buffer.extend_from_slice(data);
+
+        self.entries.push(entry);
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.buffer.len() as u32;
+        let entries = std::mem::take(&mut self.entries);
+        for entry in &entries {
+            self.write_u32(0x0201_4b50);
+            self.write_u16(20);
+            self.write_u16(20);
+            self.write_u16(0);
+            self.write_u16(0);
+            self.write_u16(0);
+            self.write_u16(DOS_DATE);
+            self.write_u32(entry.crc);
+            self.write_u32(entry.size);
+            self.write_u32(entry.size);
+            self.write_u16(entry.name.len() as u16);
+            self.write_u16(0);
+            self.write_u16(0);
+            self.write_u16(0);
+            self.write_u16(0);
+            self.write_u32(0);
+            self.write_u32(entry.offset);
+            self.buffer.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = self.buffer.len() as u32 - directory_offset;
+
+        self.write_u32(0x0605_4b50);
+        self.write_u16(0);
+        self.write_u16(0);
+        self.write_u16(entries.len() as u16);
+        self.write_u16(entries.len() as u16);
+        self.write_u32(directory_size);
+        self.write_u32(directory_offset);
+        self.write_u16(0);
+        self.buffer
+    }
+}
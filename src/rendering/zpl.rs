@@ -0,0 +1,81 @@
+use std::fmt::Write;
+
+use super::display_list::DisplayList;
+use super::geometry::Size;
+use super::raster::{render_bitmap, Bitmap};
+
+/// Returns a `^GF` command that prints the bitmap, with its bytes written as hexadecimal.
+///
+/// The command is placed at the current field origin, so it is usually preceded
+/// by `^FO` and followed by `^FS`.
+pub fn graphic_field(bitmap: &Bitmap) -> String {
+    let total = bitmap.data.len();
+    let mut command = format!("^GFA,{total},{total},{},", bitmap.bytes_per_row);
+    for byte in &bitmap.data {
+        _ = write!(command, "{byte:02X}");
+    }
+    command
+}
+
+/// Renders the display list to a ZPL label for Zebra printers, with the code
+/// at the top-left corner of the label.
+///
+/// `size` is the size the display list was built with and `scale` the number
+/// of millimeters per unit. `dots_per_mm` is the resolution of the printer,
+/// which is 8 for 203 dpi, 12 for 300 dpi and 24 for 600 dpi printers.
+pub fn render(display_list: &DisplayList, size: Size, scale: f64, dots_per_mm: f64) -> String {
+    let bitmap = render_bitmap(display_list, size, scale * dots_per_mm);
+    format!("^XA\n^FO0,0{}^FS\n^XZ\n", graphic_field(&bitmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::geometry::{Point, Rect};
+    use crate::rendering::raster::Mask;
+
+    /// Returns the bitmap of rows drawn with `#` for covered pixels and `.` for empty ones.
+    fn draw(rows: &[&str]) -> Bitmap {
+        Mask {
+            width: rows[0].len(),
+            height: rows.len(),
+            data: rows
+                .iter()
+                .flat_map(|row| row.bytes().map(|c| if c == b'#' { 255 } else { 0 }))
+                .collect(),
+        }
+        .to_bitmap()
+    }
+
+    #[test]
+    fn writes_rows_of_padded_bytes_with_the_leftmost_pixel_first() {
+        let bitmap = draw(&[
+            "#........#", //
+            ".......##.",
+            "##########",
+        ]);
+        // 10 pixels take 2 bytes per row, with the last 6 bits of every row left clear.
+        assert_eq!(graphic_field(&bitmap), "^GFA,6,6,2,80400180FFC0");
+    }
+
+    #[test]
+    fn counts_whole_bytes() {
+        let bitmap = draw(&["########", "........"]);
+        assert_eq!(graphic_field(&bitmap), "^GFA,2,2,1,FF00");
+        let bitmap = draw(&["#", "#", "#"]);
+        assert_eq!(graphic_field(&bitmap), "^GFA,3,3,1,808080");
+    }
+
+    #[test]
+    fn renders_a_label() {
+        let rect = Rect {
+            origin: Point::default(),
+            size: Size::new(3.0, 2.0),
+        };
+        let display_list = DisplayList::rounded_rect(rect, 0.0);
+        assert_eq!(
+            render(&display_list, rect.size, 0.5, 2.0),
+            "^XA\n^FO0,0^GFA,2,2,1,E0E0^FS\n^XZ\n"
+        );
+    }
+}
@@ -0,0 +1,658 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::builder::{Builder, Result};
+use crate::rendering::display_list::DisplayList;
+use crate::rendering::geometry::{Point, Rect, Size};
+#[cfg(feature = "text")]
+use crate::text::Font;
+
+/// Converts millimeters to points.
+const fn mm(mm: f64) -> f64 {
+    mm / 25.4 * 72.0
+}
+
+/// Converts inches to points.
+const fn inches(inches: f64) -> f64 {
+    inches * 72.0
+}
+
+/// The size of an ISO A4 page, in points.
+pub const A4: Size = Size {
+    width: mm(210.0),
+    height: mm(297.0),
+};
+
+/// The size of a US Letter page, in points.
+pub const LETTER: Size = Size {
+    width: inches(8.5),
+    height: inches(11.0),
+};
+
+/// The distance between a crop mark and the corner it marks.
+const MARK_OFFSET: f64 = 3.0;
+/// The length of a crop mark.
+const MARK_LENGTH: f64 = 9.0;
+/// The thickness of a crop mark.
+const MARK_WIDTH: f64 = 0.5;
+
+/// The empty space around the edges of a page, in points.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    /// Creates margins of the same width on every edge.
+    pub fn uniform(margin: f64) -> Self {
+        Self {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+}
+
+/// The layout of a sheet of adhesive labels, in points.
+///
+/// The labels are laid out in a grid, row by row, starting at the top-left label.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelTemplate {
+    pub page_size: Size,
+    /// The position of the top-left corner of the top-left label.
+    pub origin: Point,
+    pub label_size: Size,
+    /// The distance between the top-left corners of neighboring labels.
+    pub pitch: Size,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl LabelTemplate {
+    /// Avery L7160: 21 labels of 63.5 × 38.1 mm on A4.
+    pub const AVERY_L7160: Self = Self {
+        page_size: A4,
+        origin: Point {
+            x: mm(7.21),
+            y: mm(15.15),
+        },
+        label_size: Size {
+            width: mm(63.5),
+            height: mm(38.1),
+        },
+        pitch: Size {
+            width: mm(66.04),
+            height: mm(38.1),
+        },
+        columns: 3,
+        rows: 7,
+    };
+
+    /// Avery L7163: 14 labels of 99.1 × 38.1 mm on A4.
+    pub const AVERY_L7163: Self = Self {
+        page_size: A4,
+        origin: Point {
+            x: mm(4.65),
+            y: mm(15.15),
+        },
+        label_size: Size {
+            width: mm(99.1),
+            height: mm(38.1),
+        },
+        pitch: Size {
+            width: mm(101.6),
+            height: mm(38.1),
+        },
+        columns: 2,
+        rows: 7,
+    };
+
+    /// Avery L7651: 65 labels of 38.1 × 21.2 mm on A4.
+    pub const AVERY_L7651: Self = Self {
+        page_size: A4,
+        origin: Point {
+            x: mm(4.75),
+            y: mm(10.7),
+        },
+        label_size: Size {
+            width: mm(38.1),
+            height: mm(21.2),
+        },
+        pitch: Size {
+            width: mm(40.6),
+            height: mm(21.2),
+        },
+        columns: 5,
+        rows: 13,
+    };
+
+    /// Avery 5160: 30 labels of 2⅝ × 1 in on US Letter.
+    pub const AVERY_5160: Self = Self {
+        page_size: LETTER,
+        origin: Point {
+            x: inches(0.1875),
+            y: inches(0.5),
+        },
+        label_size: Size {
+            width: inches(2.625),
+            height: inches(1.0),
+        },
+        pitch: Size {
+            width: inches(2.75),
+            height: inches(1.0),
+        },
+        columns: 3,
+        rows: 10,
+    };
+
+    /// Avery 5163: 10 labels of 4 × 2 in on US Letter.
+    pub const AVERY_5163: Self = Self {
+        page_size: LETTER,
+        origin: Point {
+            x: inches(0.15625),
+            y: inches(0.5),
+        },
+        label_size: Size {
+            width: inches(4.0),
+            height: inches(2.0),
+        },
+        pitch: Size {
+            width: inches(4.1875),
+            height: inches(2.0),
+        },
+        columns: 2,
+        rows: 5,
+    };
+
+    /// The built-in templates and the names they are parsed from.
+    pub const NAMED: [(&'static str, Self); 5] = [
+        ("L7160", Self::AVERY_L7160),
+        ("L7163", Self::AVERY_L7163),
+        ("L7651", Self::AVERY_L7651),
+        ("5160", Self::AVERY_5160),
+        ("5163", Self::AVERY_5163),
+    ];
+
+    /// Returns the labels of a sheet, row by row.
+    pub fn labels(&self) -> Vec<Rect> {
+        (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .map(|(column, row)| Rect {
+                origin: Point::new(
+                    self.origin.x + column as f64 * self.pitch.width,
+                    self.origin.y + row as f64 * self.pitch.height,
+                ),
+                size: self.label_size,
+            })
+            .collect()
+    }
+}
+
+/// The error type returned when a string does not name a built-in [`LabelTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLabelTemplateError;
+
+impl Display for ParseLabelTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = LabelTemplate::NAMED.map(|(name, _)| name);
+        write!(f, "expected one of {}", names.join(", "))
+    }
+}
+
+impl Error for ParseLabelTemplateError {}
+
+impl FromStr for LabelTemplate {
+    type Err = ParseLabelTemplateError;
+
+    /// Parses the product code of a built-in template, such as `L7160`, ignoring
+    /// case and an `Avery` prefix.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let name = match s.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("avery") => s[5..].trim_start(),
+            _ => s,
+        };
+        Self::NAMED
+            .iter()
+            .find(|(template, _)| template.eq_ignore_ascii_case(name))
+            .map(|(_, template)| *template)
+            .ok_or(ParseLabelTemplateError)
+    }
+}
+
+/// How the codes are arranged on the pages of a sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetLayout {
+    /// Divides the page inside its margins into equal cells, separated by gutters
+    /// of the given width, in points.
+    Grid {
+        columns: u32,
+        rows: u32,
+        gutter: f64,
+    },
+    /// Places a code on every label of a label sheet. The template sets the size and
+    /// the margins of the pages.
+    Labels(LabelTemplate),
+}
+
+/// A code placed on the page of a sheet.
+#[derive(Debug, Clone)]
+pub struct SheetCode {
+    /// The area covered by the image of the code, which is filled with the background of a style
+    /// and spanned by its gradients.
+    pub bounds: Rect,
+    /// The drawing operations of the code, in the coordinates of the page.
+    pub display_list: DisplayList,
+}
+
+/// A page of a sheet, in points.
+#[derive(Debug, Clone)]
+pub struct SheetPage {
+    pub size: Size,
+    pub codes: Vec<SheetCode>,
+    /// The marks that show where to cut the cells apart, which are drawn in black.
+    pub crop_marks: DisplayList,
+}
+
+/// Lays out many QR Codes on printable pages, such as sheets of stickers.
+///
+/// Every code is built with the settings of the same builder and centered in its cell,
+/// as large as the cell allows unless the builder sets a module size. Sheets are laid
+/// out in points, so the resolution of the builder is ignored.
+///
+/// The pages can be rendered with [`pdf::render_sheet`](crate::rendering::pdf::render_sheet)
+/// and [`svg::render_sheet`](crate::rendering::svg::render_sheet).
+#[derive(Debug, Clone)]
+pub struct Sheet<'a> {
+    builder: Builder<'a>,
+    page_size: Size,
+    margins: Margins,
+    layout: SheetLayout,
+    padding: f64,
+    crop_marks: bool,
+    #[cfg(feature = "text")]
+    captions: bool,
+    #[cfg(feature = "text")]
+    font: &'a Font,
+}
+
+impl<'a> Sheet<'a> {
+    /// Creates a sheet of A4 pages with half-inch margins, holding 4 × 5 codes
+    /// separated by quarter-inch gutters.
+    pub fn new(builder: Builder<'a>) -> Self {
+        Self {
+            builder,
+            page_size: A4,
+            margins: Margins::uniform(36.0),
+            layout: SheetLayout::Grid {
+                columns: 4,
+                rows: 5,
+                gutter: 18.0,
+            },
+            padding: 0.0,
+            crop_marks: false,
+            #[cfg(feature = "text")]
+            captions: false,
+            #[cfg(feature = "text")]
+            font: Font::embedded(),
+        }
+    }
+
+    /// Sets the size of the pages, in points, which is ignored by label templates.
+    pub fn page_size(self, page_size: Size) -> Self {
+        Self { page_size, ..self }
+    }
+
+    /// Sets the margins of the pages, which are ignored by label templates.
+    pub fn margins(self, margins: Margins) -> Self {
+        Self { margins, ..self }
+    }
+
+    pub fn layout(self, layout: SheetLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    /// Sets the space between the edges of every cell and its code, in points.
+    pub fn padding(self, padding: f64) -> Self {
+        Self { padding, ..self }
+    }
+
+    /// Sets whether marks are drawn around the corners of the cells to guide cutting them apart.
+    ///
+    /// Marks that would overlap a cell, such as between cells without gutters, are left out.
+    pub fn crop_marks(self, crop_marks: bool) -> Self {
+        Self { crop_marks, ..self }
+    }
+
+    /// Sets whether the text of every code is set below it as a caption.
+    #[cfg(feature = "text")]
+    pub fn captions(self, captions: bool) -> Self {
+        Self { captions, ..self }
+    }
+
+    /// Sets the font of the captions.
+    #[cfg(feature = "text")]
+    pub fn font(self, font: &'a Font) -> Self {
+        Self { font, ..self }
+    }
+
+    /// Returns the size of the pages, which is the one of the label template if there is one.
+    pub fn get_page_size(&self) -> Size {
+        match self.layout {
+            SheetLayout::Grid { .. } => self.page_size,
+            SheetLayout::Labels(template) => template.page_size,
+        }
+    }
+
+    pub fn get_margins(&self) -> Margins {
+        self.margins
+    }
+
+    pub fn get_layout(&self) -> SheetLayout {
+        self.layout
+    }
+
+    pub fn get_padding(&self) -> f64 {
+        self.padding
+    }
+
+    pub fn has_crop_marks(&self) -> bool {
+        self.crop_marks
+    }
+
+    #[cfg(feature = "text")]
+    pub fn has_captions(&self) -> bool {
+        self.captions
+    }
+
+    /// Returns the cells of a page, in the order they are filled.
+    pub fn cells(&self) -> Vec<Rect> {
+        match self.layout {
+            SheetLayout::Grid {
+                columns,
+                rows,
+                gutter,
+            } => {
+                let (columns, rows) = (columns.max(1), rows.max(1));
+                let Margins {
+                    top,
+                    right,
+                    bottom,
+                    left,
+                } = self.margins;
+                let width = self.page_size.width - left - right;
+                let height = self.page_size.height - top - bottom;
+                let size = Size::new(
+                    ((width - gutter * (columns - 1) as f64) / columns as f64).max(0.0),
+                    ((height - gutter * (rows - 1) as f64) / rows as f64).max(0.0),
+                );
+                LabelTemplate {
+                    page_size: self.page_size,
+                    origin: Point::new(left, top),
+                    label_size: size,
+                    pitch: Size::new(size.width + gutter, size.height + gutter),
+                    columns,
+                    rows,
+                }
+                .labels()
+            }
+            SheetLayout::Labels(template) => template.labels(),
+        }
+    }
+
+    /// Builds a code for every payload and lays them out on as many pages as they need.
+    pub fn pages(&self, payloads: &[&str]) -> Result<Vec<SheetPage>> {
+        let cells = self.cells();
+        if cells.is_empty() {
+            return Ok(vec![]);
+        }
+        payloads
+            .chunks(cells.len())
+            .map(|payloads| {
+                let codes = payloads
+                    .iter()
+                    .zip(&cells)
+                    .map(|(payload, cell)| self.code(payload, *cell))
+                    .collect::<Result<Vec<_>>>()?;
+                let crop_marks = if self.crop_marks {
+                    crop_marks(&cells[..codes.len()], &cells)
+                } else {
+                    DisplayList::new()
+                };
+                Ok(SheetPage {
+                    size: self.get_page_size(),
+                    codes,
+                    crop_marks,
+                })
+            })
+            .collect()
+    }
+
+    /// Builds the code of the payload, centered in the cell.
+    fn code(&self, payload: &str, cell: Rect) -> Result<SheetCode> {
+        let area = Size::new(
+            (cell.size.width - 2.0 * self.padding).max(0.0),
+            (cell.size.height - 2.0 * self.padding).max(0.0),
+        );
+        let builder = self.builder.clone().with_text(payload).dpi(72.0);
+        let (display_list, size) = self.draw(builder, payload, area)?;
+        let origin = Point::new(
+            cell.origin.x + (cell.size.width - size.width) / 2.0,
+            cell.origin.y + (cell.size.height - size.height) / 2.0,
+        );
+        Ok(SheetCode {
+            bounds: Rect { origin, size },
+            display_list: display_list.translated(origin),
+        })
+    }
+
+    /// Draws the code so that it fits in the area, and returns its drawing operations and size.
+    #[cfg(feature = "text")]
+    fn draw(&self, builder: Builder, payload: &str, area: Size) -> Result<(DisplayList, Size)> {
+        if !self.captions {
+            return draw_code(builder, area);
+        }
+        let caption = builder.caption(payload).font(self.font).fit(area);
+        let size = caption.get_size()?;
+        Ok((caption.build()?, size))
+    }
+
+    /// Draws the code so that it fits in the area, and returns its drawing operations and size.
+    #[cfg(not(feature = "text"))]
+    fn draw(&self, builder: Builder, _payload: &str, area: Size) -> Result<(DisplayList, Size)> {
+        draw_code(builder, area)
+    }
+}
+
+/// Draws the code as a square that fits in the area, and returns its drawing operations and size.
+fn draw_code(builder: Builder, area: Size) -> Result<(DisplayList, Size)> {
+    let side = area.width.min(area.height);
+    let builder = builder.size(Size::new(side, side));
+    let size = builder.placement()?.size;
+    Ok((builder.build()?, size))
+}
+
+/// Returns the crop marks of the corners of `cells`, leaving out the ones that overlap
+/// any of `occupied`.
+fn crop_marks(cells: &[Rect], occupied: &[Rect]) -> DisplayList {
+    let overlaps = |a: &Rect, b: &Rect| {
+        a.origin.x < b.origin.x + b.size.width
+            && b.origin.x < a.origin.x + a.size.width
+            && a.origin.y < b.origin.y + b.size.height
+            && b.origin.y < a.origin.y + a.size.height
+    };
+    let rect = |x: f64, y: f64, width: f64, height: f64| Rect {
+        origin: Point::new(x.min(x + width), y.min(y + height)),
+        size: Size::new(width.abs(), height.abs()),
+    };
+
+    let mut display_list = DisplayList::new();
+    for cell in cells {
+        let (left, top) = (cell.origin.x, cell.origin.y);
+        let (right, bottom) = (left + cell.size.width, top + cell.size.height);
+        // Every corner has a horizontal and a vertical mark, pointing away from the cell.
+        for (x, y, dx, dy) in [
+            (left, top, -1.0, -1.0),
+            (right, top, 1.0, -1.0),
+            (right, bottom, 1.0, 1.0),
+            (left, bottom, -1.0, 1.0),
+        ] {
+            let marks = [
+                rect(
+                    x + dx * MARK_OFFSET,
+                    y - MARK_WIDTH / 2.0,
+                    dx * MARK_LENGTH,
+                    MARK_WIDTH,
+                ),
+                rect(
+                    x - MARK_WIDTH / 2.0,
+                    y + dy * MARK_OFFSET,
+                    MARK_WIDTH,
+                    dy * MARK_LENGTH,
+                ),
+            ];
+            for mark in marks {
+                if !occupied.iter().any(|cell| overlaps(&mark, cell)) {
+                    display_list.extend(DisplayList::rounded_rect(mark, 0.0));
+                }
+            }
+        }
+    }
+    display_list
+}
+
+impl<'a> Builder<'a> {
+    /// Returns a sheet that lays out codes built with the settings of this builder,
+    /// whose own input is ignored.
+    pub fn sheet(self) -> Sheet<'a> {
+        Sheet::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn center(rect: Rect) -> Point {
+        Point::new(
+            rect.origin.x + rect.size.width / 2.0,
+            rect.origin.y + rect.size.height / 2.0,
+        )
+    }
+
+    fn grid(columns: u32, rows: u32) -> Sheet<'static> {
+        Builder::text("")
+            .sheet()
+            .page_size(Size::new(200.0, 100.0))
+            .layout(SheetLayout::Grid {
+                columns,
+                rows,
+                gutter: 5.0,
+            })
+    }
+
+    #[test]
+    fn divides_the_page_inside_the_margins_into_cells() {
+        let sheet = grid(2, 2).margins(Margins {
+            top: 10.0,
+            right: 20.0,
+            bottom: 30.0,
+            left: 40.0,
+        });
+        // The page is 140 by 60 points inside the margins, with a gutter of 5 points.
+        let size = Size::new(67.5, 27.5);
+        let cells = [(40.0, 10.0), (112.5, 10.0), (40.0, 42.5), (112.5, 42.5)].map(|(x, y)| Rect {
+            origin: Point::new(x, y),
+            size,
+        });
+        assert_eq!(sheet.cells(), cells);
+    }
+
+    #[test]
+    fn centers_codes_in_their_padded_cells() {
+        let sheet = grid(2, 2).margins(Margins::uniform(0.0)).padding(4.0);
+        let cells = sheet.cells();
+        let pages = sheet.pages(&["a", "b", "c", "d"]).unwrap();
+        assert_eq!(pages.len(), 1);
+        for (code, cell) in pages[0].codes.iter().zip(&cells) {
+            let (code_center, cell_center) = (center(code.bounds), center(*cell));
+            assert!((code_center.x - cell_center.x).abs() < 1e-9);
+            assert!((code_center.y - cell_center.y).abs() < 1e-9);
+            // The cells are 97.5 by 47.5 points, which leaves a code of 39.5 points.
+            assert!((code.bounds.size.width - 39.5).abs() < 1e-9);
+            assert_eq!(code.bounds.size.width, code.bounds.size.height);
+        }
+    }
+
+    #[test]
+    fn breaks_pages_when_the_cells_are_full() {
+        let payloads = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let sheet = grid(2, 2);
+        let pages = sheet.pages(&payloads).unwrap();
+        let counts = pages
+            .iter()
+            .map(|page| page.codes.len())
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [4, 4, 1]);
+        let cells = sheet.cells();
+        for page in &pages {
+            assert_eq!(page.size, Size::new(200.0, 100.0));
+            // Every page is filled from its first cell.
+            for (code, cell) in page.codes.iter().zip(&cells) {
+                let (code_center, cell_center) = (center(code.bounds), center(*cell));
+                assert!((code_center.x - cell_center.x).abs() < 1e-9);
+                assert!((code_center.y - cell_center.y).abs() < 1e-9);
+            }
+        }
+        assert!(sheet.pages(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn places_codes_on_labels() {
+        let sheet = Builder::text("")
+            .sheet()
+            .page_size(A4)
+            .margins(Margins::uniform(100.0))
+            .layout(SheetLayout::Labels(LabelTemplate::AVERY_5163));
+        // Label templates set the size and the margins of the page.
+        assert_eq!(sheet.get_page_size(), LETTER);
+        let cells = sheet.cells();
+        assert_eq!(cells.len(), 10);
+        // The fourth label is the second one of the second row.
+        assert_eq!(
+            cells[3],
+            Rect {
+                origin: Point::new(11.25 + 301.5, 36.0 + 144.0),
+                size: Size::new(288.0, 144.0),
+            }
+        );
+
+        let payloads = ["label"; 11];
+        let pages = sheet.pages(&payloads).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].size, LETTER);
+        let code = &pages[0].codes[3];
+        let (code_center, cell_center) = (center(code.bounds), center(cells[3]));
+        assert!((code_center.x - cell_center.x).abs() < 1e-9);
+        assert!((code_center.y - cell_center.y).abs() < 1e-9);
+        assert!((code.bounds.size.height - 144.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parses_label_templates() {
+        assert_eq!("L7160".parse(), Ok(LabelTemplate::AVERY_L7160));
+        assert_eq!("avery 5163".parse(), Ok(LabelTemplate::AVERY_5163));
+        assert_eq!("l7651".parse(), Ok(LabelTemplate::AVERY_L7651));
+        assert_eq!(
+            "L9999".parse::<LabelTemplate>(),
+            Err(ParseLabelTemplateError)
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::{Face, GlyphId, OutlineBuilder, Tag};
+
+use crate::rendering::display_list::{DisplayList, DisplayListRecorder};
+use crate::rendering::geometry::{Point, Rect};
+
+/// The font embedded in the library, a subset of DejaVu Sans Bold with the Latin-1 characters.
+static EMBEDDED: Font = Font {
+    data: Cow::Borrowed(include_bytes!("../fonts/DejaVuSans-Bold-Latin.ttf")),
+};
+
+/// The error type when font data cannot be parsed.
+#[derive(Debug)]
+pub struct InvalidFont;
+
+impl Display for InvalidFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the data is not a TrueType or OpenType font")
+    }
+}
+
+impl Error for InvalidFont {}
+
+/// A TrueType or OpenType font whose glyphs are drawn as outlines.
+#[derive(Clone)]
+pub struct Font {
+    data: Cow<'static, [u8]>,
+}
+
+impl Debug for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Font")
+            .field("len", &self.data.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Font {
+    /// Returns the font embedded in the library, a bold sans-serif typeface
+    /// that covers the Latin-1 characters.
+    pub fn embedded() -> &'static Font {
+        &EMBEDDED
+    }
+
+    /// Parses the contents of a `.ttf` or `.otf` file, or the first font of a collection.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, InvalidFont> {
+        Face::parse(&data, 0).map_err(|_| InvalidFont)?;
+        Ok(Self {
+            data: Cow::Owned(data),
+        })
+    }
+
+    fn face(&self) -> Face<'_> {
+        Face::parse(&self.data, 0).expect("font should have been validated")
+    }
+
+    /// Returns the height of capital letters above the baseline at the given font size.
+    pub fn cap_height(&self, font_size: f64) -> f64 {
+        let face = self.face();
+        let height = face.capital_height().unwrap_or(face.ascender());
+        height as f64 * font_size / face.units_per_em() as f64
+    }
+
+    /// Returns the width of the text set in a single line at the given font size.
+    pub fn text_width(&self, text: &str, font_size: f64) -> f64 {
+        let face = self.face();
+        let (_, width) = set_line(&face, text);
+        width * font_size / face.units_per_em() as f64
+    }
+
+    /// Returns the largest font size, up to `font_size`, at which the text set in a single line
+    /// is not wider than `max_width`.
+    pub fn fit_font_size(&self, text: &str, font_size: f64, max_width: f64) -> f64 {
+        let width = self.text_width(text, font_size);
+        if width > max_width && width > 0.0 {
+            font_size * max_width.max(0.0) / width
+        } else {
+            font_size
+        }
+    }
+
+    /// Returns the outlines of the text set in a single line at the given font size,
+    /// starting at `origin` on the baseline.
+    ///
+    /// The outer contours of the glyphs run clockwise and their counters counterclockwise,
+    /// like the ones of QR Codes.
+    pub fn text_outline(&self, text: &str, font_size: f64, origin: Point) -> DisplayList {
+        let face = self.face();
+        let scale = font_size / face.units_per_em() as f64;
+        let mut display_list = DisplayList::new();
+        let mut outliner = GlyphOutliner {
+            recorder: display_list.begin_recording(),
+            origin,
+            scale,
+            start: Point::default(),
+            current: Point::default(),
+        };
+        let (glyphs, _) = set_line(&face, text);
+        for (glyph, x) in glyphs {
+            outliner.origin.x = origin.x + x * scale;
+            face.outline_glyph(glyph, &mut outliner);
+        }
+
+        // The outer contours of PostScript outlines run counterclockwise.
+        if face.tables().cff.is_some() {
+            display_list = display_list.reversed();
+        }
+        display_list
+    }
+
+    /// Returns the outlines of the text set in a single line and centered in the rectangle,
+    /// with capital letters centered vertically.
+    ///
+    /// The font size is reduced when the text is wider than the rectangle at `font_size`.
+    pub fn centered_text_outline(&self, text: &str, font_size: f64, rect: Rect) -> DisplayList {
+        let font_size = self.fit_font_size(text, font_size, rect.size.width);
+        let width = self.text_width(text, font_size);
+        let origin = Point::new(
+            rect.origin.x + (rect.size.width - width) / 2.0,
+            rect.origin.y + (rect.size.height + self.cap_height(font_size)) / 2.0,
+        );
+        self.text_outline(text, font_size, origin)
+    }
+}
+
+/// Maps the text to glyphs and returns them with their horizontal positions,
+/// followed by the width of the line, in font units.
+///
+/// Pairs of glyphs are kerned with the `kern` feature of the `GPOS` table,
+/// or with the `kern` table of fonts that lack one.
+fn set_line(face: &Face, text: &str) -> (Vec<(GlyphId, f64)>, f64) {
+    let mut glyphs = vec![];
+    let mut x = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = face.glyph_index(c).unwrap_or_default();
+        if let Some(previous) = previous {
+            x += kerning(face, previous, glyph) as f64;
+        }
+        glyphs.push((glyph, x));
+        x += face.glyph_hor_advance(glyph).unwrap_or_default() as f64;
+        previous = Some(glyph);
+    }
+    (glyphs, x)
+}
+
+/// Returns the adjustment of the advance of the `left` glyph when it is followed
+/// by the `right` glyph, in font units.
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> i16 {
+    let tables = face.tables();
+    if let Some(gpos) = tables.gpos {
+        let kern = Tag::from_bytes(b"kern");
+        let lookups = gpos
+            .features
+            .into_iter()
+            .filter(|feature| feature.tag == kern)
+            .flat_map(|feature| feature.lookup_indices)
+            .filter_map(|index| gpos.lookups.get(index));
+        for lookup in lookups {
+            for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+                let PositioningSubtable::Pair(pair) = subtable else {
+                    continue;
+                };
+                if let Some(adjustment) = pair_kerning(&pair, left, right) {
+                    return adjustment;
+                }
+            }
+        }
+    }
+    tables
+        .kern
+        .and_then(|kern| {
+            kern.subtables
+                .into_iter()
+                .filter(|subtable| subtable.horizontal && !subtable.variable)
+                .find_map(|subtable| subtable.glyphs_kerning(left, right))
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the advance adjustment of the first glyph of a pair from a pair adjustment subtable.
+fn pair_kerning(pair: &PairAdjustment, left: GlyphId, right: GlyphId) -> Option<i16> {
+    let (first, _) = match pair {
+        PairAdjustment::Format1 { coverage, sets } => sets.get(coverage.get(left)?)?.get(right)?,
+        PairAdjustment::Format2 {
+            coverage,
+            classes,
+            matrix,
+        } => {
+            coverage.get(left)?;
+            matrix.get((classes.0.get(left), classes.1.get(right)))?
+        }
+    };
+    Some(first.x_advance)
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        EMBEDDED.clone()
+    }
+}
+
+/// Converts glyph outlines from font units, with the y-axis pointing up,
+/// to drawing operations at a given position and scale.
+struct GlyphOutliner<'d> {
+    recorder: DisplayListRecorder<'d>,
+    /// The position of the origin of the glyph on the baseline.
+    origin: Point,
+    /// The size of a font unit.
+    scale: f64,
+    start: Point,
+    current: Point,
+}
+
+impl GlyphOutliner<'_> {
+    fn point(&self, x: f32, y: f32) -> Point {
+        Point::new(
+            self.origin.x + x as f64 * self.scale,
+            self.origin.y - y as f64 * self.scale,
+        )
+    }
+}
+
+impl OutlineBuilder for GlyphOutliner<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.recorder.move_to(point);
+        (self.start, self.current) = (point, point);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.recorder.line_to(point);
+        self.current = point;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.recorder.quad_to(self.point(x1, y1), point);
+        self.current = point;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.recorder
+            .cubic_to(self.point(x1, y1), self.point(x2, y2), point);
+        self.current = point;
+    }
+
+    fn close(&mut self) {
+        // End every contour where it started, for receivers that ignore closing operations.
+        if self.current != self.start {
+            self.recorder.line_to(self.start);
+        }
+        self.recorder.close_path();
+        self.current = self.start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kerns_pairs_of_glyphs() {
+        let font = Font::embedded();
+        let width = |text| font.text_width(text, 100.0);
+        assert!(width("AV") < width("A") + width("V"));
+        assert!(width("To") < width("T") + width("o"));
+        assert_eq!(width("HH"), 2.0 * width("H"));
+    }
+
+    #[test]
+    fn fits_the_font_size_to_the_width() {
+        let font = Font::embedded();
+        let text = "https://example.com";
+        let font_size = font.fit_font_size(text, 20.0, 50.0);
+        assert!(font_size < 20.0);
+        assert!((font.text_width(text, font_size) - 50.0).abs() < 1e-9);
+        assert_eq!(font.fit_font_size(text, 20.0, 1000.0), 20.0);
+    }
+}
@@ -1,4 +1,307 @@
 /// The error correction level in a QR Code symbol.
-pub use qrcodegen::QrCodeEcc as ErrorCorrectionLevel;
+pub use crate::qrcodegen::QrCodeEcc as ErrorCorrectionLevel;
 
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::grid::ModuleGrid;
 pub use crate::rendering::geometry::Size;
+pub use crate::rendering::style::Color;
+
+/// The radii of the rounded corners, in modules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CornerRadii {
+    /// The radius of the convex corners of data modules, at most 0.5.
+    pub module: f64,
+    /// The radius of the concave corners where data modules meet, at most 0.5.
+    pub concave: f64,
+    /// The radius of the outer corners of the finder pattern rings.
+    pub finder_outer: f64,
+    /// The radius of the inner corners of the finder pattern rings.
+    pub finder_inner: f64,
+    /// The radius of the corners of the finder pattern centers.
+    pub finder_center: f64,
+}
+
+impl Default for CornerRadii {
+    fn default() -> Self {
+        Self {
+            module: 0.5,
+            concave: 0.25,
+            finder_outer: 2.1,
+            finder_inner: 1.3,
+            finder_center: 0.6,
+        }
+    }
+}
+
+impl CornerRadii {
+    /// Radii of zero, which produce square modules.
+    pub const SQUARE: Self = Self {
+        module: 0.0,
+        concave: 0.0,
+        finder_outer: 0.0,
+        finder_inner: 0.0,
+        finder_center: 0.0,
+    };
+
+    /// Checks that every radius is finite and non-negative, and that the module and
+    /// concave radii do not exceed 0.5, which the bindings require of the radii they receive.
+    pub fn validate(&self) -> Result<(), InvalidCornerRadii> {
+        let all = [
+            self.module,
+            self.concave,
+            self.finder_outer,
+            self.finder_inner,
+            self.finder_center,
+        ];
+        if all.iter().any(|r| !r.is_finite() || *r < 0.0) || self.module > 0.5 || self.concave > 0.5
+        {
+            return Err(InvalidCornerRadii);
+        }
+        Ok(())
+    }
+}
+
+/// The error type returned when [`CornerRadii`] are out of range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCornerRadii;
+
+impl Display for InvalidCornerRadii {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "corner radii must be finite and non-negative, and the module and concave radii must not exceed 0.5",
+        )
+    }
+}
+
+impl Error for InvalidCornerRadii {}
+
+/// How the dark modules of a QR Code are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ModuleShape {
+    /// Neighboring modules are merged into shapes whose corners are rounded with the corner radii.
+    #[default]
+    Connected,
+    /// Every data module is drawn on its own, which makes it a dot with a module radius of 0.5.
+    ///
+    /// The finder patterns stay connected, and inverted codes are drawn connected since
+    /// their light modules merge with the quiet zone.
+    Dots,
+}
+
+/// A region centered in the QR Code whose modules are cleared to make room for a logo.
+///
+/// The modules of the finder patterns and their separators are never cleared.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogoRegion {
+    /// The width of the region, in modules.
+    pub width: u32,
+    /// The height of the region, in modules.
+    pub height: u32,
+}
+
+impl LogoRegion {
+    /// Creates a region with the given dimensions, in modules.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    /// Returns `true` if the region does not clear any module.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Clears the modules of the grid covered by the region.
+    pub(crate) fn clear(&self, modules: &mut ModuleGrid) {
+        if self.is_empty() {
+            return;
+        }
+        let size = modules.size();
+        let (width, height) = (
+            (self.width as i32).min(size),
+            (self.height as i32).min(size),
+        );
+        let (left, top) = ((size - width) / 2, (size - height) / 2);
+        // The finder patterns and their separators occupy 8x8 modules in three corners.
+        let is_finder =
+            |x: i32, y: i32| ((x < 8 || x >= size - 8) && y < 8) || (x < 8 && y >= size - 8);
+        for y in top..top + height {
+            for x in left..left + width {
+                if !is_finder(x, y) {
+                    modules.set(x, y, false);
+                }
+            }
+        }
+    }
+}
+
+/// A physical length, which relates the units of an image to the size it is printed at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Length {
+    Millimeters(f64),
+    Inches(f64),
+    /// Typographic points, 72 to the inch.
+    Points(f64),
+}
+
+impl Length {
+    pub fn to_inches(self) -> f64 {
+        match self {
+            Length::Millimeters(mm) => mm / 25.4,
+            Length::Inches(inches) => inches,
+            Length::Points(points) => points / 72.0,
+        }
+    }
+
+    pub fn to_millimeters(self) -> f64 {
+        self.to_inches() * 25.4
+    }
+
+    /// Returns the length in the units of an image with `dpi` units per inch.
+    pub fn to_units(self, dpi: f64) -> f64 {
+        self.to_inches() * dpi
+    }
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Millimeters(mm) => write!(f, "{mm}mm"),
+            Length::Inches(inches) => write!(f, "{inches}in"),
+            Length::Points(points) => write!(f, "{points}pt"),
+        }
+    }
+}
+
+/// The error type returned when a string cannot be parsed as a [`Length`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLengthError;
+
+impl Display for ParseLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected a positive number followed by `mm`, `in` or `pt`")
+    }
+}
+
+impl Error for ParseLengthError {}
+
+impl FromStr for Length {
+    type Err = ParseLengthError;
+
+    /// Parses a length such as `0.33mm`, `0.013in` or `1pt`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.len().saturating_sub(2);
+        let (Some(value), Some(unit)) = (s.get(..split), s.get(split..)) else {
+            return Err(ParseLengthError);
+        };
+        let value = value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .ok_or(ParseLengthError)?;
+        match unit.to_ascii_lowercase().as_str() {
+            "mm" => Ok(Length::Millimeters(value)),
+            "in" => Ok(Length::Inches(value)),
+            "pt" => Ok(Length::Points(value)),
+            _ => Err(ParseLengthError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corner_radii_must_be_in_range() {
+        assert_eq!(CornerRadii::default().validate(), Ok(()));
+        assert_eq!(CornerRadii::SQUARE.validate(), Ok(()));
+        for radii in [
+            CornerRadii {
+                module: f64::NAN,
+                ..CornerRadii::default()
+            },
+            CornerRadii {
+                finder_outer: -1.0,
+                ..CornerRadii::default()
+            },
+            CornerRadii {
+                finder_center: f64::INFINITY,
+                ..CornerRadii::default()
+            },
+            CornerRadii {
+                module: 0.6,
+                ..CornerRadii::default()
+            },
+            CornerRadii {
+                concave: 0.6,
+                ..CornerRadii::default()
+            },
+        ] {
+            assert_eq!(radii.validate(), Err(InvalidCornerRadii), "{radii:?}");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn corner_radii_round_trip_through_json() {
+        let radii = CornerRadii {
+            module: 0.4,
+            concave: 0.1,
+            finder_outer: 1.5,
+            finder_inner: 1.0,
+            finder_center: 0.5,
+        };
+        let json = serde_json::to_string(&radii).unwrap();
+        assert_eq!(serde_json::from_str::<CornerRadii>(&json).unwrap(), radii);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn partial_corner_radii_take_the_default_radii() {
+        let radii: CornerRadii = serde_json::from_str(r#"{"module":0.3}"#).unwrap();
+        assert_eq!(
+            radii,
+            CornerRadii {
+                module: 0.3,
+                ..CornerRadii::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn module_shapes_round_trip_through_json() {
+        for (shape, json) in [
+            (ModuleShape::Connected, r#""connected""#),
+            (ModuleShape::Dots, r#""dots""#),
+        ] {
+            assert_eq!(serde_json::to_string(&shape).unwrap(), json);
+            assert_eq!(serde_json::from_str::<ModuleShape>(json).unwrap(), shape);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn lengths_round_trip_through_json() {
+        for (length, json) in [
+            (Length::Millimeters(0.33), r#"{"millimeters":0.33}"#),
+            (Length::Inches(0.5), r#"{"inches":0.5}"#),
+            (Length::Points(1.0), r#"{"points":1.0}"#),
+        ] {
+            assert_eq!(serde_json::to_string(&length).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Length>(json).unwrap(), length);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Bindings for JavaScript and TypeScript, built with `wasm-bindgen`.
+//!
+//! Every function takes the data to encode, either a `string` or a `Uint8Array`,
+//! and an optional `RoundedQrOptions` object.
+
+use js_sys::{Float32Array, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+
+use crate::builder::Builder;
+use crate::rendering::display_list::{
+    ArcTo, ClosePath, CubicTo, DisplayList, DisplayListOpReceiver, LineTo, MoveTo, QuadTo,
+};
+use crate::rendering::style::{Color, Style};
+use crate::rendering::svg;
+use crate::types::{ErrorCorrectionLevel, Size};
+
+/// The widest quiet zone, in modules.
+const MAX_QUIET_ZONE: u32 = crate::options::Options::MAX_QUIET_ZONE;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_OPTIONS: &'static str = r##"
+export type RoundedQrData = string | Uint8Array;
+
+export interface RoundedQrOptions {
+    /** The error correction level. Defaults to `"medium"`. */
+    ecl?: "low" | "medium" | "quartile" | "high";
+    /** The width and height of the image. Defaults to 256. */
+    size?: number;
+    /** The width of the empty border around the code, in modules, at most 100. Defaults to 0. */
+    quietZone?: number;
+    /** The color of the modules, as `#rrggbb` or `#rrggbbaa`. Defaults to `"#000000"`. */
+    foreground?: string;
+    /** The color behind the modules, or `null` for none. Defaults to `"#ffffff"`. */
+    background?: string | null;
+}
+
+/** The tags that start every operation in the array returned by `toOps`. */
+export const enum RoundedQrOp {
+    /** Followed by `x, y`. */
+    MoveTo = 0,
+    /** Followed by `x, y`. */
+    LineTo = 1,
+    /** Followed by `centerX, centerY, radius, startAngle, endAngle, clockwise`, where `clockwise` is 0 or 1. */
+    ArcTo = 2,
+    /** Not followed by any value. */
+    ClosePath = 3,
+    /** Followed by `x1, y1, x2, y2, x, y`, the two control points and the end point of a cubic Bézier curve. */
+    CubicTo = 4,
+    /** Followed by `x1, y1, x, y`, the control point and the end point of a quadratic Bézier curve. */
+    QuadTo = 5,
+}
+"##;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "RoundedQrData")]
+    pub type Data;
+
+    #[wasm_bindgen(typescript_type = "RoundedQrOptions")]
+    pub type Options;
+}
+
+/// The options of a call, with the defaults applied.
+struct ParsedOptions {
+    ecl: ErrorCorrectionLevel,
+    size: Option<f64>,
+    quiet_zone: Option<u32>,
+    style: Style,
+}
+
+fn property(object: &JsValue, key: &str) -> Result<Option<JsValue>, JsError> {
+    if object.is_undefined() || object.is_null() {
+        return Ok(None);
+    }
+    let value = Reflect::get(object, &JsValue::from_str(key))
+        .map_err(|_| JsError::new("options must be an object"))?;
+    Ok((!value.is_undefined()).then_some(value))
+}
+
+fn color(value: &JsValue, key: &str) -> Result<Color, JsError> {
+    value
+        .as_string()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| JsError::new(&format!("`{key}` must be a color such as \"#000000\"")))
+}
+
+fn parse_options(options: &JsValue) -> Result<ParsedOptions, JsError> {
+    let ecl = match property(options, "ecl")? {
+        None => ErrorCorrectionLevel::Medium,
+        Some(value) => match value.as_string().as_deref() {
+            Some("low") => ErrorCorrectionLevel::Low,
+            Some("medium") => ErrorCorrectionLevel::Medium,
+            Some("quartile") => ErrorCorrectionLevel::Quartile,
+            Some("high") => ErrorCorrectionLevel::High,
+            _ => {
+                return Err(JsError::new(
+                    "`ecl` must be one of \"low\", \"medium\", \"quartile\" or \"high\"",
+                ))
+            }
+        },
+    };
+    let size = match property(options, "size")? {
+        None => None,
+        Some(value) => match value.as_f64() {
+            Some(size) if size > 0.0 => Some(size),
+            _ => return Err(JsError::new("`size` must be a positive number")),
+        },
+    };
+    let quiet_zone = match property(options, "quietZone")? {
+        None => None,
+        Some(value) => match value.as_f64() {
+            Some(modules)
+                if (0.0..=f64::from(MAX_QUIET_ZONE)).contains(&modules)
+                    && modules.fract() == 0.0 =>
+            {
+                Some(modules as u32)
+            }
+            _ => {
+                return Err(JsError::new(&format!(
+                    "`quietZone` must be an integer from 0 to {MAX_QUIET_ZONE}"
+                )))
+            }
+        },
+    };
+    let mut style = Style::default();
+    if let Some(value) = property(options, "foreground")? {
+        style.foreground = color(&value, "foreground")?.into();
+    }
+    if let Some(value) = property(options, "background")? {
+        style.background = if value.is_null() {
+            None
+        } else {
+            Some(color(&value, "background")?.into())
+        };
+    }
+    Ok(ParsedOptions {
+        ecl,
+        size,
+        quiet_zone,
+        style,
+    })
+}
+
+/// Builds the display list of the data and returns it with the size of the image.
+fn build(data: &JsValue, options: &ParsedOptions) -> Result<(DisplayList, Size), JsError> {
+    let text = data.as_string();
+    let bytes = if text.is_none() && data.is_instance_of::<Uint8Array>() {
+        Some(Uint8Array::new(data).to_vec())
+    } else {
+        None
+    };
+    let mut builder = match (&text, &bytes) {
+        (Some(text), _) => Builder::text(text),
+        (None, Some(bytes)) => Builder::binary(bytes),
+        (None, None) => return Err(JsError::new("data must be a string or a Uint8Array")),
+    };
+    builder = builder.error_correction_level(options.ecl);
+    if let Some(size) = options.size {
+        builder = builder.size(Size::new(size, size));
+    }
+    if let Some(modules) = options.quiet_zone {
+        builder = builder.quiet_zone(modules);
+    }
+    let size = builder.get_size();
+    let display_list = builder.build()?;
+    Ok((display_list, size))
+}
+
+/// Returns a standalone SVG document of the QR Code.
+#[wasm_bindgen(js_name = toSvg)]
+pub fn to_svg(data: &Data, options: Option<Options>) -> Result<String, JsError> {
+    let options = parse_options(&options.map(JsValue::from).unwrap_or_default())?;
+    let (display_list, size) = build(data, &options)?;
+    Ok(svg::render(&display_list, size, &options.style))
+}
+
+/// Returns the outline of the QR Code as SVG path data, which can be passed to `new Path2D()`.
+#[wasm_bindgen(js_name = toPathData)]
+pub fn to_path_data(data: &Data, options: Option<Options>) -> Result<String, JsError> {
+    let options = parse_options(&options.map(JsValue::from).unwrap_or_default())?;
+    let (display_list, _) = build(data, &options)?;
+    Ok(svg::path_data(&display_list))
+}
+
+/// A receiver that flattens drawing operations to tagged values, as described by `RoundedQrOp`.
+#[derive(Default)]
+struct FlatOps(Vec<f32>);
+
+impl DisplayListOpReceiver for FlatOps {
+    fn dispatch_move_to(&mut self, op: MoveTo) {
+        self.0.extend([0.0, op.point.x as f32, op.point.y as f32]);
+    }
+
+    fn dispatch_line_to(&mut self, op: LineTo) {
+        self.0.extend([1.0, op.point.x as f32, op.point.y as f32]);
+    }
+
+    fn dispatch_arc_to(&mut self, op: ArcTo) {
+        self.0.extend([
+            2.0,
+            op.center.x as f32,
+            op.center.y as f32,
+            op.radius as f32,
+            op.start_angle,
+            op.end_angle,
+            op.clockwise as u8 as f32,
+        ]);
+    }
+
+    fn dispatch_cubic_to(&mut self, op: CubicTo) {
+        self.0.extend([
+            4.0,
+            op.control1.x as f32,
+            op.control1.y as f32,
+            op.control2.x as f32,
+            op.control2.y as f32,
+            op.point.x as f32,
+            op.point.y as f32,
+        ]);
+    }
+
+    fn dispatch_quad_to(&mut self, op: QuadTo) {
+        self.0.extend([
+            5.0,
+            op.control.x as f32,
+            op.control.y as f32,
+            op.point.x as f32,
+            op.point.y as f32,
+        ]);
+    }
+
+    fn dispatch_close_path(&mut self, _op: ClosePath) {
+        self.0.push(3.0);
+    }
+}
+
+/// Returns the drawing operations of the QR Code as a flat array of tagged values,
+/// each operation starting with a `RoundedQrOp` tag followed by its arguments.
+#[wasm_bindgen(js_name = toOps)]
+pub fn to_ops(data: &Data, options: Option<Options>) -> Result<Float32Array, JsError> {
+    let options = parse_options(&options.map(JsValue::from).unwrap_or_default())?;
+    let (display_list, _) = build(data, &options)?;
+    let mut ops = FlatOps::default();
+    display_list.present(&mut ops);
+    Ok(Float32Array::from(ops.0.as_slice()))
+}
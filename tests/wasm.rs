@@ -0,0 +1,92 @@
+//! Tests of the JavaScript bindings, run under Node with `wasm-pack test --node -- --features wasm`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use js_sys::{Float32Array, Uint8Array, JSON};
+use rounded_qr::builder::Builder;
+use rounded_qr::rendering::svg;
+use rounded_qr::wasm::{self, Data, Options};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn text(text: &str) -> Data {
+    JsValue::from_str(text).unchecked_into()
+}
+
+fn options(json: &str) -> Option<Options> {
+    Some(JSON::parse(json).unwrap().unchecked_into())
+}
+
+#[wasm_bindgen_test]
+fn to_svg_returns_a_document() {
+    let svg = wasm::to_svg(&text("rounded-qr"), None).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"width="256""#));
+    assert!(svg.contains(r##"fill="#000000""##));
+}
+
+#[wasm_bindgen_test]
+fn to_svg_applies_the_options() {
+    let svg = wasm::to_svg(
+        &text("rounded-qr"),
+        options(r##"{"size": 100, "foreground": "#ff0000", "background": null}"##),
+    )
+    .unwrap();
+    assert!(svg.contains(r#"width="100""#));
+    assert!(svg.contains(r##"fill="#ff0000""##));
+    assert!(!svg.contains("#ffffff"));
+}
+
+#[wasm_bindgen_test]
+fn to_path_data_matches_the_builder() {
+    let path_data = wasm::to_path_data(&text("rounded-qr"), options(r#"{"quietZone": 4}"#));
+    let display_list = Builder::text("rounded-qr").quiet_zone(4).build().unwrap();
+    assert_eq!(path_data.unwrap(), svg::path_data(&display_list));
+}
+
+#[wasm_bindgen_test]
+fn bytes_are_encoded_like_text() {
+    let bytes: Data = JsValue::from(Uint8Array::from(b"rounded-qr".as_slice())).unchecked_into();
+    let from_bytes = wasm::to_path_data(&bytes, None).unwrap();
+    let display_list = Builder::binary(b"rounded-qr").build().unwrap();
+    assert_eq!(from_bytes, svg::path_data(&display_list));
+}
+
+#[wasm_bindgen_test]
+fn to_ops_returns_tagged_operations() {
+    let ops: Float32Array = wasm::to_ops(&text("rounded-qr"), None).unwrap();
+    let ops = ops.to_vec();
+    assert_eq!(ops[0], 0.0, "the operations start with a move");
+    // Every tag is followed by the number of arguments described by `RoundedQrOp`.
+    let mut i = 0;
+    while i < ops.len() {
+        i += match ops[i] as u8 {
+            0 | 1 => 3,
+            2 => 7,
+            3 => 1,
+            4 => 7,
+            5 => 5,
+            tag => panic!("unknown tag {tag} at {i}"),
+        };
+    }
+    assert_eq!(i, ops.len());
+}
+
+#[wasm_bindgen_test]
+fn invalid_input_is_rejected() {
+    let number: Data = JsValue::from_f64(1.0).unchecked_into();
+    assert!(wasm::to_svg(&number, None).is_err());
+    assert!(wasm::to_svg(&text("a"), options(r#"{"ecl": "maximum"}"#)).is_err());
+    assert!(wasm::to_svg(&text("a"), options(r#"{"size": -1}"#)).is_err());
+    assert!(wasm::to_svg(&text("a"), options(r#"{"quietZone": 1.5}"#)).is_err());
+    for quiet_zone in ["-1", "101", "1e10", "4294967296"] {
+        let json = format!(r#"{{"quietZone": {quiet_zone}}}"#);
+        assert!(
+            wasm::to_svg(&text("a"), options(&json)).is_err(),
+            "{quiet_zone}"
+        );
+    }
+    assert!(wasm::to_svg(&text("a"), options(r#"{"quietZone": 100}"#)).is_ok());
+    assert!(wasm::to_svg(&text("a"), options(r#"{"foreground": "red"}"#)).is_err());
+    assert!(wasm::to_svg(&text(&"a".repeat(8000)), None).is_err());
+}